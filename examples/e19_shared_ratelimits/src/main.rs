@@ -0,0 +1,159 @@
+//! Several processes using the same token each keep their own ratelimits by
+//! default, so they race each other into 429s. This example stores the
+//! ratelimits in a file instead, which is shared by every process started with
+//! the same `RATELIMITS_PATH`.
+//!
+//! Try running it a few times at once.
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use serenity::http::ratelimiting::{Ratelimit, RatelimitKey, RatelimitStore, Route};
+use serenity::http::{Http, LightMethod};
+use serenity::{async_trait, Result};
+
+/// How long the lock file may exist before it is considered to be left behind
+/// by a process which died while holding it. The lock is only held for a single
+/// read and write of the state, so this is plenty.
+const STALE_LOCK: Duration = Duration::from_secs(5);
+
+/// The ratelimits and the bucket hashes, keyed by their `Debug` representation.
+type State = (HashMap<String, Ratelimit>, HashMap<String, String>);
+
+/// Keeps the ratelimit state in a JSON file. A lock file next to it is held
+/// while the state is read or written, so that updates are atomic across
+/// processes.
+struct FileRatelimitStore {
+    path: PathBuf,
+    lock_path: PathBuf,
+}
+
+impl FileRatelimitStore {
+    fn new(path: PathBuf) -> Self {
+        let lock_path = path.with_extension("lock");
+
+        Self {
+            path,
+            lock_path,
+        }
+    }
+
+    /// Waits for the lock file to be free, then creates it. A stale lock file
+    /// is removed instead of waited on.
+    async fn lock(&self) -> Result<FileLock> {
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&self.lock_path) {
+                Ok(_) => {
+                    return Ok(FileLock {
+                        path: self.lock_path.clone(),
+                    })
+                },
+                Err(why) if why.kind() == ErrorKind::AlreadyExists => {
+                    if self.lock_is_stale() {
+                        let _ = fs::remove_file(&self.lock_path);
+
+                        continue;
+                    }
+
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                },
+                Err(why) => return Err(why.into()),
+            }
+        }
+    }
+
+    fn lock_is_stale(&self) -> bool {
+        let modified = match fs::metadata(&self.lock_path).and_then(|meta| meta.modified()) {
+            Ok(modified) => modified,
+            Err(_) => return false,
+        };
+
+        SystemTime::now().duration_since(modified).map_or(false, |age| age > STALE_LOCK)
+    }
+
+    fn read(&self) -> Result<State> {
+        match fs::read(&self.path) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(why) if why.kind() == ErrorKind::NotFound => Ok(State::default()),
+            Err(why) => Err(why.into()),
+        }
+    }
+
+    fn write(&self, state: &State) -> Result<()> {
+        fs::write(&self.path, serde_json::to_vec(state)?)?;
+
+        Ok(())
+    }
+}
+
+/// Removes the lock file once dropped.
+struct FileLock {
+    path: PathBuf,
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[async_trait]
+impl RatelimitStore for FileRatelimitStore {
+    async fn update(
+        &self,
+        key: &RatelimitKey,
+        f: &mut (dyn for<'a> FnMut(&'a mut Ratelimit) + Send),
+    ) -> Result<()> {
+        let _lock = self.lock().await?;
+
+        let mut state = self.read()?;
+        f(state.0.entry(format!("{:?}", key)).or_default());
+
+        self.write(&state)
+    }
+
+    async fn get(&self, key: &RatelimitKey) -> Result<Option<Ratelimit>> {
+        let _lock = self.lock().await?;
+
+        Ok(self.read()?.0.remove(&format!("{:?}", key)))
+    }
+
+    async fn bucket_hash(&self, method: LightMethod, route: Route) -> Result<Option<String>> {
+        let _lock = self.lock().await?;
+
+        Ok(self.read()?.1.remove(&format!("{:?} {:?}", method, route)))
+    }
+
+    async fn set_bucket_hash(&self, method: LightMethod, route: Route, hash: String) -> Result<()> {
+        let _lock = self.lock().await?;
+
+        let mut state = self.read()?;
+        state.1.insert(format!("{:?} {:?}", method, route), hash);
+
+        self.write(&state)
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    // Configure the client with your Discord bot token in the environment.
+    let token = env::var("DISCORD_TOKEN").expect("Expected a token in the environment");
+    let path = env::var("RATELIMITS_PATH")
+        .map_or_else(|_| env::temp_dir().join("serenity-ratelimits.json"), PathBuf::from);
+
+    // Every `Http` using a store backed by the same file - in this process or
+    // in another one - now waits on the same ratelimits.
+    let mut http = Http::new(&token);
+    http.ratelimiter.set_store(Arc::new(FileRatelimitStore::new(path)));
+
+    for _ in 0..10 {
+        match http.get_current_user().await {
+            Ok(user) => println!("Fetched {}", user.tag()),
+            Err(why) => println!("Error fetching the current user: {:?}", why),
+        }
+    }
+}
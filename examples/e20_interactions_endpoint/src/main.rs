@@ -0,0 +1,55 @@
+//! Receives slash commands over HTTP instead of the gateway. Set the
+//! "Interactions Endpoint URL" of the application in the developer portal to
+//! the public address this example listens on, e.g. via a tunnel to
+//! `http://127.0.0.1:8080`.
+//!
+//! Register a `ping` command first, as shown in example 14.
+use std::env;
+
+use serenity::async_trait;
+use serenity::client::{Context, EventHandler, InteractionsEndpoint};
+use serenity::model::application::interaction::{Interaction, InteractionResponseType};
+
+struct Handler;
+
+#[async_trait]
+impl EventHandler for Handler {
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        if let Interaction::ApplicationCommand(command) = interaction {
+            let content = match command.data.name.as_str() {
+                "ping" => "Pong!".to_string(),
+                _ => "not implemented :(".to_string(),
+            };
+
+            // Sent back in the body of the webhook response, not via the API.
+            if let Err(why) = command
+                .create_interaction_response(&ctx.http, |response| {
+                    response
+                        .kind(InteractionResponseType::ChannelMessageWithSource)
+                        .interaction_response_data(|message| message.content(content))
+                })
+                .await
+            {
+                println!("Cannot respond to slash command: {}", why);
+            }
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    // Configure the endpoint with your Discord bot token and the application's
+    // public key in the environment.
+    let token = env::var("DISCORD_TOKEN").expect("Expected a token in the environment");
+    let public_key =
+        env::var("DISCORD_PUBLIC_KEY").expect("Expected a public key in the environment");
+
+    let endpoint = InteractionsEndpoint::builder(public_key, &token)
+        .event_handler(Handler)
+        .build()
+        .expect("Invalid public key");
+
+    if let Err(why) = endpoint.serve(([127, 0, 0, 1], 8080)).await {
+        println!("Endpoint error: {:?}", why);
+    }
+}
@@ -1,6 +1,8 @@
 //! A collection of events created by the client, not a part of the Discord API
 //! itself.
 
+use std::time::Duration;
+
 use super::ShardId;
 use crate::gateway::ConnectionStage;
 
@@ -25,3 +27,52 @@ pub struct ShardStageUpdateEvent {
     /// The ID of the shard that had its connection stage change.
     pub shard_id: ShardId,
 }
+
+/// An event in the lifecycle of a shard, as supervised by the
+/// [`ShardManager`].
+///
+/// Lifecycle events are received via [`ShardManager::lifecycle_events`].
+///
+/// [`ShardManager`]: super::ShardManager
+/// [`ShardManager::lifecycle_events`]: super::ShardManager::lifecycle_events
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ShardLifecycleEvent {
+    /// The ID of the shard the event is about.
+    pub shard_id: ShardId,
+    /// What happened to the shard.
+    pub kind: ShardLifecycle,
+}
+
+/// What happened to a shard in a [`ShardLifecycleEvent`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ShardLifecycle {
+    /// The shard is connecting to the gateway.
+    Connecting,
+    /// The shard identified and received a READY.
+    Ready,
+    /// The shard resumed its session.
+    Resumed,
+    /// The connection of the shard is considered zombied, as too many
+    /// consecutive heartbeats were not acknowledged.
+    Zombied {
+        /// The number of consecutive heartbeats which were not acknowledged.
+        missed_heartbeat_acks: u32,
+    },
+    /// The shard failed and is restarted after the backoff.
+    Restarted {
+        /// The number of consecutive restarts, including this one, since the
+        /// shard was last connected.
+        attempt: u32,
+        /// The duration waited before starting the shard again, which is zero
+        /// for the first consecutive restart.
+        backoff: Duration,
+    },
+    /// The shard failed too many times in a row, and is not restarted again.
+    GaveUp {
+        /// The number of consecutive restarts since the shard was last
+        /// connected.
+        attempts: u32,
+    },
+}
@@ -0,0 +1,143 @@
+use std::collections::HashSet;
+
+use crate::model::event::EventType;
+
+/// An allow-list of the dispatch events a [`ShardRunner`] deserializes and
+/// dispatches.
+///
+/// The name of every dispatch event received is checked against the list
+/// before the event is deserialized, so events which are not on the list are
+/// skipped without being parsed. This saves a considerable amount of CPU for
+/// frequent events such as [`EventType::PresenceUpdate`] and
+/// [`EventType::TypingStart`], if they are not needed.
+///
+/// Skipped events are not passed to the [`EventHandler`], the
+/// [`RawEventHandler`], the framework, collectors or the cache.
+///
+/// [`EventType::Ready`] and [`EventType::Resumed`] are always allowed, as the
+/// shard needs them to manage its session. When a voice manager is used,
+/// [`EventType::VoiceStateUpdate`] and [`EventType::VoiceServerUpdate`] are
+/// always allowed as well.
+///
+/// # Examples
+///
+/// Only receive messages and interactions, while still keeping the cache up
+/// to date:
+///
+/// ```rust,no_run
+/// # use serenity::prelude::*;
+/// # struct Handler;
+/// # impl EventHandler for Handler {}
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// use serenity::client::bridge::gateway::EventTypeFilter;
+/// use serenity::model::event::EventType;
+///
+/// let filter = EventTypeFilter::new([EventType::MessageCreate, EventType::InteractionCreate])
+///     .cache_events(true);
+///
+/// let token = std::env::var("DISCORD_TOKEN")?;
+/// let mut client = Client::builder(&token, GatewayIntents::default())
+///     .event_handler(Handler)
+///     .event_type_filter(filter)
+///     .await?;
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`ShardRunner`]: super::ShardRunner
+/// [`EventHandler`]: crate::client::EventHandler
+/// [`RawEventHandler`]: crate::client::RawEventHandler
+#[derive(Clone, Debug)]
+pub struct EventTypeFilter {
+    names: HashSet<String>,
+}
+
+impl EventTypeFilter {
+    /// Creates a filter allowing the given event types.
+    pub fn new(event_types: impl IntoIterator<Item = EventType>) -> Self {
+        let mut filter = Self {
+            names: HashSet::new(),
+        };
+
+        filter.extend([EventType::Ready, EventType::Resumed]);
+        filter.extend(event_types);
+
+        filter
+    }
+
+    /// Sets whether the events which update the cache are allowed as well,
+    /// regardless of whether they are on the list.
+    ///
+    /// **Note**: Once allowed, the events are not disallowed again by passing
+    /// `false`.
+    #[must_use]
+    pub fn cache_events(mut self, cache_events: bool) -> Self {
+        if cache_events {
+            self.extend([
+                EventType::ChannelCreate,
+                EventType::ChannelDelete,
+                EventType::ChannelPinsUpdate,
+                EventType::ChannelUpdate,
+                EventType::GuildCreate,
+                EventType::GuildDelete,
+                EventType::GuildEmojisUpdate,
+                EventType::GuildMemberAdd,
+                EventType::GuildMemberRemove,
+                EventType::GuildMemberUpdate,
+                EventType::GuildMembersChunk,
+                EventType::GuildRoleCreate,
+                EventType::GuildRoleDelete,
+                EventType::GuildRoleUpdate,
+                EventType::GuildStickersUpdate,
+                EventType::GuildUpdate,
+                EventType::MessageCreate,
+                EventType::MessageUpdate,
+                EventType::PresenceUpdate,
+                EventType::PresencesReplace,
+                EventType::ThreadCreate,
+                EventType::ThreadDelete,
+                EventType::ThreadUpdate,
+                EventType::UserUpdate,
+                EventType::VoiceStateUpdate,
+            ]);
+        }
+
+        self
+    }
+
+    /// Whether the filter allows the event with the given name, as sent in
+    /// the `t` field of a dispatch.
+    #[must_use]
+    pub fn allows(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+
+    fn extend(&mut self, event_types: impl IntoIterator<Item = EventType>) {
+        let names =
+            event_types.into_iter().filter_map(|event_type| event_type.name().map(String::from));
+
+        self.names.extend(names);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EventTypeFilter;
+    use crate::model::event::EventType;
+
+    #[test]
+    fn test_allows() {
+        let filter = EventTypeFilter::new([EventType::MessageCreate]);
+
+        assert!(filter.allows("MESSAGE_CREATE"));
+        assert!(filter.allows("READY"));
+        assert!(!filter.allows("PRESENCE_UPDATE"));
+        assert!(!filter.allows("GUILD_CREATE"));
+
+        let filter = filter.cache_events(true);
+
+        assert!(filter.allows("PRESENCE_UPDATE"));
+        assert!(filter.allows("GUILD_CREATE"));
+        assert!(!filter.allows("TYPING_START"));
+    }
+}
@@ -44,22 +44,35 @@
 
 pub mod event;
 
+mod event_type_filter;
 mod shard_manager;
 mod shard_manager_monitor;
 mod shard_messenger;
 mod shard_queuer;
 mod shard_runner;
 mod shard_runner_message;
+mod supervisor;
 
+use std::collections::HashMap;
 use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration as StdDuration;
 
+use futures::channel::mpsc::UnboundedSender as Sender;
+use tokio::sync::Mutex;
+
+pub use self::event_type_filter::EventTypeFilter;
+pub(crate) use self::shard_manager::ShardSettings;
 pub use self::shard_manager::{ShardManager, ShardManagerOptions};
 pub use self::shard_manager_monitor::{ShardManagerError, ShardManagerMonitor};
-pub use self::shard_messenger::ShardMessenger;
+pub(crate) use self::shard_messenger::CollectGuildMembers;
+pub use self::shard_messenger::{GuildMembers, ShardMessenger};
 pub use self::shard_queuer::ShardQueuer;
 pub use self::shard_runner::{ShardRunner, ShardRunnerOptions};
 pub use self::shard_runner_message::{ChunkGuildFilter, ShardRunnerMessage};
+pub(crate) use self::supervisor::ShardLifecycleHub;
+pub use self::supervisor::SupervisorPolicy;
 use crate::gateway::ConnectionStage;
 
 /// A message either for a [`ShardManager`] or a [`ShardRunner`].
@@ -152,6 +165,33 @@ pub struct ShardRunnerInfo {
     pub stage: ConnectionStage,
 }
 
+/// The handle given to the runners of a new set of shards started by
+/// [`ShardManager::reshard`], with which they report whether they warmed up.
+#[derive(Clone, Debug)]
+pub(crate) struct ShardWarmUp {
+    /// The generation of the new shards.
+    generation: u64,
+    /// The map to insert the information about the new runners into.
+    runners: Arc<Mutex<HashMap<ShardId, ShardRunnerInfo>>>,
+    /// The channel to report over whether a shard received its READY and all
+    /// of its guilds, or failed.
+    tx: Sender<(ShardId, bool)>,
+    /// Whether the new set of shards was abandoned, in which case shards of
+    /// it which are yet to start are not started, and those which started
+    /// too late to be shut down shut themselves down.
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ShardWarmUp {
+    fn report(&self, shard_id: ShardId, ready: bool) {
+        drop(self.tx.unbounded_send((shard_id, ready)));
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
 impl AsRef<ShardMessenger> for ShardRunnerInfo {
     fn as_ref(&self) -> &ShardMessenger {
         &self.runner_tx
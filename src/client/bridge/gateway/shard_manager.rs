@@ -1,26 +1,41 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 
 use futures::channel::mpsc::{self, UnboundedReceiver as Receiver, UnboundedSender as Sender};
 use futures::StreamExt;
 use tokio::sync::{Mutex, RwLock};
-use tokio::time::timeout;
+use tokio::time::{sleep, timeout, Duration};
 use tracing::{info, instrument, warn};
 use typemap_rev::TypeMap;
 
+use super::event::{ShardLifecycle, ShardLifecycleEvent};
+use super::shard_queuer::StartSchedule;
 use super::{
+    EventTypeFilter,
+    ShardClientMessage,
     ShardId,
+    ShardLifecycleHub,
     ShardManagerMessage,
     ShardManagerMonitor,
     ShardQueuer,
     ShardQueuerMessage,
     ShardRunnerInfo,
+    ShardWarmUp,
+    SupervisorPolicy,
 };
 #[cfg(feature = "voice")]
 use crate::client::bridge::voice::VoiceGatewayManager;
 use crate::client::{EventHandler, RawEventHandler};
 #[cfg(feature = "framework")]
 use crate::framework::Framework;
+use crate::gateway::{
+    GatewayError,
+    InterMessage,
+    ShardSession,
+    TransportCompression,
+    TransportEncoding,
+};
 use crate::internal::prelude::*;
 use crate::internal::tokio::spawn_named;
 use crate::model::gateway::GatewayIntents;
@@ -104,6 +119,9 @@ pub struct ShardManager {
     /// need to. Instead prefer to use methods on this struct that are provided
     /// where possible.
     pub runners: Arc<Mutex<HashMap<ShardId, ShardRunnerInfo>>>,
+    /// The sessions to resume when starting shards, and those saved by shards
+    /// shut down with a resumable close code.
+    sessions: Arc<Mutex<HashMap<ShardId, ShardSession>>>,
     /// The index of the first shard to initialize, 0-indexed.
     shard_index: u64,
     /// The number of shards to initialize.
@@ -112,17 +130,49 @@ pub struct ShardManager {
     shard_total: u64,
     shard_queuer: Sender<ShardQueuerMessage>,
     shard_shutdown: Receiver<ShardId>,
+    supervisor_policy: SupervisorPolicy,
+    lifecycle: ShardLifecycleHub,
+    /// The number of consecutive restarts per shard since it was last
+    /// connected.
+    restart_attempts: HashMap<ShardId, u32>,
+    /// The generation of the shards which dispatch events, which is
+    /// incremented by every [`Self::reshard`].
+    live_generation: Arc<AtomicU64>,
+    /// The generation of the newest set of shards started by
+    /// [`Self::reshard`], which might have been abandoned.
+    last_generation: u64,
+    warm_starts: Sender<(ShardId, ShardId, ShardWarmUp)>,
 }
 
 impl ShardManager {
     /// Creates a new shard manager, returning both the manager and a monitor
     /// for usage in a separate thread.
     pub async fn new(opt: ShardManagerOptions<'_>) -> (Arc<Mutex<Self>>, ShardManagerMonitor) {
+        Self::with_settings(opt, ShardSettings::default()).await
+    }
+
+    /// Creates a new shard manager like [`Self::new`], with the settings of
+    /// the shards given to the [`ClientBuilder`].
+    ///
+    /// [`ClientBuilder`]: crate::client::ClientBuilder
+    pub(crate) async fn with_settings(
+        opt: ShardManagerOptions<'_>,
+        settings: ShardSettings,
+    ) -> (Arc<Mutex<Self>>, ShardManagerMonitor) {
         let (thread_tx, thread_rx) = mpsc::unbounded();
         let (shard_queue_tx, shard_queue_rx) = mpsc::unbounded();
+        let (warm_starts_tx, warm_starts_rx) = mpsc::unbounded();
 
         let runners = Arc::new(Mutex::new(HashMap::new()));
+        let sessions = settings
+            .sessions
+            .into_iter()
+            .map(|session| (ShardId(session.shard_info[0]), session))
+            .collect();
+        let sessions = Arc::new(Mutex::new(sessions));
         let (shutdown_send, shutdown_recv) = mpsc::unbounded();
+        let lifecycle = ShardLifecycleHub::default();
+        let live_generation = Arc::new(AtomicU64::new(0));
 
         let mut shard_queuer = ShardQueuer {
             data: Arc::clone(opt.data),
@@ -131,15 +181,24 @@ impl ShardManager {
             #[cfg(feature = "framework")]
             framework: Arc::clone(opt.framework),
             last_start: None,
+            schedule: StartSchedule::default(),
             manager_tx: thread_tx.clone(),
             queue: VecDeque::new(),
             runners: Arc::clone(&runners),
+            sessions: Arc::clone(&sessions),
             rx: shard_queue_rx,
             #[cfg(feature = "voice")]
             voice_manager: opt.voice_manager.clone(),
             ws_url: Arc::clone(opt.ws_url),
             cache_and_http: Arc::clone(opt.cache_and_http),
             intents: opt.intents,
+            compression: settings.compression,
+            encoding: settings.encoding,
+            event_type_filter: settings.event_type_filter,
+            supervisor_policy: settings.supervisor_policy.clone(),
+            lifecycle: lifecycle.clone(),
+            live_generation: Arc::clone(&live_generation),
+            warm_starts: warm_starts_rx,
         };
 
         spawn_named("shard_queuer::run", async move {
@@ -154,6 +213,13 @@ impl ShardManager {
             shard_total: opt.shard_total,
             shard_shutdown: shutdown_recv,
             runners,
+            sessions,
+            supervisor_policy: settings.supervisor_policy,
+            lifecycle,
+            restart_attempts: HashMap::new(),
+            live_generation,
+            last_generation: 0,
+            warm_starts: warm_starts_tx,
         }));
 
         (Arc::clone(&manager), ShardManagerMonitor {
@@ -194,6 +260,7 @@ impl ShardManager {
     /// This will shutdown all existing shards.
     ///
     /// This will _not_ instantiate the new shards.
+    /// Use [`Self::reshard`] to replace the shards without downtime instead.
     #[instrument(skip(self))]
     pub async fn set_shards(&mut self, index: u64, init: u64, total: u64) {
         self.shutdown_all().await;
@@ -203,6 +270,133 @@ impl ShardManager {
         self.shard_total = total;
     }
 
+    /// Replaces the shards with a new set of shards, without interrupting the
+    /// dispatch of events.
+    ///
+    /// The new shards are started alongside the current ones, but do not
+    /// dispatch events yet. Once every new shard received its READY and all
+    /// of its guilds, dispatching is handed over to the new shards at once,
+    /// and the current shards are shut down. Until then, the current shards
+    /// keep dispatching events as usual.
+    ///
+    /// If not every new shard became ready within the `timeout`, or one of
+    /// them failed, the new shards are shut down and the current ones are
+    /// kept.
+    ///
+    /// **Note**: The manager stays locked while the new shards warm up, so
+    /// restarts of failing current shards are delayed until resharding
+    /// finished. Restarts which are still waiting for their backoff once the
+    /// new shards are handed over to are dropped. A voice manager is not told
+    /// about the new total number of shards.
+    ///
+    /// # Examples
+    ///
+    /// Reshard to the number of shards recommended by Discord:
+    ///
+    /// ```rust,no_run
+    /// # use serenity::prelude::*;
+    /// # async fn run(client: Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::time::Duration;
+    ///
+    /// let shards = client.cache_and_http.http.get_bot_gateway().await?.shards;
+    ///
+    /// client.shard_manager.lock().await.reshard(0, shards, shards, Duration::from_secs(600)).await?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::ReshardFailed`] if the new shards did not all
+    /// become ready.
+    #[instrument(skip(self))]
+    pub async fn reshard(
+        &mut self,
+        index: u64,
+        init: u64,
+        total: u64,
+        timeout_after: Duration,
+    ) -> Result<()> {
+        info!("Resharding to shards {} to {} of {}", index, index + init, total);
+
+        // The shards of an abandoned set may still be running, so generations
+        // are never reused.
+        self.last_generation += 1;
+
+        let generation = self.last_generation;
+        let runners = Arc::new(Mutex::new(HashMap::new()));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (tx, mut rx) = mpsc::unbounded();
+
+        let warm_up = ShardWarmUp {
+            generation,
+            runners: Arc::clone(&runners),
+            tx,
+            cancelled: Arc::clone(&cancelled),
+        };
+
+        for shard_id in index..index + init {
+            let msg = (ShardId(shard_id), ShardId(total), warm_up.clone());
+
+            drop(self.warm_starts.unbounded_send(msg));
+        }
+
+        drop(warm_up);
+
+        let warm = async {
+            let mut ready = HashSet::new();
+
+            while let Some((shard_id, ok)) = rx.next().await {
+                if !ok {
+                    warn!("Shard {} failed to warm up", shard_id);
+
+                    return false;
+                }
+
+                ready.insert(shard_id);
+
+                if ready.len() as u64 == init {
+                    return true;
+                }
+            }
+
+            false
+        };
+
+        if !timeout(timeout_after, warm).await.unwrap_or(false) {
+            warn!("Abandoning resharding to {} shards", total);
+
+            // The new shards which are yet to start are not started, and those
+            // which start after being retired shut themselves down.
+            cancelled.store(true, Ordering::SeqCst);
+
+            // The new shards are not live, and thus never restarted.
+            retire(std::mem::take(&mut *runners.lock().await));
+
+            return Err(Error::Gateway(GatewayError::ReshardFailed));
+        }
+
+        // Hand dispatching over to the new shards, which stops the current
+        // shards dispatching at the same time.
+        self.live_generation.store(generation, Ordering::SeqCst);
+
+        let retired = {
+            let mut current = self.runners.lock().await;
+            std::mem::replace(&mut *current, std::mem::take(&mut *runners.lock().await))
+        };
+
+        info!("Resharded to {} shards, shutting down {} previous shards", total, retired.len());
+
+        retire(retired);
+
+        self.shard_index = index;
+        self.shard_init = init;
+        self.shard_total = total;
+        self.restart_attempts.clear();
+
+        Ok(())
+    }
+
     /// Restarts a shard runner.
     ///
     /// This sends a shutdown signal to a shard's associated [`ShardRunner`],
@@ -243,12 +437,115 @@ impl ShardManager {
     pub async fn restart(&mut self, shard_id: ShardId) {
         info!("Restarting shard {}", shard_id);
         self.shutdown(shard_id, 4000).await;
+        // The shard is restarted with a new session.
+        self.sessions.lock().await.remove(&shard_id);
 
         let shard_total = self.shard_total;
 
         self.boot([shard_id, ShardId(shard_total)]);
     }
 
+    /// Restarts a shard runner which failed, according to the
+    /// [`SupervisorPolicy`].
+    ///
+    /// The shard is started again after the backoff for the number of
+    /// consecutive restarts since it was last connected, or not at all if
+    /// that number exceeds the maximum.
+    #[instrument(skip(self))]
+    pub(super) async fn restart_supervised(&mut self, shard_id: ShardId) {
+        let attempt = {
+            let attempts = self.restart_attempts.entry(shard_id).or_insert(0);
+            *attempts += 1;
+            *attempts
+        };
+
+        if self.supervisor_policy.gives_up(attempt) {
+            warn!("Giving up on shard {} after {} restarts", shard_id, attempt - 1);
+
+            self.shutdown(shard_id, 1000).await;
+            self.sessions.lock().await.remove(&shard_id);
+            self.lifecycle.emit(shard_id, ShardLifecycle::GaveUp {
+                attempts: attempt - 1,
+            });
+
+            return;
+        }
+
+        let backoff = self.supervisor_policy.backoff(attempt);
+
+        info!("Restarting shard {} in {:?} (attempt {})", shard_id, backoff, attempt);
+        self.shutdown(shard_id, 4000).await;
+        // The shard is restarted with a new session.
+        self.sessions.lock().await.remove(&shard_id);
+        self.lifecycle.emit(shard_id, ShardLifecycle::Restarted {
+            attempt,
+            backoff,
+        });
+
+        if backoff.is_zero() {
+            self.boot([shard_id, ShardId(self.shard_total)]);
+
+            return;
+        }
+
+        let shard_queuer = self.shard_queuer.clone();
+        let live_generation = Arc::clone(&self.live_generation);
+        let generation = live_generation.load(Ordering::SeqCst);
+        let msg = ShardQueuerMessage::Start(shard_id, ShardId(self.shard_total));
+
+        spawn_named("shard_manager::restart_supervised", async move {
+            sleep(backoff).await;
+
+            // The shards were replaced by resharding in the meantime, so the
+            // shard is not restarted.
+            if live_generation.load(Ordering::SeqCst) != generation {
+                info!("Dropping restart of shard {}, as the shards were resharded", shard_id);
+
+                return;
+            }
+
+            drop(shard_queuer.unbounded_send(msg));
+        });
+    }
+
+    /// Resets the number of consecutive restarts of a shard, once it is
+    /// connected.
+    pub(super) fn shard_connected(&mut self, shard_id: ShardId) {
+        self.restart_attempts.remove(&shard_id);
+    }
+
+    /// Returns a stream of the [`ShardLifecycleEvent`]s of the shards, such as
+    /// them connecting, becoming zombied or being restarted.
+    ///
+    /// This can be used to alert on shards which fail repeatedly.
+    ///
+    /// # Examples
+    ///
+    /// Log every restart of a shard:
+    ///
+    /// ```rust,no_run
+    /// # use serenity::prelude::*;
+    /// # async fn run(client: Client) {
+    /// use futures::StreamExt;
+    /// use serenity::client::bridge::gateway::event::ShardLifecycle;
+    ///
+    /// let mut events = client.shard_manager.lock().await.lifecycle_events();
+    ///
+    /// while let Some(event) = events.next().await {
+    ///     if let ShardLifecycle::Restarted {
+    ///         attempt, ..
+    ///     } = event.kind
+    ///     {
+    ///         println!("Shard {} restarted (attempt {})", event.shard_id, attempt);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn lifecycle_events(&self) -> Receiver<ShardLifecycleEvent> {
+        self.lifecycle.subscribe()
+    }
+
     /// Returns the [`ShardId`]s of the shards that have been instantiated and
     /// currently have a valid [`ShardRunner`].
     ///
@@ -301,6 +598,46 @@ impl ShardManager {
     /// over the [`Self::shutdown`] method.
     #[instrument(skip(self))]
     pub async fn shutdown_all(&mut self) {
+        self.shutdown_all_with(1000).await;
+    }
+
+    /// Shuts down all shards like [`Self::shutdown_all`], but without ending
+    /// their sessions, and returns the sessions so they can be resumed.
+    ///
+    /// The sessions can be serialized and given to a client started in
+    /// another process via [`ClientBuilder::shard_sessions`], whose shards
+    /// then resume them instead of identifying. This allows restarting a bot
+    /// without missing events, as long as it restarts before Discord expires
+    /// the sessions.
+    ///
+    /// # Examples
+    ///
+    /// Save the sessions to a file when shutting down:
+    ///
+    /// ```rust,no_run
+    /// # use serenity::prelude::*;
+    /// # async fn run(client: Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let sessions = client.shard_manager.lock().await.shutdown_all_resumable().await;
+    ///
+    /// std::fs::write("sessions.json", serde_json::to_string(&sessions)?)?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ClientBuilder::shard_sessions`]: crate::client::ClientBuilder::shard_sessions
+    #[instrument(skip(self))]
+    pub async fn shutdown_all_resumable(&mut self) -> Vec<ShardSession> {
+        // Any close code other than 1000 and 1001 keeps the sessions valid.
+        self.shutdown_all_with(4000).await;
+
+        let mut sessions =
+            self.sessions.lock().await.drain().map(|(_, session)| session).collect::<Vec<_>>();
+        sessions.sort_by_key(|session| session.shard_info[0]);
+
+        sessions
+    }
+
+    async fn shutdown_all_with(&mut self, code: u16) {
         let keys = {
             let runners = self.runners.lock().await;
 
@@ -314,7 +651,7 @@ impl ShardManager {
         info!("Shutting down all shards");
 
         for shard_id in keys {
-            self.shutdown(shard_id, 1000).await;
+            self.shutdown(shard_id, code).await;
         }
 
         drop(self.shard_queuer.unbounded_send(ShardQueuerMessage::Shutdown));
@@ -331,6 +668,17 @@ impl ShardManager {
     }
 }
 
+/// Shuts down the runners of shards which are not live, without waiting for
+/// them to finish.
+fn retire(runners: HashMap<ShardId, ShardRunnerInfo>) {
+    for (shard_id, runner) in runners {
+        let shutdown = ShardManagerMessage::Shutdown(shard_id, 1000);
+        let msg = InterMessage::Client(Box::new(ShardClientMessage::Manager(shutdown)));
+
+        drop(runner.runner_tx.tx.unbounded_send(msg));
+    }
+}
+
 impl Drop for ShardManager {
     /// A custom drop implementation to clean up after the manager.
     ///
@@ -359,3 +707,172 @@ pub struct ShardManagerOptions<'a> {
     pub cache_and_http: &'a Arc<CacheAndHttp>,
     pub intents: GatewayIntents,
 }
+
+/// The settings of the shards which are set through the [`ClientBuilder`]
+/// rather than the [`ShardManagerOptions`].
+///
+/// [`ClientBuilder`]: crate::client::ClientBuilder
+#[derive(Default)]
+pub(crate) struct ShardSettings {
+    /// The compression of the payloads received by the shards.
+    pub compression: TransportCompression,
+    /// The encoding of the payloads exchanged by the shards.
+    pub encoding: TransportEncoding,
+    /// The sessions for the shards to resume instead of identifying.
+    pub sessions: Vec<ShardSession>,
+    /// The dispatch events for the shards to deserialize, or `None` for all
+    /// of them.
+    pub event_type_filter: Option<EventTypeFilter>,
+    /// The policy for supervising the health of the shards.
+    pub supervisor_policy: SupervisorPolicy,
+}
+
+#[cfg(all(test, feature = "mock_gateway"))]
+mod tests {
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use http_crate::response::Builder;
+    use reqwest::{Request, Response};
+    use tokio::sync::{Mutex, RwLock};
+    use tokio::time::{sleep, timeout, Duration};
+    use typemap_rev::TypeMap;
+
+    use super::{ShardManager, ShardManagerOptions};
+    #[cfg(feature = "cache")]
+    use crate::cache::Cache;
+    use crate::client::bridge::gateway::ShardId;
+    #[cfg(feature = "framework")]
+    use crate::client::Context;
+    #[cfg(feature = "framework")]
+    use crate::framework::Framework;
+    use crate::gateway::mock::MockGateway;
+    use crate::gateway::GatewayError;
+    use crate::http::{HttpBuilder, Transport};
+    use crate::json::json;
+    #[cfg(feature = "framework")]
+    use crate::model::channel::Message;
+    use crate::model::gateway::GatewayIntents;
+    use crate::{CacheAndHttp, Error};
+
+    #[cfg(feature = "framework")]
+    struct NoFramework;
+
+    #[cfg(feature = "framework")]
+    #[async_trait]
+    impl Framework for NoFramework {
+        async fn dispatch(&self, _: Context, _: Message) {}
+    }
+
+    /// Answers every request with the bot's gateway, allowing 16 shards to
+    /// identify at once.
+    struct BotGateway;
+
+    #[async_trait]
+    impl Transport for BotGateway {
+        async fn execute(&self, _: Request) -> crate::Result<Response> {
+            let body = json!({
+                "url": "wss://gateway.discord.gg",
+                "shards": 1,
+                "session_start_limit": {
+                    "total": 1000,
+                    "remaining": 1000,
+                    "reset_after": 86_400_000,
+                    "max_concurrency": 16,
+                },
+            });
+
+            Ok(Builder::new().status(200).body(body.to_string()).unwrap().into())
+        }
+    }
+
+    /// Starts a manager of shard 0 of 1 connected to the gateway, along with
+    /// its monitor.
+    async fn start_manager(gateway: &MockGateway) -> Arc<Mutex<ShardManager>> {
+        #[cfg(feature = "framework")]
+        let framework: Arc<dyn Framework + Send + Sync> = Arc::new(NoFramework);
+        let cache_and_http = Arc::new(CacheAndHttp {
+            #[cfg(feature = "cache")]
+            cache: Arc::new(Cache::default()),
+            http: Arc::new(HttpBuilder::new("token").transport(BotGateway).build()),
+        });
+
+        let (manager, mut monitor) = ShardManager::new(ShardManagerOptions {
+            data: &Arc::new(RwLock::new(TypeMap::new())),
+            event_handler: &None,
+            raw_event_handler: &None,
+            #[cfg(feature = "framework")]
+            framework: &framework,
+            shard_index: 0,
+            shard_init: 1,
+            shard_total: 1,
+            #[cfg(feature = "voice")]
+            voice_manager: &None,
+            ws_url: &Arc::new(Mutex::new(gateway.url())),
+            cache_and_http: &cache_and_http,
+            intents: GatewayIntents::empty(),
+        })
+        .await;
+
+        tokio::spawn(async move { drop(monitor.run().await) });
+
+        manager
+    }
+
+    /// Whether the gateway received an IDENTIFY with the shard information.
+    async fn identified(gateway: &MockGateway, shard_info: [u64; 2]) -> bool {
+        gateway.received().await.iter().any(|payload| {
+            payload["op"].as_u64() == Some(2) && payload["d"]["shard"] == json!(shard_info)
+        })
+    }
+
+    async fn wait_identified(gateway: &MockGateway, shard_info: [u64; 2]) {
+        let identify = async {
+            while !identified(gateway, shard_info).await {
+                sleep(Duration::from_millis(10)).await;
+            }
+        };
+
+        timeout(Duration::from_secs(5), identify).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reshard() {
+        let gateway = MockGateway::builder().token("token").start().await.unwrap();
+        let manager = start_manager(&gateway).await;
+
+        manager.lock().await.initialize().unwrap();
+        wait_identified(&gateway, [0, 1]).await;
+
+        // The new shard becomes ready right away, and is handed over to.
+        let mut guard = manager.lock().await;
+        guard.reshard(1, 1, 2, Duration::from_secs(5)).await.unwrap();
+
+        assert!(identified(&gateway, [1, 2]).await);
+        assert_eq!(guard.shards_instantiated().await, vec![ShardId(1)]);
+        assert_eq!(guard.live_generation.load(Ordering::SeqCst), 1);
+        assert_eq!(guard.shard_total, 2);
+
+        // Shard 1 can only identify again 5 seconds after it last did, long
+        // after resharding was abandoned, and is then not started anymore.
+        // Pausing the clock lets that wait elapse without sleeping for real.
+        tokio::time::pause();
+
+        match guard.reshard(1, 1, 3, Duration::from_millis(100)).await {
+            Err(Error::Gateway(GatewayError::ReshardFailed)) => {},
+            other => panic!("expected resharding to fail, got {:?}", other),
+        }
+
+        drop(guard);
+        sleep(Duration::from_secs(6)).await;
+
+        let guard = manager.lock().await;
+
+        assert!(!identified(&gateway, [1, 3]).await);
+        assert_eq!(gateway.connections().await, 2);
+        assert_eq!(guard.shards_instantiated().await, vec![ShardId(1)]);
+        assert_eq!(guard.live_generation.load(Ordering::SeqCst), 1);
+        assert_eq!(guard.shard_total, 2);
+    }
+}
@@ -7,6 +7,7 @@ use tracing::{debug, instrument, warn};
 
 use super::{ShardManager, ShardManagerMessage};
 use crate::client::bridge::gateway::ShardId;
+use crate::gateway::ConnectionStage;
 
 /// The shard manager monitor monitors the shard manager and performs actions
 /// on it as received.
@@ -60,7 +61,7 @@ impl ShardManagerMonitor {
         while let Some(value) = self.rx.next().await {
             match value {
                 ShardManagerMessage::Restart(shard_id) => {
-                    self.manager.lock().await.restart(shard_id).await;
+                    self.manager.lock().await.restart_supervised(shard_id).await;
                     drop(self.shutdown.unbounded_send(shard_id));
                 },
                 ShardManagerMessage::ShardUpdate {
@@ -68,7 +69,12 @@ impl ShardManagerMonitor {
                     latency,
                     stage,
                 } => {
-                    let manager = self.manager.lock().await;
+                    let mut manager = self.manager.lock().await;
+
+                    if stage == ConnectionStage::Connected {
+                        manager.shard_connected(id);
+                    }
+
                     let mut runners = manager.runners.lock().await;
 
                     if let Some(runner) = runners.get_mut(&id) {
@@ -1,5 +1,10 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
 use async_tungstenite::tungstenite::Message;
-use futures::channel::mpsc::{TrySendError, UnboundedSender as Sender};
+use futures::channel::mpsc::{self, TrySendError, UnboundedSender as Sender};
+use futures::{Stream, StreamExt};
 
 use super::{ChunkGuildFilter, ShardClientMessage, ShardRunnerMessage};
 #[cfg(feature = "collector")]
@@ -10,8 +15,41 @@ use crate::collector::{
     ModalInteractionFilter,
     ReactionFilter,
 };
-use crate::gateway::InterMessage;
+use crate::gateway::{CollectGuildMembersRequest, GatewayError, InterMessage};
+use crate::model::event::GuildMembersChunkEvent;
 use crate::model::prelude::*;
+use crate::Error;
+
+/// The number of the next nonce generated for a member chunk request.
+static NEXT_CHUNK_NONCE: AtomicU64 = AtomicU64::new(0);
+
+/// The members of a [`Guild`] received in response to a request made via
+/// [`ShardMessenger::chunk_guild_members`].
+///
+/// [`Guild`]: crate::model::guild::Guild
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct GuildMembers {
+    /// The members received, by user ID.
+    pub members: HashMap<UserId, Member>,
+    /// The IDs of the requested users which were not found in the guild.
+    pub not_found: Vec<UserId>,
+    /// The presences of the members received, if they were requested.
+    pub presences: HashMap<UserId, Presence>,
+}
+
+/// A request for the member chunks received in response to a request made
+/// via [`ShardMessenger::chunk_guild_stream`], to be sent to the runner of the
+/// shard.
+#[derive(Clone, Debug)]
+pub(crate) struct CollectGuildMembers {
+    pub(crate) guild_id: GuildId,
+    pub(crate) limit: Option<u16>,
+    pub(crate) presences: bool,
+    pub(crate) filter: ChunkGuildFilter,
+    pub(crate) nonce: String,
+    pub(crate) tx: Sender<GuildMembersChunkEvent>,
+}
 
 /// A lightweight wrapper around an mpsc sender.
 ///
@@ -119,6 +157,118 @@ impl ShardMessenger {
         }));
     }
 
+    /// Requests that a [`Guild`] be chunked, returning a stream of the
+    /// [`GuildMembersChunkEvent`]s received in response.
+    ///
+    /// A nonce identifying the chunks is generated, and the stream ends once
+    /// every chunk has been received, or the shard shuts down. Requesting
+    /// presences requires the [`GatewayIntents::GUILD_PRESENCES`] intent.
+    ///
+    /// The chunks are still dispatched to the event handlers and the cache as
+    /// usual. Refer to [`Self::chunk_guild`] for more information.
+    ///
+    /// [`Guild`]: crate::model::guild::Guild
+    pub fn chunk_guild_stream(
+        &self,
+        guild_id: GuildId,
+        limit: Option<u16>,
+        presences: bool,
+        filter: ChunkGuildFilter,
+    ) -> impl Stream<Item = GuildMembersChunkEvent> {
+        let (tx, rx) = mpsc::unbounded();
+        let nonce = format!("serenity-{}", NEXT_CHUNK_NONCE.fetch_add(1, Ordering::Relaxed));
+
+        let request = CollectGuildMembers {
+            guild_id,
+            limit,
+            presences,
+            filter,
+            nonce,
+            tx,
+        };
+
+        // If the shard is gone, dropping the sender ends the stream.
+        drop(self.tx.unbounded_send(InterMessage::CollectGuildMembers(
+            CollectGuildMembersRequest(Box::new(request)),
+        )));
+
+        rx
+    }
+
+    /// Requests that a [`Guild`] be chunked, and waits until all chunks
+    /// received in response are collected.
+    ///
+    /// Refer to [`Self::chunk_guild_stream`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// Look up the members of a guild whose names start with `"do"`:
+    ///
+    /// ```rust,no_run
+    /// # use serenity::prelude::*;
+    /// # use serenity::model::id::GuildId;
+    /// # async fn run(ctx: Context) -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::time::Duration;
+    ///
+    /// use serenity::client::bridge::gateway::ChunkGuildFilter;
+    ///
+    /// let guild_members = ctx
+    ///     .shard
+    ///     .chunk_guild_members(
+    ///         GuildId(81384788765712384),
+    ///         Some(100),
+    ///         false,
+    ///         ChunkGuildFilter::Query("do".to_owned()),
+    ///         Duration::from_secs(10),
+    ///     )
+    ///     .await?;
+    ///
+    /// println!("Found {} members", guild_members.members.len());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::ChunkGuildIncomplete`] if not all chunks were
+    /// received before the `timeout` elapsed or the shard shut down.
+    ///
+    /// [`Guild`]: crate::model::guild::Guild
+    pub async fn chunk_guild_members(
+        &self,
+        guild_id: GuildId,
+        limit: Option<u16>,
+        presences: bool,
+        filter: ChunkGuildFilter,
+        timeout: Duration,
+    ) -> crate::Result<GuildMembers> {
+        let mut chunks = self.chunk_guild_stream(guild_id, limit, presences, filter);
+        let mut guild_members = GuildMembers::default();
+
+        let collect = async {
+            let mut received = 0;
+
+            while let Some(chunk) = chunks.next().await {
+                received += 1;
+
+                guild_members.members.extend(chunk.members);
+                guild_members.not_found.extend(chunk.not_found);
+                guild_members.presences.extend(chunk.presences);
+
+                if received >= chunk.chunk_count {
+                    return true;
+                }
+            }
+
+            false
+        };
+
+        match tokio::time::timeout(timeout, collect).await {
+            Ok(true) => Ok(guild_members),
+            _ => Err(Error::Gateway(GatewayError::ChunkGuildIncomplete)),
+        }
+    }
+
     /// Sets the user's current activity, if any.
     ///
     /// Other presence settings are maintained.
@@ -1,32 +1,46 @@
 use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
 
 use futures::channel::mpsc::{UnboundedReceiver as Receiver, UnboundedSender as Sender};
-use futures::StreamExt;
+use futures::future::Either;
+use futures::{stream, StreamExt};
 use tokio::sync::{Mutex, RwLock};
-use tokio::time::{sleep, timeout, Duration, Instant};
+use tokio::time::{sleep_until, timeout, Duration, Instant};
 use tracing::{debug, info, instrument, warn};
 use typemap_rev::TypeMap;
 
 use super::{
+    EventTypeFilter,
     ShardClientMessage,
     ShardId,
+    ShardLifecycleHub,
     ShardManagerMessage,
     ShardMessenger,
     ShardQueuerMessage,
     ShardRunner,
     ShardRunnerInfo,
     ShardRunnerOptions,
+    ShardWarmUp,
+    SupervisorPolicy,
 };
+use crate::client::bridge::gateway::event::ShardLifecycle;
 #[cfg(feature = "voice")]
 use crate::client::bridge::voice::VoiceGatewayManager;
 use crate::client::{EventHandler, RawEventHandler};
 #[cfg(feature = "framework")]
 use crate::framework::Framework;
-use crate::gateway::{ConnectionStage, InterMessage, Shard};
+use crate::gateway::{
+    ConnectionStage,
+    InterMessage,
+    Shard,
+    ShardSession,
+    TransportCompression,
+    TransportEncoding,
+};
 use crate::internal::prelude::*;
 use crate::internal::tokio::spawn_named;
-use crate::model::gateway::GatewayIntents;
+use crate::model::gateway::{GatewayIntents, SessionStartLimit};
 use crate::CacheAndHttp;
 
 const WAIT_BETWEEN_BOOTS_IN_SECONDS: u64 = 5;
@@ -34,9 +48,15 @@ const WAIT_BETWEEN_BOOTS_IN_SECONDS: u64 = 5;
 /// The shard queuer is a simple loop that runs indefinitely to manage the
 /// startup of shards.
 ///
+/// Shards are identified in the buckets given by the bot's
+/// [`SessionStartLimit::max_concurrency`], so that up to that many shards
+/// are started every 5 seconds. The number of remaining session starts is
+/// tracked as well, and once it runs out, starting shards waits until the
+/// limit resets.
+///
 /// A shard queuer instance _should_ be run in its own thread, due to the
-/// blocking nature of the loop itself as well as a 5 second thread sleep
-/// between shard starts.
+/// blocking nature of the loop itself as well as the sleeps between shard
+/// starts.
 pub struct ShardQueuer {
     /// A copy of [`Client::data`] to be given to runners for contextual
     /// dispatching.
@@ -58,8 +78,12 @@ pub struct ShardQueuer {
     pub framework: Arc<dyn Framework + Send + Sync>,
     /// The instant that a shard was last started.
     ///
-    /// This is used to determine how long to wait between shard IDENTIFYs.
+    /// The wait between shard IDENTIFYs is determined per identify bucket,
+    /// as shards in different buckets may identify at the same time.
     pub last_start: Option<Instant>,
+    /// When shards may start, per identify bucket and according to the
+    /// session start limit.
+    pub(super) schedule: StartSchedule,
     /// A copy of the sender channel to communicate with the
     /// [`ShardManagerMonitor`].
     ///
@@ -71,6 +95,9 @@ pub struct ShardQueuer {
     pub queue: VecDeque<(u64, u64)>,
     /// A copy of the map of shard runners.
     pub runners: Arc<Mutex<HashMap<ShardId, ShardRunnerInfo>>>,
+    /// The sessions to resume when starting shards, and those saved by shards
+    /// shut down with a resumable close code.
+    pub(super) sessions: Arc<Mutex<HashMap<ShardId, ShardSession>>>,
     /// A receiver channel for the shard queuer to be told to start shards.
     pub rx: Receiver<ShardQueuerMessage>,
     /// A copy of the client's voice manager.
@@ -80,6 +107,25 @@ pub struct ShardQueuer {
     pub ws_url: Arc<Mutex<String>>,
     pub cache_and_http: Arc<CacheAndHttp>,
     pub intents: GatewayIntents,
+    /// The compression of the payloads received by the shards.
+    pub(super) compression: TransportCompression,
+    /// The encoding of the payloads exchanged by the shards.
+    pub(super) encoding: TransportEncoding,
+    /// The dispatch events for the shards to deserialize, or `None` for all
+    /// of them.
+    pub(super) event_type_filter: Option<EventTypeFilter>,
+    /// The policy for supervising the health of the shards.
+    pub(super) supervisor_policy: SupervisorPolicy,
+    /// The hub to send the lifecycle events of the shards to.
+    pub(super) lifecycle: ShardLifecycleHub,
+    /// The generation of the shards which dispatch events. Shards started
+    /// via [`ShardQueuerMessage::Start`] belong to it.
+    pub(super) live_generation: Arc<AtomicU64>,
+    /// The shards of a new set of shards to start, which do not dispatch
+    /// events until the set is handed over to by [`ShardManager::reshard`].
+    ///
+    /// [`ShardManager::reshard`]: super::ShardManager::reshard
+    pub(super) warm_starts: Receiver<(ShardId, ShardId, ShardWarmUp)>,
 }
 
 impl ShardQueuer {
@@ -90,16 +136,25 @@ impl ShardQueuer {
     ///
     /// If a [`ShardQueuerMessage::Start`] is received, this will:
     ///
-    /// 1. Check how much time has passed since the last shard was started
-    /// 2. If the amount of time is less than the ratelimit, it will sleep until
-    /// that time has passed
-    /// 3. Start the shard by ID
+    /// 1. Wait for the session start limit to reset, if no session starts
+    ///    are remaining
+    /// 2. Check how much time has passed since the last shard in the same
+    ///    identify bucket was started
+    /// 3. If the amount of time is less than the ratelimit, it will sleep until
+    ///    that time has passed
+    /// 4. Start the shard by ID
+    ///
+    /// The shards of a new set of shards started by [`ShardManager::reshard`]
+    /// are started the same way, unless the set was abandoned in the
+    /// meantime.
     ///
     /// If a [`ShardQueuerMessage::Shutdown`] is received, this will return and
     /// the loop will be over.
     ///
     /// **Note**: This should be run in its own thread due to the blocking
     /// nature of the loop.
+    ///
+    /// [`ShardManager::reshard`]: super::ShardManager::reshard
     #[instrument(skip(self))]
     pub async fn run(&mut self) {
         // The duration to timeout from reads over the Rx channel. This can be
@@ -108,25 +163,36 @@ impl ShardQueuer {
         const TIMEOUT: Duration = Duration::from_secs(WAIT_BETWEEN_BOOTS_IN_SECONDS);
 
         loop {
-            match timeout(TIMEOUT, self.rx.next()).await {
-                Ok(Some(ShardQueuerMessage::Shutdown)) => {
+            let next = {
+                let messages = (&mut self.rx).map(Either::Left);
+                let warm_starts = (&mut self.warm_starts).map(Either::Right);
+
+                timeout(TIMEOUT, stream::select(messages, warm_starts).next()).await
+            };
+
+            match next {
+                Ok(Some(Either::Left(ShardQueuerMessage::Shutdown))) => {
                     debug!("[Shard Queuer] Received to shutdown.");
                     self.shutdown_runners().await;
 
                     break;
                 },
-                Ok(Some(ShardQueuerMessage::ShutdownShard(shard, code))) => {
+                Ok(Some(Either::Left(ShardQueuerMessage::ShutdownShard(shard, code)))) => {
                     debug!("[Shard Queuer] Received to shutdown shard {} with {}.", shard.0, code);
                     self.shutdown(shard, code).await;
                 },
-                Ok(Some(ShardQueuerMessage::Start(id, total))) => {
+                Ok(Some(Either::Left(ShardQueuerMessage::Start(id, total)))) => {
                     debug!("[Shard Queuer] Received to start shard {} of {}.", id.0, total.0);
-                    self.checked_start(id.0, total.0).await;
+                    self.checked_start(id.0, total.0, None).await;
+                },
+                Ok(Some(Either::Right((id, total, warm_up)))) => {
+                    debug!("[Shard Queuer] Received to warm up shard {} of {}.", id.0, total.0);
+                    self.checked_start(id.0, total.0, Some(warm_up)).await;
                 },
                 Ok(None) => break,
                 Err(_) => {
                     if let Some((id, total)) = self.queue.pop_front() {
-                        self.checked_start(id, total).await;
+                        self.checked_start(id, total, None).await;
                     }
                 },
             }
@@ -134,54 +200,149 @@ impl ShardQueuer {
     }
 
     #[instrument(skip(self))]
-    async fn check_last_start(&mut self) {
-        let instant = match self.last_start {
-            Some(instant) => instant,
+    async fn fetch_session_start_limit(&mut self) {
+        match self.cache_and_http.http.get_bot_gateway().await {
+            Ok(gateway) => self.schedule.set_limit(gateway.session_start_limit, Instant::now()),
+            Err(why) => {
+                warn!("[Shard Queuer] Failed to fetch session start limit: {:?}", why);
+            },
+        }
+    }
+
+    #[instrument(skip(self))]
+    async fn check_session_start_limit(&mut self) {
+        if self.schedule.is_stale(Instant::now()) {
+            self.fetch_session_start_limit().await;
+        }
+
+        let reset = match self.schedule.exhausted_until() {
+            Some(reset) => reset,
             None => return,
         };
 
-        // We must wait 5 seconds between IDENTIFYs to avoid session
-        // invalidations.
-        let duration = Duration::from_secs(WAIT_BETWEEN_BOOTS_IN_SECONDS);
-        let elapsed = instant.elapsed();
+        warn!(
+            "[Shard Queuer] No session starts remaining, waiting {:?} for the limit to reset",
+            reset.saturating_duration_since(Instant::now()),
+        );
 
-        if elapsed >= duration {
-            return;
-        }
-
-        let to_sleep = duration - elapsed;
+        sleep_until(reset).await;
+        self.fetch_session_start_limit().await;
+    }
 
-        sleep(to_sleep).await;
+    #[instrument(skip(self))]
+    async fn check_last_start(&mut self, shard_id: u64) {
+        // We must wait 5 seconds between IDENTIFYs in the same bucket to
+        // avoid session invalidations.
+        if let Some(instant) = self.schedule.identify_at(shard_id, Instant::now()) {
+            sleep_until(instant).await;
+        }
     }
 
     #[instrument(skip(self))]
-    async fn checked_start(&mut self, id: u64, total: u64) {
+    async fn checked_start(&mut self, id: u64, total: u64, warm_up: Option<ShardWarmUp>) {
         debug!("[Shard Queuer] Checked start for shard {} out of {}", id, total);
-        self.check_last_start().await;
 
-        if let Err(why) = self.start(id, total).await {
+        // Resuming a session does not count as a session start.
+        let resuming = warm_up.is_none()
+            && self
+                .sessions
+                .lock()
+                .await
+                .get(&ShardId(id))
+                .map_or(false, |session| session.shard_info == [id, total]);
+
+        if !resuming {
+            self.check_session_start_limit().await;
+            self.check_last_start(id).await;
+        }
+
+        // The shards of an abandoned set of shards are not started anymore.
+        if warm_up.as_ref().map_or(false, ShardWarmUp::is_cancelled) {
+            debug!("[Shard Queuer] Not starting shard {} of abandoned shards", id);
+
+            return;
+        }
+
+        if let Err(why) = self.start(id, total, warm_up.clone()).await {
             warn!("[Shard Queuer] Err starting shard {}: {:?}", id, why);
-            info!("[Shard Queuer] Re-queueing start of shard {}", id);
 
-            self.queue.push_back((id, total));
+            // A new set of shards is abandoned instead if one of them fails.
+            if let Some(warm_up) = warm_up {
+                warm_up.report(ShardId(id), false);
+            } else {
+                info!("[Shard Queuer] Re-queueing start of shard {}", id);
+
+                self.queue.push_back((id, total));
+            }
         }
 
-        self.last_start = Some(Instant::now());
+        if !resuming {
+            let now = Instant::now();
+
+            self.schedule.record_start(id, now);
+            self.last_start = Some(now);
+        }
     }
 
     #[instrument(skip(self))]
-    async fn start(&mut self, shard_id: u64, shard_total: u64) -> Result<()> {
+    async fn start(
+        &mut self,
+        shard_id: u64,
+        shard_total: u64,
+        warm_up: Option<ShardWarmUp>,
+    ) -> Result<()> {
         let shard_info = [shard_id, shard_total];
 
-        let mut shard = Shard::new(
-            Arc::clone(&self.ws_url),
-            &self.cache_and_http.http.token,
-            shard_info,
-            self.intents,
-        )
-        .await?;
+        self.lifecycle.emit(ShardId(shard_id), ShardLifecycle::Connecting);
+
+        // The shards of a new set always identify, and leave the sessions of
+        // the current shards alone.
+        let session = match warm_up {
+            Some(_) => None,
+            None => self.sessions.lock().await.remove(&ShardId(shard_id)),
+        };
+
+        // A session can only be resumed with the same shard information.
+        let resumed = match session.filter(|session| session.shard_info == shard_info) {
+            Some(session) => {
+                info!("[Shard Queuer] Resuming session of shard {}", shard_id);
+
+                let shard = Shard::resuming(
+                    Arc::clone(&self.ws_url),
+                    &self.cache_and_http.http.token,
+                    self.intents,
+                    self.compression,
+                    self.encoding,
+                    session,
+                )
+                .await;
+
+                if let Err(why) = &shard {
+                    warn!("[Shard Queuer] Failed to resume shard {}: {:?}", shard_id, why);
+                }
+
+                shard.ok()
+            },
+            None => None,
+        };
+
+        let mut shard = match resumed {
+            Some(shard) => shard,
+            None => {
+                Shard::with_transport(
+                    Arc::clone(&self.ws_url),
+                    &self.cache_and_http.http.token,
+                    shard_info,
+                    self.intents,
+                    self.compression,
+                    self.encoding,
+                )
+                .await?
+            },
+        };
 
         shard.set_http(Arc::clone(&self.cache_and_http.http));
+        shard.set_max_missed_heartbeat_acks(self.supervisor_policy.max_missed_heartbeat_acks);
 
         let mut runner = ShardRunner::new(ShardRunnerOptions {
             data: Arc::clone(&self.data),
@@ -194,7 +355,12 @@ impl ShardQueuer {
             voice_manager: self.voice_manager.clone(),
             shard,
             cache_and_http: Arc::clone(&self.cache_and_http),
-        });
+        })
+        .sessions(Arc::clone(&self.sessions))
+        .event_type_filter(self.event_type_filter.clone())
+        .lifecycle(self.lifecycle.clone())
+        .live_generation(Arc::clone(&self.live_generation))
+        .warm_up(warm_up.clone());
 
         let runner_info = ShardRunnerInfo {
             latency: None,
@@ -207,7 +373,8 @@ impl ShardQueuer {
             debug!("[ShardRunner {:?}] Stopping", runner.shard.shard_info());
         });
 
-        self.runners.lock().await.insert(ShardId(shard_id), runner_info);
+        let runners = warm_up.as_ref().map_or(&self.runners, |warm_up| &warm_up.runners);
+        runners.lock().await.insert(ShardId(shard_id), runner_info);
 
         Ok(())
     }
@@ -256,3 +423,131 @@ impl ShardQueuer {
         }
     }
 }
+
+/// When shards may start, according to the bot's session start limit and
+/// the identify buckets.
+#[derive(Debug, Default)]
+pub(super) struct StartSchedule {
+    /// The instants that a shard was last started, per identify bucket, which
+    /// is the shard ID modulo the `max_concurrency`.
+    last_starts: HashMap<u64, Instant>,
+    /// The session start limit, fetched before starting the first shard, and
+    /// updated as shards are started.
+    limit: Option<SessionStartLimit>,
+    /// The instant at which the session start limit resets.
+    reset: Option<Instant>,
+}
+
+impl StartSchedule {
+    /// The number of shards that may identify every 5 seconds.
+    fn max_concurrency(&self) -> u64 {
+        self.limit.as_ref().map_or(1, |limit| limit.max_concurrency.max(1))
+    }
+
+    /// Whether the session start limit needs to be fetched, as it never was
+    /// or it has reset since.
+    fn is_stale(&self, now: Instant) -> bool {
+        self.reset.map_or(true, |reset| reset <= now)
+    }
+
+    fn set_limit(&mut self, limit: SessionStartLimit, now: Instant) {
+        self.reset = Some(now + Duration::from_millis(limit.reset_after));
+        self.limit = Some(limit);
+    }
+
+    /// The instant until which no session may start, if no session starts
+    /// are remaining.
+    fn exhausted_until(&self) -> Option<Instant> {
+        match (&self.limit, self.reset) {
+            (Some(limit), Some(reset)) if limit.remaining == 0 => Some(reset),
+            _ => None,
+        }
+    }
+
+    /// The instant at which the shard may identify, if a shard in the same
+    /// identify bucket was started less than 5 seconds before.
+    fn identify_at(&self, shard_id: u64, now: Instant) -> Option<Instant> {
+        let bucket = shard_id % self.max_concurrency();
+        let last_start = self.last_starts.get(&bucket)?;
+
+        Some(*last_start + Duration::from_secs(WAIT_BETWEEN_BOOTS_IN_SECONDS))
+            .filter(|instant| *instant > now)
+    }
+
+    /// Records a shard starting a new session.
+    fn record_start(&mut self, shard_id: u64, now: Instant) {
+        let bucket = shard_id % self.max_concurrency();
+        self.last_starts.insert(bucket, now);
+
+        if let Some(limit) = &mut self.limit {
+            limit.remaining = limit.remaining.saturating_sub(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::time::{Duration, Instant};
+
+    use super::StartSchedule;
+    use crate::model::gateway::SessionStartLimit;
+
+    fn limit(remaining: u64, max_concurrency: u64) -> SessionStartLimit {
+        SessionStartLimit {
+            remaining,
+            reset_after: 60_000,
+            total: 1000,
+            max_concurrency,
+        }
+    }
+
+    #[test]
+    fn test_identify_buckets() {
+        let now = Instant::now();
+        let mut schedule = StartSchedule::default();
+        schedule.set_limit(limit(1000, 4), now);
+
+        for shard_id in 0..4 {
+            assert_eq!(schedule.identify_at(shard_id, now), None);
+            schedule.record_start(shard_id, now);
+        }
+
+        // Shards 4 to 7 are in the buckets of shards 0 to 3.
+        let later = now + Duration::from_secs(2);
+        assert_eq!(schedule.identify_at(4, later), Some(now + Duration::from_secs(5)));
+        assert_eq!(schedule.identify_at(7, later), Some(now + Duration::from_secs(5)));
+        assert_eq!(schedule.identify_at(4, now + Duration::from_secs(5)), None);
+    }
+
+    #[test]
+    fn test_identify_without_limit() {
+        let now = Instant::now();
+        let mut schedule = StartSchedule::default();
+        schedule.record_start(0, now);
+
+        // Without a known limit, all shards are in a single bucket.
+        assert_eq!(schedule.identify_at(1, now), Some(now + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_session_start_limit_wait() {
+        let now = Instant::now();
+        let mut schedule = StartSchedule::default();
+        assert!(schedule.is_stale(now));
+
+        schedule.set_limit(limit(2, 1), now);
+        assert!(!schedule.is_stale(now));
+
+        schedule.record_start(0, now);
+        assert_eq!(schedule.exhausted_until(), None);
+
+        schedule.record_start(1, now + Duration::from_secs(5));
+        let reset = now + Duration::from_secs(60);
+        assert_eq!(schedule.exhausted_until(), Some(reset));
+        assert!(schedule.is_stale(reset));
+
+        // The limit is fetched again once it has reset.
+        schedule.set_limit(limit(1000, 1), reset);
+        assert_eq!(schedule.exhausted_until(), None);
+    }
+}
@@ -1,4 +1,6 @@
 use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use async_tungstenite::tungstenite;
@@ -6,13 +8,21 @@ use async_tungstenite::tungstenite::error::Error as TungsteniteError;
 use async_tungstenite::tungstenite::protocol::frame::CloseFrame;
 use futures::channel::mpsc::{self, UnboundedReceiver as Receiver, UnboundedSender as Sender};
 use futures::{SinkExt, StreamExt};
-use serde::Deserialize;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tracing::{debug, error, info, instrument, trace, warn};
 use typemap_rev::TypeMap;
 
-use super::event::{ClientEvent, ShardStageUpdateEvent};
-use super::{ShardClientMessage, ShardId, ShardManagerMessage, ShardRunnerMessage};
+use super::event::{ClientEvent, ShardLifecycle, ShardStageUpdateEvent};
+use super::{
+    CollectGuildMembers,
+    EventTypeFilter,
+    ShardClientMessage,
+    ShardId,
+    ShardLifecycleHub,
+    ShardManagerMessage,
+    ShardRunnerMessage,
+    ShardWarmUp,
+};
 #[cfg(feature = "voice")]
 use crate::client::bridge::voice::VoiceGatewayManager;
 use crate::client::dispatch::{dispatch, DispatchEvent};
@@ -29,12 +39,21 @@ use crate::collector::{
 };
 #[cfg(feature = "framework")]
 use crate::framework::Framework;
-use crate::gateway::{GatewayError, InterMessage, ReconnectType, Shard, ShardAction};
+use crate::gateway::{
+    CommandKind,
+    GatewayError,
+    InterMessage,
+    ReconnectType,
+    Shard,
+    ShardAction,
+    ShardSession,
+};
 use crate::internal::prelude::*;
-use crate::internal::ws_impl::{ReceiverExt, SenderExt};
+use crate::internal::ws_impl::DispatchHeader;
 #[cfg(feature = "collector")]
 use crate::model::application::interaction::Interaction;
-use crate::model::event::{Event, GatewayEvent};
+use crate::model::event::{Event, GatewayEvent, GuildMembersChunkEvent};
+use crate::model::id::GuildId;
 use crate::CacheAndHttp;
 
 /// A runner for managing a [`Shard`] and its respective WebSocket client.
@@ -45,11 +64,26 @@ pub struct ShardRunner {
     #[cfg(feature = "framework")]
     framework: Arc<dyn Framework + Send + Sync>,
     manager_tx: Sender<ShardManagerMessage>,
+    // the sessions of shards shut down with a resumable close code
+    sessions: Arc<Mutex<HashMap<ShardId, ShardSession>>>,
     // channel to receive messages from the shard manager and dispatches
     runner_rx: Receiver<InterMessage>,
     // channel to send messages to the shard runner from the shard manager
     runner_tx: Sender<InterMessage>,
     pub(crate) shard: Shard,
+    event_type_filter: Option<EventTypeFilter>,
+    // the requests for member chunks awaiting their chunks, by nonce
+    member_chunk_requests: HashMap<String, MemberChunkRequest>,
+    lifecycle: ShardLifecycleHub,
+    // the generation of this shard, and the generation of the shards which
+    // dispatch events
+    generation: u64,
+    live_generation: Arc<AtomicU64>,
+    // the handle to report whether the shard warmed up with, if it belongs
+    // to a new set of shards which is not yet dispatching events
+    warm_up: Option<ShardWarmUp>,
+    // the guilds of a warming up shard which have not been received yet
+    pending_guilds: Option<HashSet<GuildId>>,
     #[cfg(feature = "voice")]
     voice_manager: Option<Arc<dyn VoiceGatewayManager + Send + Sync + 'static>>,
     cache_and_http: Arc<CacheAndHttp>,
@@ -79,7 +113,15 @@ impl ShardRunner {
             #[cfg(feature = "framework")]
             framework: opt.framework,
             manager_tx: opt.manager_tx,
+            sessions: Arc::default(),
             shard: opt.shard,
+            event_type_filter: None,
+            member_chunk_requests: HashMap::new(),
+            lifecycle: ShardLifecycleHub::default(),
+            generation: 0,
+            live_generation: Arc::default(),
+            warm_up: None,
+            pending_guilds: None,
             #[cfg(feature = "voice")]
             voice_manager: opt.voice_manager,
             cache_and_http: opt.cache_and_http,
@@ -96,6 +138,55 @@ impl ShardRunner {
         }
     }
 
+    /// Sets the map to save the shard's session to when it is shut down with
+    /// a close code that keeps it resumable.
+    pub(super) fn sessions(mut self, sessions: Arc<Mutex<HashMap<ShardId, ShardSession>>>) -> Self {
+        self.sessions = sessions;
+
+        self
+    }
+
+    /// Sets the dispatch events to deserialize, or `None` to deserialize all
+    /// of them.
+    pub(super) fn event_type_filter(mut self, event_type_filter: Option<EventTypeFilter>) -> Self {
+        self.event_type_filter = event_type_filter;
+
+        self
+    }
+
+    /// Sets the hub to send the lifecycle events of the shard to.
+    pub(super) fn lifecycle(mut self, lifecycle: ShardLifecycleHub) -> Self {
+        self.lifecycle = lifecycle;
+
+        self
+    }
+
+    /// Sets the generation of the shards which dispatch events, which the
+    /// shard belongs to unless it is warming up.
+    pub(super) fn live_generation(mut self, live_generation: Arc<AtomicU64>) -> Self {
+        if self.warm_up.is_none() {
+            self.generation = live_generation.load(Ordering::SeqCst);
+        }
+
+        self.live_generation = live_generation;
+
+        self
+    }
+
+    /// Sets the handle to report whether the shard warmed up with, if it
+    /// belongs to a new set of shards started by [`ShardManager::reshard`].
+    ///
+    /// [`ShardManager::reshard`]: super::ShardManager::reshard
+    pub(super) fn warm_up(mut self, warm_up: Option<ShardWarmUp>) -> Self {
+        self.generation = match &warm_up {
+            Some(warm_up) => warm_up.generation,
+            None => self.live_generation.load(Ordering::SeqCst),
+        };
+        self.warm_up = warm_up;
+
+        self
+    }
+
     /// Starts the runner's loop to receive events.
     ///
     /// This runs a loop that performs the following in each iteration:
@@ -129,10 +220,39 @@ impl ShardRunner {
                 return Ok(());
             }
 
+            // A shard of an abandoned set of shards which started too late to
+            // be shut down by the manager shuts itself down.
+            if self.warm_up.as_ref().map_or(false, ShardWarmUp::is_cancelled) {
+                info!("[ShardRunner {:?}] Resharding was abandoned", self.shard.shard_info());
+
+                self.checked_shutdown(ShardId(self.shard.shard_info()[0]), 1000).await;
+
+                return Ok(());
+            }
+
+            self.check_handover().await;
+
+            if let Err(why) = self.shard.send_queued_commands().await {
+                debug!(
+                    "[ShardRunner {:?}] Failed to send queued commands: {:?}",
+                    self.shard.shard_info(),
+                    why
+                );
+            }
+
             // check heartbeat
             if !self.shard.check_heartbeat().await {
                 warn!("[ShardRunner {:?}] Error heartbeating", self.shard.shard_info(),);
 
+                if self.shard.is_zombied() {
+                    self.lifecycle.emit(
+                        ShardId(self.shard.shard_info()[0]),
+                        ShardLifecycle::Zombied {
+                            missed_heartbeat_acks: self.shard.missed_heartbeat_acks(),
+                        },
+                    );
+                }
+
                 return self.request_restart().await;
             }
 
@@ -140,7 +260,7 @@ impl ShardRunner {
             let (event, action, successful) = self.recv_event().await?;
             let post = self.shard.stage();
 
-            if post != pre {
+            if post != pre && self.is_live() {
                 self.update_manager();
 
                 let e = ClientEvent::ShardStageUpdate(ShardStageUpdateEvent {
@@ -184,12 +304,29 @@ impl ShardRunner {
             }
 
             if let Some(event) = event {
-                #[cfg(feature = "collector")]
-                {
-                    self.handle_filters(&event);
+                match event {
+                    Event::Ready(_) => self
+                        .lifecycle
+                        .emit(ShardId(self.shard.shard_info()[0]), ShardLifecycle::Ready),
+                    Event::Resumed(_) => {
+                        self.lifecycle
+                            .emit(ShardId(self.shard.shard_info()[0]), ShardLifecycle::Resumed);
+                    },
+                    _ => {},
                 }
 
-                self.dispatch(DispatchEvent::Model(event)).await;
+                self.track_warm_up(&event);
+
+                if self.is_live() {
+                    self.handle_member_chunks(&event);
+
+                    #[cfg(feature = "collector")]
+                    {
+                        self.handle_filters(&event);
+                    }
+
+                    self.dispatch(DispatchEvent::Model(event)).await;
+                }
             }
 
             if !successful && !self.shard.stage().is_connecting() {
@@ -199,6 +336,103 @@ impl ShardRunner {
         }
     }
 
+    /// Whether the shard belongs to the generation of shards which dispatch
+    /// events.
+    ///
+    /// Shards of a new set started by [`ShardManager::reshard`] are not live
+    /// until the set is handed over to, and the shards of the previous set
+    /// stop being live at the same time.
+    ///
+    /// [`ShardManager::reshard`]: super::ShardManager::reshard
+    fn is_live(&self) -> bool {
+        self.live_generation.load(Ordering::SeqCst) == self.generation
+    }
+
+    /// Reports a warming up shard as ready once it received its READY and
+    /// every guild listed in it.
+    fn track_warm_up(&mut self, event: &Event) {
+        let warm_up = match &self.warm_up {
+            Some(warm_up) => warm_up,
+            None => return,
+        };
+
+        if let Event::Ready(ready) = event {
+            self.pending_guilds = Some(ready.ready.guilds.iter().map(|guild| guild.id).collect());
+        }
+
+        let pending_guilds = match &mut self.pending_guilds {
+            Some(pending_guilds) => pending_guilds,
+            None => return,
+        };
+
+        match event {
+            Event::Ready(_) => {},
+            Event::GuildCreate(event) => {
+                pending_guilds.remove(&event.guild.id);
+            },
+            // Guilds which are unavailable due to an outage are not waited for.
+            Event::GuildDelete(event) => {
+                pending_guilds.remove(&event.guild.id);
+            },
+            _ => return,
+        }
+
+        if pending_guilds.is_empty() {
+            warm_up.report(ShardId(self.shard.shard_info()[0]), true);
+            self.pending_guilds = None;
+        }
+    }
+
+    /// Finishes warming up once the shard's set was handed over to, taking
+    /// over from the shard of the previous set.
+    #[cfg_attr(not(feature = "voice"), allow(clippy::unused_async))]
+    async fn check_handover(&mut self) {
+        if self.warm_up.is_none() || !self.is_live() {
+            return;
+        }
+
+        info!("[ShardRunner {:?}] Handed over to", self.shard.shard_info());
+
+        self.warm_up = None;
+        self.pending_guilds = None;
+
+        #[cfg(feature = "cache")]
+        {
+            *self.cache_and_http.cache.shard_count.write() = self.shard.shard_info()[1];
+        }
+
+        #[cfg(feature = "voice")]
+        if let Some(voice_manager) = &self.voice_manager {
+            voice_manager.register_shard(self.shard.shard_info()[0], self.runner_tx.clone()).await;
+        }
+
+        self.update_manager();
+    }
+
+    /// Sends member chunks to the requests awaiting them, forgetting each
+    /// request once all of its chunks have been received.
+    fn handle_member_chunks(&mut self, event: &Event) {
+        let chunk = match event {
+            Event::GuildMembersChunk(chunk) => chunk,
+            _ => return,
+        };
+
+        let nonce = match &chunk.nonce {
+            Some(nonce) => nonce,
+            None => return,
+        };
+
+        if let Some(request) = self.member_chunk_requests.get_mut(nonce) {
+            request.received += 1;
+
+            if request.tx.unbounded_send(chunk.clone()).is_err()
+                || request.received >= chunk.chunk_count
+            {
+                self.member_chunk_requests.remove(nonce);
+            }
+        }
+    }
+
     /// Lets filters check the `event` to send them to collectors if the `event`
     /// is accepted by them.
     #[cfg(feature = "collector")]
@@ -311,7 +545,12 @@ impl ShardRunner {
             }
         }
 
-        // Inform the manager that shutdown for this shard has finished.
+        // Inform the manager that shutdown for this shard has finished. The
+        // manager does not wait for shards which are not live.
+        if !self.is_live() {
+            return false;
+        }
+
         if let Err(why) = self.manager_tx.unbounded_send(ShardManagerMessage::ShutdownFinished(id))
         {
             warn!(
@@ -354,6 +593,14 @@ impl ShardRunner {
                     self.checked_shutdown(id, 4000).await
                 },
                 ShardClientMessage::Manager(ShardManagerMessage::Shutdown(id, code)) => {
+                    // Closing with any other code than 1000 or 1001 keeps the
+                    // session resumable, so it is saved to be exported.
+                    if id.0 == self.shard.shard_info()[0] && code != 1000 && code != 1001 {
+                        if let Some(session) = self.shard.session() {
+                            self.sessions.lock().await.insert(id, session);
+                        }
+                    }
+
                     self.checked_shutdown(id, code).await
                 },
                 ShardClientMessage::Manager(ShardManagerMessage::ShutdownAll) => {
@@ -467,7 +714,30 @@ impl ShardRunner {
             },
             InterMessage::Json(value) => {
                 // Value must be forwarded over the websocket
-                self.shard.client.send_json(&value).await.is_ok()
+                self.shard.send_command(CommandKind::Other, value).await.is_ok()
+            },
+            InterMessage::CollectGuildMembers(request) => {
+                let CollectGuildMembers {
+                    guild_id,
+                    limit,
+                    presences,
+                    filter,
+                    nonce,
+                    tx,
+                } = *request.0;
+
+                // Forget requests whose receivers were dropped, e.g. after
+                // timing out.
+                self.member_chunk_requests.retain(|_, request| !request.tx.is_closed());
+                self.member_chunk_requests.insert(nonce.clone(), MemberChunkRequest {
+                    tx,
+                    received: 0,
+                });
+
+                self.shard
+                    .chunk_guild_with_presences(guild_id, limit, presences, filter, Some(&nonce))
+                    .await
+                    .is_ok()
             },
         }
     }
@@ -533,12 +803,58 @@ impl ShardRunner {
         Ok(true)
     }
 
+    /// Whether the payload is a dispatch which is not allowed by the
+    /// [`EventTypeFilter`], and thus should not be deserialized.
+    fn skips_dispatch(&self, header: &DispatchHeader) -> bool {
+        let filter = match &self.event_type_filter {
+            Some(filter) => filter,
+            None => return false,
+        };
+
+        let name = match header.t.as_deref() {
+            Some(name) => name,
+            None => return false,
+        };
+
+        if name == "GUILD_MEMBERS_CHUNK" && !self.member_chunk_requests.is_empty() {
+            return false;
+        }
+
+        // A warming up shard waits for its guilds to arrive.
+        if self.warm_up.is_some() && matches!(name, "GUILD_CREATE" | "GUILD_DELETE") {
+            return false;
+        }
+
+        #[cfg(feature = "voice")]
+        if self.voice_manager.is_some()
+            && matches!(name, "VOICE_STATE_UPDATE" | "VOICE_SERVER_UPDATE")
+        {
+            return false;
+        }
+
+        !filter.allows(name)
+    }
+
     /// Returns a received event, as well as whether reading the potentially
     /// present event was successful.
     #[instrument(skip(self))]
     async fn recv_event(&mut self) -> Result<(Option<Event>, Option<ShardAction>, bool)> {
-        let gw_event = match self.shard.client.recv_json().await {
-            Ok(Some(value)) => GatewayEvent::deserialize(value).map(Some).map_err(From::from),
+        let gw_event = match self.shard.recv_json().await {
+            Ok(Some(payload)) => {
+                if self.event_type_filter.is_some() {
+                    let header = payload.dispatch_header();
+
+                    if self.skips_dispatch(&header) {
+                        if let Some(seq) = header.s {
+                            self.shard.update_seq(seq);
+                        }
+
+                        return Ok((None, None, true));
+                    }
+                }
+
+                payload.deserialize::<GatewayEvent>().map(Some)
+            },
             Ok(None) => Ok(None),
             Err(Error::Tungstenite(TungsteniteError::Io(_))) => {
                 debug!("Attempting to auto-reconnect");
@@ -621,7 +937,9 @@ impl ShardRunner {
         #[cfg(feature = "voice")]
         {
             if let Ok(GatewayEvent::Dispatch(_, ref event)) = event {
-                self.handle_voice_event(event).await;
+                if self.is_live() {
+                    self.handle_voice_event(event).await;
+                }
             }
         }
 
@@ -635,6 +953,17 @@ impl ShardRunner {
 
     #[instrument(skip(self))]
     async fn request_restart(&mut self) -> Result<()> {
+        // Shards which are not live are not restarted. A new set of shards is
+        // abandoned instead if one of them fails, and the shards of a previous
+        // set are being shut down.
+        if !self.is_live() {
+            if let Some(warm_up) = &self.warm_up {
+                warm_up.report(ShardId(self.shard.shard_info()[0]), false);
+            }
+
+            return Ok(());
+        }
+
         self.update_manager();
 
         debug!("[ShardRunner {:?}] Requesting restart", self.shard.shard_info(),);
@@ -655,6 +984,10 @@ impl ShardRunner {
 
     #[instrument(skip(self))]
     fn update_manager(&self) {
+        if !self.is_live() {
+            return;
+        }
+
         drop(self.manager_tx.unbounded_send(ShardManagerMessage::ShardUpdate {
             id: ShardId(self.shard.shard_info()[0]),
             latency: self.shard.latency(),
@@ -663,6 +996,13 @@ impl ShardRunner {
     }
 }
 
+/// A request for member chunks, awaiting its chunks.
+struct MemberChunkRequest {
+    tx: Sender<GuildMembersChunkEvent>,
+    // the number of chunks received so far
+    received: u32,
+}
+
 /// Options to be passed to [`ShardRunner::new`].
 pub struct ShardRunnerOptions {
     pub data: Arc<RwLock<TypeMap>>,
@@ -676,3 +1016,236 @@ pub struct ShardRunnerOptions {
     pub voice_manager: Option<Arc<dyn VoiceGatewayManager + Send + Sync>>,
     pub cache_and_http: Arc<CacheAndHttp>,
 }
+
+#[cfg(all(test, feature = "mock_gateway"))]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+
+    use futures::channel::mpsc::{self, UnboundedReceiver as Receiver};
+    use futures::StreamExt;
+    use tokio::sync::{Mutex, RwLock};
+    use tokio::time::{sleep, timeout, Duration};
+    use typemap_rev::TypeMap;
+
+    use super::{ShardRunner, ShardRunnerOptions};
+    #[cfg(feature = "cache")]
+    use crate::cache::Cache;
+    use crate::client::bridge::gateway::{
+        ChunkGuildFilter,
+        ShardManagerMessage,
+        ShardMessenger,
+        ShardWarmUp,
+    };
+    #[cfg(feature = "framework")]
+    use crate::client::Context;
+    #[cfg(feature = "framework")]
+    use crate::framework::Framework;
+    use crate::gateway::mock::{MockAction, MockGateway};
+    use crate::gateway::{GatewayError, Shard};
+    use crate::http::Http;
+    use crate::json::{json, Value};
+    #[cfg(feature = "framework")]
+    use crate::model::channel::Message;
+    use crate::model::gateway::GatewayIntents;
+    use crate::model::id::{GuildId, UserId};
+    use crate::{CacheAndHttp, Error};
+
+    #[cfg(feature = "framework")]
+    struct NoFramework;
+
+    #[cfg(feature = "framework")]
+    #[async_trait::async_trait]
+    impl Framework for NoFramework {
+        async fn dispatch(&self, _: Context, _: Message) {}
+    }
+
+    /// Creates the runner of a shard connected to the gateway.
+    async fn runner(gateway: &MockGateway) -> (ShardRunner, Receiver<ShardManagerMessage>) {
+        let url = Arc::new(Mutex::new(gateway.url()));
+        let intents = GatewayIntents::GUILD_MEMBERS | GatewayIntents::GUILD_PRESENCES;
+        let shard = Shard::new(url, "token", [0, 1], intents).await.unwrap();
+        let (manager_tx, manager_rx) = mpsc::unbounded();
+
+        let runner = ShardRunner::new(ShardRunnerOptions {
+            data: Arc::new(RwLock::new(TypeMap::new())),
+            event_handler: None,
+            raw_event_handler: None,
+            #[cfg(feature = "framework")]
+            framework: Arc::new(NoFramework),
+            manager_tx,
+            shard,
+            #[cfg(feature = "voice")]
+            voice_manager: None,
+            cache_and_http: Arc::new(CacheAndHttp {
+                #[cfg(feature = "cache")]
+                cache: Arc::new(Cache::default()),
+                http: Arc::new(Http::new("token")),
+            }),
+        });
+
+        (runner, manager_rx)
+    }
+
+    /// Starts the runner of a shard connected to the gateway, returning a
+    /// messenger for it once the shard identified.
+    async fn start_runner(
+        gateway: &MockGateway,
+    ) -> (ShardMessenger, Receiver<ShardManagerMessage>) {
+        let (mut runner, manager_rx) = runner(gateway).await;
+        let messenger = ShardMessenger::new(runner.runner_tx());
+
+        tokio::spawn(async move { drop(runner.run().await) });
+        received(gateway, 2).await;
+
+        (messenger, manager_rx)
+    }
+
+    /// Waits for the gateway to receive a payload with the opcode, returning
+    /// its data.
+    async fn received(gateway: &MockGateway, op: u64) -> Value {
+        loop {
+            let payloads = gateway.received().await;
+
+            if let Some(payload) =
+                payloads.iter().find(|payload| payload["op"].as_u64() == Some(op))
+            {
+                return payload["d"].clone();
+            }
+
+            sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    fn member(id: u64) -> Value {
+        json!({
+            "user": {
+                "id": id.to_string(),
+                "username": "user",
+                "discriminator": "0001",
+                "avatar": null,
+            },
+            "roles": [],
+            "joined_at": "2021-03-02T09:12:33.417000+00:00",
+            "deaf": false,
+            "mute": false,
+        })
+    }
+
+    fn presence(id: u64) -> Value {
+        json!({
+            "user": {"id": id.to_string()},
+            "status": "online",
+            "activities": [],
+        })
+    }
+
+    #[tokio::test]
+    async fn test_chunk_guild_members() {
+        let gateway = MockGateway::builder().token("token").start().await.unwrap();
+        let (messenger, _manager_rx) = start_runner(&gateway).await;
+
+        let filter = ChunkGuildFilter::UserIds(vec![UserId(1), UserId(2), UserId(3)]);
+        let members =
+            messenger.chunk_guild_members(GuildId(1), None, true, filter, Duration::from_secs(5));
+        let respond = async {
+            let request = received(&gateway, 8).await;
+            assert_eq!(request["presences"], true);
+
+            // The members are sent in two chunks, and one user is missing.
+            gateway.send(MockAction::dispatch(
+                "GUILD_MEMBERS_CHUNK",
+                json!({
+                    "guild_id": "1",
+                    "members": [member(1)],
+                    "chunk_index": 0,
+                    "chunk_count": 2,
+                    "presences": [presence(1)],
+                    "nonce": request["nonce"],
+                }),
+            ));
+            gateway.send(MockAction::dispatch(
+                "GUILD_MEMBERS_CHUNK",
+                json!({
+                    "guild_id": "1",
+                    "members": [member(2)],
+                    "chunk_index": 1,
+                    "chunk_count": 2,
+                    "not_found": ["3"],
+                    "presences": [presence(2)],
+                    "nonce": request["nonce"],
+                }),
+            ));
+        };
+
+        let (members, ()) =
+            timeout(Duration::from_secs(5), futures::future::join(members, respond)).await.unwrap();
+        let members = members.unwrap();
+
+        let mut ids = members.members.keys().copied().collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(ids, [UserId(1), UserId(2)]);
+        assert_eq!(members.not_found, [UserId(3)]);
+        assert_eq!(members.presences.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_chunk_guild_members_timeout() {
+        let gateway = MockGateway::builder().token("token").start().await.unwrap();
+        let (messenger, _manager_rx) = start_runner(&gateway).await;
+
+        let members = messenger.chunk_guild_members(
+            GuildId(1),
+            Some(2),
+            false,
+            ChunkGuildFilter::None,
+            Duration::from_millis(500),
+        );
+        let respond = async {
+            let request = received(&gateway, 8).await;
+            assert_eq!(request["presences"], false);
+
+            // Only the first of two chunks is sent.
+            gateway.send(MockAction::dispatch(
+                "GUILD_MEMBERS_CHUNK",
+                json!({
+                    "guild_id": "1",
+                    "members": [member(1)],
+                    "chunk_index": 0,
+                    "chunk_count": 2,
+                    "nonce": request["nonce"],
+                }),
+            ));
+        };
+
+        let (members, ()) =
+            timeout(Duration::from_secs(5), futures::future::join(members, respond)).await.unwrap();
+
+        assert!(matches!(members, Err(Error::Gateway(GatewayError::ChunkGuildIncomplete))));
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_warm_up() {
+        let gateway = MockGateway::builder().token("token").start().await.unwrap();
+        let (runner, mut manager_rx) = runner(&gateway).await;
+        let (tx, mut warm_up_rx) = mpsc::unbounded();
+
+        // The shard started after its set of shards was abandoned.
+        let warm_up = ShardWarmUp {
+            generation: 1,
+            runners: Arc::default(),
+            tx,
+            cancelled: Arc::new(AtomicBool::new(true)),
+        };
+        let mut runner = runner.warm_up(Some(warm_up));
+
+        // It shuts itself down without identifying, reporting or asking the
+        // manager for anything.
+        timeout(Duration::from_secs(5), runner.run()).await.unwrap().unwrap();
+        drop(runner);
+
+        assert!(!gateway.received().await.iter().any(|payload| payload["op"] == 2));
+        assert!(warm_up_rx.next().await.is_none());
+        assert!(manager_rx.next().await.is_none());
+    }
+}
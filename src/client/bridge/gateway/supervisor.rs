@@ -0,0 +1,172 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::channel::mpsc::{self, UnboundedReceiver as Receiver, UnboundedSender as Sender};
+
+use super::event::{ShardLifecycle, ShardLifecycleEvent};
+use super::ShardId;
+
+/// A policy for supervising the health of shards, and restarting them when
+/// they fail.
+///
+/// By default, a connection is considered zombied after a single heartbeat
+/// was not acknowledged, and failed shards are restarted indefinitely. The
+/// first restart of a shard happens right away, like without a policy. Only
+/// if the shard keeps failing without connecting in between are further
+/// restarts delayed, with an exponential backoff from 1 second up to 1 minute.
+///
+/// # Examples
+///
+/// Tolerate a missed heartbeat acknowledgement, and stop restarting a shard
+/// after it failed 5 times in a row:
+///
+/// ```rust,no_run
+/// # use serenity::prelude::*;
+/// # struct Handler;
+/// # impl EventHandler for Handler {}
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// use serenity::client::bridge::gateway::SupervisorPolicy;
+///
+/// let policy = SupervisorPolicy::default().max_missed_heartbeat_acks(2).max_restart_attempts(5);
+///
+/// let token = std::env::var("DISCORD_TOKEN")?;
+/// let mut client = Client::builder(&token, GatewayIntents::default())
+///     .event_handler(Handler)
+///     .supervisor_policy(policy)
+///     .await?;
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct SupervisorPolicy {
+    pub(super) max_missed_heartbeat_acks: u32,
+    pub(super) max_restart_attempts: Option<u32>,
+    pub(super) initial_backoff: Duration,
+    pub(super) max_backoff: Duration,
+}
+
+impl Default for SupervisorPolicy {
+    fn default() -> Self {
+        Self {
+            max_missed_heartbeat_acks: 1,
+            max_restart_attempts: None,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+impl SupervisorPolicy {
+    /// Sets the number of consecutive heartbeats which may go unacknowledged
+    /// before the connection of a shard is considered zombied, and the shard
+    /// reconnects. The minimum is 1.
+    #[must_use]
+    pub fn max_missed_heartbeat_acks(mut self, max_missed_heartbeat_acks: u32) -> Self {
+        self.max_missed_heartbeat_acks = max_missed_heartbeat_acks.max(1);
+
+        self
+    }
+
+    /// Sets the number of consecutive restarts of a shard after which it is
+    /// given up on, instead of restarted again. A shard which connects
+    /// successfully starts counting from zero again.
+    #[must_use]
+    pub fn max_restart_attempts(mut self, max_restart_attempts: u32) -> Self {
+        self.max_restart_attempts = Some(max_restart_attempts);
+
+        self
+    }
+
+    /// Sets the duration waited before the second consecutive restart of a
+    /// shard, as the first one happens right away. The duration doubles with
+    /// every further consecutive restart, up to the maximum backoff.
+    #[must_use]
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+
+        self
+    }
+
+    /// Sets the maximum duration waited before restarting a shard.
+    #[must_use]
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+
+        self
+    }
+
+    /// The duration waited before the given consecutive restart attempt,
+    /// starting at 1, which is not waited for.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        if attempt <= 1 {
+            return Duration::ZERO;
+        }
+
+        let factor = 2u32.saturating_pow(attempt - 2);
+
+        self.initial_backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+
+    /// Whether a shard is given up on at the given consecutive restart
+    /// attempt.
+    pub(super) fn gives_up(&self, attempt: u32) -> bool {
+        self.max_restart_attempts.map_or(false, |max| attempt > max)
+    }
+}
+
+/// Sends [`ShardLifecycleEvent`]s to everyone who subscribed to them.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ShardLifecycleHub {
+    subscribers: Arc<Mutex<Vec<Sender<ShardLifecycleEvent>>>>,
+}
+
+impl ShardLifecycleHub {
+    /// Returns a receiver for the lifecycle events emitted from now on.
+    pub(crate) fn subscribe(&self) -> Receiver<ShardLifecycleEvent> {
+        let (tx, rx) = mpsc::unbounded();
+
+        self.subscribers.lock().expect("lifecycle lock poisoned").push(tx);
+
+        rx
+    }
+
+    /// Sends a lifecycle event to the subscribers, forgetting those which
+    /// stopped listening.
+    pub(crate) fn emit(&self, shard_id: ShardId, kind: ShardLifecycle) {
+        let event = ShardLifecycleEvent {
+            shard_id,
+            kind,
+        };
+
+        self.subscribers
+            .lock()
+            .expect("lifecycle lock poisoned")
+            .retain(|tx| tx.unbounded_send(event.clone()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::SupervisorPolicy;
+
+    #[test]
+    fn test_backoff() {
+        let policy = SupervisorPolicy::default().max_restart_attempts(3);
+
+        // The first restart is not delayed.
+        assert_eq!(policy.backoff(1), Duration::ZERO);
+        assert_eq!(policy.backoff(2), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(2));
+        assert_eq!(policy.backoff(5), Duration::from_secs(8));
+        assert_eq!(policy.backoff(8), Duration::from_secs(60));
+        assert_eq!(policy.backoff(100), Duration::from_secs(60));
+
+        assert!(!policy.gives_up(3));
+        assert!(policy.gives_up(4));
+        assert!(!SupervisorPolicy::default().gives_up(u32::MAX));
+    }
+}
@@ -18,6 +18,12 @@ pub enum Error {
     /// When all shards that the client is responsible for have shutdown with an
     /// error.
     Shutdown,
+    /// When the public key given to an [`InteractionsEndpoint`] is not a
+    /// hex encoded Ed25519 public key.
+    ///
+    /// [`InteractionsEndpoint`]: super::InteractionsEndpoint
+    #[cfg(feature = "interactions_endpoint")]
+    InvalidPublicKey,
 }
 
 impl fmt::Display for Error {
@@ -25,6 +31,8 @@ impl fmt::Display for Error {
         match self {
             Self::ShardBootFailure => f.write_str("Failed to (re-)boot a shard"),
             Self::Shutdown => f.write_str("The clients shards shutdown"),
+            #[cfg(feature = "interactions_endpoint")]
+            Self::InvalidPublicKey => f.write_str("Invalid public key"),
         }
     }
 }
@@ -0,0 +1,543 @@
+//! Receiving interactions over an outgoing webhook instead of the gateway.
+
+use std::collections::HashMap;
+use std::convert::{Infallible, TryFrom};
+use std::io::{Error as IoError, ErrorKind};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use ed25519_dalek::{Signature, VerifyingKey};
+use futures::channel::mpsc::unbounded;
+use futures::future::{select, Either};
+use hyper::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request as HyperRequest, Response as HyperResponse, Server, StatusCode};
+use tokio::sync::{oneshot, Mutex, RwLock};
+use tokio::time::timeout;
+use tracing::{debug, warn};
+use typemap_rev::{TypeMap, TypeMapKey};
+
+use super::{ClientError, Context, EventHandler};
+#[cfg(feature = "cache")]
+use crate::cache::Cache;
+use crate::http::request::Request;
+use crate::http::routing::RouteInfo;
+use crate::http::{Http, HttpBuilder, Middleware};
+use crate::internal::prelude::*;
+use crate::internal::tokio::spawn_named;
+use crate::json;
+use crate::model::application::interaction::Interaction;
+
+/// A builder for an [`InteractionsEndpoint`].
+#[must_use]
+pub struct InteractionsEndpointBuilder {
+    public_key: String,
+    http: HttpBuilder,
+    data: TypeMap,
+    event_handler: Option<Arc<dyn EventHandler>>,
+    response_timeout: Duration,
+}
+
+impl InteractionsEndpointBuilder {
+    /// Creates a builder for the application with the given hex encoded
+    /// public key, as shown in the developer portal. The `token` is used for
+    /// requests made from event handlers, such as followup messages.
+    pub fn new(public_key: impl Into<String>, token: impl AsRef<str>) -> Self {
+        Self {
+            public_key: public_key.into(),
+            http: HttpBuilder::new(token),
+            data: TypeMap::new(),
+            event_handler: None,
+            response_timeout: Duration::from_secs(3),
+        }
+    }
+
+    /// Sets the [`HttpBuilder`] the [`Http`] client available in
+    /// [`Context`]s is built from, replacing the token given to
+    /// [`Self::new`].
+    pub fn http_builder(mut self, http: HttpBuilder) -> Self {
+        self.http = http;
+
+        self
+    }
+
+    /// Sets the entire [`TypeMap`] that will be available in [`Context`]s.
+    /// A [`TypeMap`] must not be constructed manually: [`Self::type_map_insert`]
+    /// can be used to insert one type at a time.
+    pub fn type_map(mut self, type_map: TypeMap) -> Self {
+        self.data = type_map;
+
+        self
+    }
+
+    /// Insert a single `value` into the internal [`TypeMap`] that will
+    /// be available in [`Context::data`].
+    pub fn type_map_insert<T: TypeMapKey>(mut self, value: T::Value) -> Self {
+        self.data.insert::<T>(value);
+
+        self
+    }
+
+    /// Sets the event handler whose [`EventHandler::interaction_create`] is
+    /// called for every received interaction. Other events are never
+    /// dispatched.
+    pub fn event_handler<H: EventHandler + 'static>(mut self, event_handler: H) -> Self {
+        self.event_handler = Some(Arc::new(event_handler));
+
+        self
+    }
+
+    /// Sets the event handler. Passed by Arc.
+    pub fn event_handler_arc<H: EventHandler + 'static>(
+        mut self,
+        event_handler_arc: Arc<H>,
+    ) -> Self {
+        self.event_handler = Some(event_handler_arc);
+
+        self
+    }
+
+    /// Sets how long to wait for the event handler to create the initial
+    /// response. Discord requires a response within 3 seconds, which is the
+    /// default.
+    pub fn response_timeout(mut self, response_timeout: Duration) -> Self {
+        self.response_timeout = response_timeout;
+
+        self
+    }
+
+    /// Builds the endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::InvalidPublicKey`] if the public key is not a
+    /// hex encoded Ed25519 public key.
+    pub fn build(self) -> Result<InteractionsEndpoint> {
+        let public_key = decode_hex(&self.public_key)
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+            .ok_or(Error::Client(ClientError::InvalidPublicKey))?;
+
+        let responses = Arc::new(InitialResponses::default());
+
+        Ok(InteractionsEndpoint {
+            data: Arc::new(RwLock::new(self.data)),
+            http: Arc::new(self.http.middleware(Arc::clone(&responses)).build()),
+            #[cfg(feature = "cache")]
+            cache: Arc::new(Cache::new()),
+            public_key,
+            event_handler: self.event_handler,
+            responses,
+            response_timeout: self.response_timeout,
+        })
+    }
+}
+
+/// Receives interactions over an outgoing webhook, as configured by the
+/// "Interactions Endpoint URL" of an application, instead of the gateway.
+///
+/// Every request is checked against the application's public key, PINGs are
+/// answered automatically, and all other interactions are dispatched to
+/// [`EventHandler::interaction_create`]. The initial response the handler
+/// creates, e.g. via [`ApplicationCommandInteraction::create_interaction_response`],
+/// is sent back in the body of the webhook response, rather than to Discord's
+/// API.
+///
+/// There is no shard behind the [`Context`] passed to the handler, so
+/// methods communicating with the gateway, such as [`Context::set_activity`],
+/// do nothing.
+///
+/// **Note**: Initial responses with attachments can not be returned in the
+/// webhook response. Create a deferred response and attach files to a
+/// followup message instead.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use serenity::async_trait;
+/// use serenity::client::{Context, EventHandler, InteractionsEndpoint};
+/// use serenity::model::application::interaction::{Interaction, InteractionResponseType};
+///
+/// struct Handler;
+///
+/// #[async_trait]
+/// impl EventHandler for Handler {
+///     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+///         if let Interaction::ApplicationCommand(command) = interaction {
+///             let _ = command
+///                 .create_interaction_response(&ctx.http, |r| {
+///                     r.kind(InteractionResponseType::ChannelMessageWithSource)
+///                         .interaction_response_data(|d| d.content("Pong!"))
+///                 })
+///                 .await;
+///         }
+///     }
+/// }
+///
+/// # async fn run() -> serenity::Result<()> {
+/// let endpoint =
+///     InteractionsEndpoint::builder("public key", "token").event_handler(Handler).build()?;
+///
+/// endpoint.serve(([127, 0, 0, 1], 8080)).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`ApplicationCommandInteraction::create_interaction_response`]: crate::model::application::interaction::application_command::ApplicationCommandInteraction::create_interaction_response
+pub struct InteractionsEndpoint {
+    /// A data map available in every [`Context`], see [`Client::data`].
+    ///
+    /// [`Client::data`]: super::Client::data
+    pub data: Arc<RwLock<TypeMap>>,
+    /// The client for requests made from event handlers.
+    pub http: Arc<Http>,
+    /// The cache available in every [`Context`]. It is not filled by the
+    /// endpoint, as interactions carry all the data they refer to.
+    #[cfg(feature = "cache")]
+    pub cache: Arc<Cache>,
+    public_key: VerifyingKey,
+    event_handler: Option<Arc<dyn EventHandler>>,
+    responses: Arc<InitialResponses>,
+    response_timeout: Duration,
+}
+
+impl InteractionsEndpoint {
+    /// Returns a builder for the application with the given hex encoded
+    /// public key. See [`InteractionsEndpointBuilder::new`].
+    pub fn builder(
+        public_key: impl Into<String>,
+        token: impl AsRef<str>,
+    ) -> InteractionsEndpointBuilder {
+        InteractionsEndpointBuilder::new(public_key, token)
+    }
+
+    /// Answers a single webhook request.
+    ///
+    /// This can be used to mount the endpoint into an existing HTTP server,
+    /// instead of using [`Self::serve`].
+    pub async fn handle(&self, request: HyperRequest<Body>) -> HyperResponse<Body> {
+        if request.method() != Method::POST {
+            return empty_response(StatusCode::METHOD_NOT_ALLOWED);
+        }
+
+        let (parts, body) = request.into_parts();
+
+        let body = match hyper::body::to_bytes(body).await {
+            Ok(body) => body,
+            Err(why) => {
+                debug!("Failed to read interaction body: {:?}", why);
+
+                return empty_response(StatusCode::BAD_REQUEST);
+            },
+        };
+
+        if !self.verify(&parts.headers, &body) {
+            return empty_response(StatusCode::UNAUTHORIZED);
+        }
+
+        let interaction = match String::from_utf8(body.to_vec())
+            .map_err(|_| ())
+            .and_then(|mut body| json::from_str::<Interaction>(&mut body).map_err(|_| ()))
+        {
+            Ok(interaction) => interaction,
+            Err(()) => return empty_response(StatusCode::BAD_REQUEST),
+        };
+
+        if let Interaction::Ping(_) = interaction {
+            return json_response(br#"{"type":1}"#.to_vec());
+        }
+
+        match self.dispatch(interaction).await {
+            Some(response) => json_response(response),
+            None => empty_response(StatusCode::INTERNAL_SERVER_ERROR),
+        }
+    }
+
+    /// Listens for webhook requests on the given address until an error
+    /// occurs.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the address can not be bound to, or the server
+    /// fails.
+    pub async fn serve(self, addr: impl Into<SocketAddr>) -> Result<()> {
+        let endpoint = Arc::new(self);
+
+        let make_service = make_service_fn(move |_| {
+            let endpoint = Arc::clone(&endpoint);
+
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let endpoint = Arc::clone(&endpoint);
+
+                    async move { Ok::<_, Infallible>(endpoint.handle(request).await) }
+                }))
+            }
+        });
+
+        Server::try_bind(&addr.into())
+            .map_err(into_io_error)?
+            .serve(make_service)
+            .await
+            .map_err(into_io_error)?;
+
+        Ok(())
+    }
+
+    /// Checks the request's signature of the timestamp and body.
+    fn verify(&self, headers: &HeaderMap, body: &[u8]) -> bool {
+        let signature = headers
+            .get("X-Signature-Ed25519")
+            .and_then(|value| value.to_str().ok())
+            .and_then(decode_hex)
+            .and_then(|bytes| Signature::from_slice(&bytes).ok());
+
+        let (signature, timestamp) = match (signature, headers.get("X-Signature-Timestamp")) {
+            (Some(signature), Some(timestamp)) => (signature, timestamp),
+            _ => return false,
+        };
+
+        let mut message = timestamp.as_bytes().to_vec();
+        message.extend_from_slice(body);
+
+        self.public_key.verify_strict(&message, &signature).is_ok()
+    }
+
+    /// Calls the event handler, and waits for the initial response it
+    /// creates.
+    async fn dispatch(&self, interaction: Interaction) -> Option<Vec<u8>> {
+        let event_handler = match &self.event_handler {
+            Some(event_handler) => Arc::clone(event_handler),
+            None => return None,
+        };
+
+        if self.http.application_id().is_none() {
+            self.http.set_application_id(interaction.application_id().0);
+        }
+
+        let interaction_id = interaction.id().0;
+        let (tx, rx) = oneshot::channel();
+        self.responses.pending.lock().await.insert(interaction_id, tx);
+
+        let ctx = self.context();
+        let handler = spawn_named("interactions_endpoint::interaction_create", async move {
+            event_handler.interaction_create(ctx, interaction).await;
+        });
+
+        // The handler keeps running after the timeout, so that it can still
+        // send followup messages.
+        let response = match timeout(self.response_timeout, select(rx, handler)).await {
+            Ok(Either::Left((response, _))) => response.ok(),
+            _ => None,
+        };
+
+        self.responses.pending.lock().await.remove(&interaction_id);
+
+        if response.is_none() {
+            warn!("No initial response was created for interaction {}", interaction_id);
+        }
+
+        response
+    }
+
+    fn context(&self) -> Context {
+        // Messages to the non-existent shard are dropped.
+        let (runner_tx, _) = unbounded();
+
+        Context::new(
+            Arc::clone(&self.data),
+            runner_tx,
+            0,
+            Arc::clone(&self.http),
+            #[cfg(feature = "cache")]
+            Arc::clone(&self.cache),
+        )
+    }
+}
+
+/// Intercepts the initial responses to interactions which are waited on,
+/// instead of sending them to Discord.
+#[derive(Default)]
+struct InitialResponses {
+    pending: Mutex<HashMap<u64, oneshot::Sender<Vec<u8>>>>,
+}
+
+#[async_trait]
+impl Middleware for Arc<InitialResponses> {
+    async fn before_request(&self, request: &mut Request<'_>) -> Result<Option<reqwest::Response>> {
+        let interaction_id = match request.route_ref() {
+            RouteInfo::CreateInteractionResponse {
+                interaction_id, ..
+            } => *interaction_id,
+            _ => return Ok(None),
+        };
+
+        // Responses with attachments have a multipart body instead.
+        let body = match request.body_ref() {
+            Some(body) => body.to_vec(),
+            None => return Ok(None),
+        };
+
+        let tx = match self.pending.lock().await.remove(&interaction_id) {
+            Some(tx) => tx,
+            None => return Ok(None),
+        };
+
+        if tx.send(body).is_err() {
+            return Ok(None);
+        }
+
+        let response = HyperResponse::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Vec::new())
+            .map_err(into_io_error)?;
+
+        Ok(Some(response.into()))
+    }
+}
+
+fn empty_response(status: StatusCode) -> HyperResponse<Body> {
+    let mut response = HyperResponse::new(Body::empty());
+    *response.status_mut() = status;
+
+    response
+}
+
+fn json_response(body: Vec<u8>) -> HyperResponse<Body> {
+    let mut response = HyperResponse::new(Body::from(body));
+    response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+    response
+}
+
+fn into_io_error<E>(why: E) -> IoError
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    IoError::new(ErrorKind::Other, why)
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+
+    use ed25519_dalek::{Signer, SigningKey};
+    use hyper::{Body, Request, StatusCode};
+
+    use super::InteractionsEndpoint;
+    use crate::async_trait;
+    use crate::client::{Context, EventHandler};
+    use crate::model::application::interaction::{Interaction, InteractionResponseType};
+
+    const PING: &str = r#"{"id":"2","application_id":"1","type":1,"token":"t","version":1}"#;
+    const COMMAND: &str = r#"{
+        "id": "2",
+        "application_id": "1",
+        "type": 2,
+        "data": {"id": "3", "name": "ping", "type": 1},
+        "channel_id": "4",
+        "user": {"id": "5", "username": "user", "discriminator": "0001", "avatar": null},
+        "token": "t",
+        "version": 1,
+        "locale": "en-US"
+    }"#;
+
+    struct Handler;
+
+    #[async_trait]
+    impl EventHandler for Handler {
+        async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+            if let Interaction::ApplicationCommand(command) = interaction {
+                command
+                    .create_interaction_response(&ctx.http, |r| {
+                        r.kind(InteractionResponseType::ChannelMessageWithSource)
+                            .interaction_response_data(|d| d.content("Pong!"))
+                    })
+                    .await
+                    .unwrap();
+            }
+        }
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().fold(String::new(), |mut hex, byte| {
+            write!(hex, "{:02x}", byte).unwrap();
+            hex
+        })
+    }
+
+    fn endpoint(key: &SigningKey) -> InteractionsEndpoint {
+        InteractionsEndpoint::builder(hex(key.verifying_key().as_bytes()), "token")
+            .event_handler(Handler)
+            .build()
+            .unwrap()
+    }
+
+    fn signed(key: &SigningKey, body: &str) -> Request<Body> {
+        let timestamp = "1700000000";
+        let signature = key.sign(format!("{}{}", timestamp, body).as_bytes());
+
+        Request::post("/")
+            .header("X-Signature-Ed25519", hex(&signature.to_bytes()))
+            .header("X-Signature-Timestamp", timestamp)
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    async fn body(response: hyper::Response<Body>) -> String {
+        let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_answers_ping() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let response = endpoint(&key).handle(signed(&key, PING)).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body(response).await, r#"{"type":1}"#);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_signature() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let other_key = SigningKey::from_bytes(&[8; 32]);
+        let response = endpoint(&key).handle(signed(&other_key, PING)).await;
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let mut request = signed(&key, PING);
+        request.headers_mut().remove("X-Signature-Timestamp");
+        let response = endpoint(&key).handle(request).await;
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_returns_initial_response() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let response = endpoint(&key).handle(signed(&key, COMMAND)).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = body(response).await;
+        assert!(body.contains(r#""type":4"#));
+        assert!(body.contains("Pong!"));
+    }
+
+    #[test]
+    fn test_invalid_public_key() {
+        assert!(InteractionsEndpoint::builder("abc", "token").build().is_err());
+    }
+}
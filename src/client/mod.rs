@@ -24,6 +24,8 @@ mod dispatch;
 mod error;
 #[cfg(feature = "gateway")]
 mod event_handler;
+#[cfg(feature = "interactions_endpoint")]
+mod interactions_endpoint;
 
 use std::future::Future;
 use std::pin::Pin;
@@ -37,10 +39,13 @@ use typemap_rev::{TypeMap, TypeMapKey};
 
 #[cfg(feature = "gateway")]
 use self::bridge::gateway::{
+    EventTypeFilter,
     ShardManager,
     ShardManagerError,
     ShardManagerMonitor,
     ShardManagerOptions,
+    ShardSettings,
+    SupervisorPolicy,
 };
 #[cfg(feature = "voice")]
 use self::bridge::voice::VoiceGatewayManager;
@@ -48,8 +53,10 @@ pub use self::context::Context;
 pub use self::error::Error as ClientError;
 #[cfg(feature = "gateway")]
 pub use self::event_handler::{EventHandler, RawEventHandler};
+#[cfg(feature = "interactions_endpoint")]
+pub use self::interactions_endpoint::{InteractionsEndpoint, InteractionsEndpointBuilder};
 #[cfg(feature = "gateway")]
-use super::gateway::GatewayError;
+use super::gateway::{GatewayError, ShardSession, TransportCompression, TransportEncoding};
 #[cfg(feature = "cache")]
 pub use crate::cache::Cache;
 #[cfg(feature = "cache")]
@@ -73,6 +80,12 @@ pub struct ClientBuilder {
     http: Option<Http>,
     fut: Option<BoxFuture<'static, Result<Client>>>,
     intents: GatewayIntents,
+    compression: TransportCompression,
+    encoding: TransportEncoding,
+    sessions: Vec<ShardSession>,
+    event_type_filter: Option<EventTypeFilter>,
+    supervisor_policy: SupervisorPolicy,
+    gateway_url: Option<String>,
     #[cfg(feature = "cache")]
     cache_settings: Option<CacheSettings>,
     #[cfg(feature = "framework")]
@@ -91,6 +104,12 @@ impl ClientBuilder {
             http: Some(http),
             fut: None,
             intents,
+            compression: TransportCompression::default(),
+            encoding: TransportEncoding::default(),
+            sessions: Vec::new(),
+            event_type_filter: None,
+            supervisor_policy: SupervisorPolicy::default(),
+            gateway_url: None,
             #[cfg(feature = "cache")]
             cache_settings: Some(CacheSettings::new()),
             #[cfg(feature = "framework")]
@@ -310,6 +329,132 @@ impl ClientBuilder {
         self.intents
     }
 
+    /// Sets the compression of the payloads received from the gateway,
+    /// [`TransportCompression::Payload`] by default.
+    ///
+    /// Compressing the whole connection with
+    /// [`TransportCompression::ZlibStream`] greatly reduces the bandwidth
+    /// used by bots in many guilds.
+    pub fn gateway_compression(mut self, compression: TransportCompression) -> Self {
+        self.compression = compression;
+
+        self
+    }
+
+    /// Gets the gateway compression. See [`Self::gateway_compression`] for
+    /// more info.
+    pub fn get_gateway_compression(&self) -> TransportCompression {
+        self.compression
+    }
+
+    /// Sets the encoding of the payloads exchanged with the gateway,
+    /// [`TransportEncoding::Json`] by default.
+    ///
+    /// [`TransportEncoding::Etf`] is more compact and cheaper to decode.
+    pub fn gateway_encoding(mut self, encoding: TransportEncoding) -> Self {
+        self.encoding = encoding;
+
+        self
+    }
+
+    /// Gets the gateway encoding. See [`Self::gateway_encoding`] for more
+    /// info.
+    pub fn get_gateway_encoding(&self) -> TransportEncoding {
+        self.encoding
+    }
+
+    /// Sets the sessions for the shards to resume instead of identifying, as
+    /// exported via [`ShardManager::shutdown_all_resumable`] by a previous
+    /// client.
+    ///
+    /// A shard only resumes a session given for the same shard ID and total
+    /// number of shards. If resuming fails, the shard identifies as usual.
+    ///
+    /// # Examples
+    ///
+    /// Resume the sessions saved to a file when shutting down:
+    ///
+    /// ```rust,no_run
+    /// # use serenity::prelude::*;
+    /// # struct Handler;
+    /// # impl EventHandler for Handler {}
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// use serenity::gateway::ShardSession;
+    ///
+    /// let token = std::env::var("DISCORD_TOKEN")?;
+    /// let sessions: Vec<ShardSession> = match std::fs::read_to_string("sessions.json") {
+    ///     Ok(sessions) => serde_json::from_str(&sessions)?,
+    ///     Err(_) => Vec::new(),
+    /// };
+    ///
+    /// let mut client = Client::builder(&token, GatewayIntents::default())
+    ///     .event_handler(Handler)
+    ///     .shard_sessions(sessions)
+    ///     .await?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn shard_sessions(mut self, sessions: impl IntoIterator<Item = ShardSession>) -> Self {
+        self.sessions = sessions.into_iter().collect();
+
+        self
+    }
+
+    /// Gets the sessions to resume. See [`Self::shard_sessions`] for more
+    /// info.
+    pub fn get_shard_sessions(&self) -> &[ShardSession] {
+        &self.sessions
+    }
+
+    /// Sets the dispatch events for the shards to deserialize. Events not
+    /// allowed by the filter are skipped before being parsed.
+    ///
+    /// By default, all events are deserialized. See [`EventTypeFilter`] for
+    /// more info.
+    pub fn event_type_filter(mut self, filter: EventTypeFilter) -> Self {
+        self.event_type_filter = Some(filter);
+
+        self
+    }
+
+    /// Gets the event type filter. See [`Self::event_type_filter`] for more
+    /// info.
+    pub fn get_event_type_filter(&self) -> Option<&EventTypeFilter> {
+        self.event_type_filter.as_ref()
+    }
+
+    /// Sets the policy for supervising the health of the shards, such as how
+    /// often failed shards are restarted.
+    ///
+    /// See [`SupervisorPolicy`] for more info.
+    pub fn supervisor_policy(mut self, policy: SupervisorPolicy) -> Self {
+        self.supervisor_policy = policy;
+
+        self
+    }
+
+    /// Gets the shard supervisor policy. See [`Self::supervisor_policy`] for
+    /// more info.
+    pub fn get_supervisor_policy(&self) -> &SupervisorPolicy {
+        &self.supervisor_policy
+    }
+
+    /// Sets the URL of the gateway for the shards to connect to, instead of
+    /// the one retrieved via [`Http::get_gateway`].
+    ///
+    /// This is mainly useful to connect to a local gateway server in tests,
+    /// such as the one of the `mock_gateway` feature.
+    pub fn gateway_url(mut self, url: impl Into<String>) -> Self {
+        self.gateway_url = Some(url.into());
+
+        self
+    }
+
+    /// Gets the gateway URL. See [`Self::gateway_url`] for more info.
+    pub fn get_gateway_url(&self) -> Option<&str> {
+        self.gateway_url.as_deref()
+    }
+
     /// Sets an event handler with multiple methods for each possible event.
     pub fn event_handler<H: EventHandler + 'static>(mut self, event_handler: H) -> Self {
         self.event_handler = Some(Arc::new(event_handler));
@@ -363,6 +508,12 @@ impl Future for ClientBuilder {
             let event_handler = self.event_handler.take();
             let raw_event_handler = self.raw_event_handler.take();
             let intents = self.intents;
+            let compression = self.compression;
+            let encoding = self.encoding;
+            let sessions = std::mem::take(&mut self.sessions);
+            let event_type_filter = self.event_type_filter.take();
+            let supervisor_policy = self.supervisor_policy.clone();
+            let gateway_url = self.gateway_url.take();
 
             let mut http = self.http.take().unwrap();
             if let Some(event_handler) = event_handler.clone() {
@@ -383,30 +534,43 @@ impl Future for ClientBuilder {
             });
 
             self.fut = Some(Box::pin(async move {
-                let ws_url = Arc::new(Mutex::new(match http.get_gateway().await {
-                    Ok(response) => response.url,
-                    Err(err) => {
-                        tracing::warn!("HTTP request to get gateway URL failed: {}", err);
-                        "wss://gateway.discord.gg".to_string()
+                let ws_url = match gateway_url {
+                    Some(url) => url,
+                    None => match http.get_gateway().await {
+                        Ok(response) => response.url,
+                        Err(err) => {
+                            tracing::warn!("HTTP request to get gateway URL failed: {}", err);
+                            "wss://gateway.discord.gg".to_string()
+                        },
                     },
-                }));
+                };
+                let ws_url = Arc::new(Mutex::new(ws_url));
 
                 let (shard_manager, shard_manager_worker) = {
-                    ShardManager::new(ShardManagerOptions {
-                        data: &data,
-                        event_handler: &event_handler,
-                        raw_event_handler: &raw_event_handler,
-                        #[cfg(feature = "framework")]
-                        framework: &framework,
-                        shard_index: 0,
-                        shard_init: 0,
-                        shard_total: 0,
-                        #[cfg(feature = "voice")]
-                        voice_manager: &voice_manager,
-                        ws_url: &ws_url,
-                        cache_and_http: &cache_and_http,
-                        intents,
-                    })
+                    ShardManager::with_settings(
+                        ShardManagerOptions {
+                            data: &data,
+                            event_handler: &event_handler,
+                            raw_event_handler: &raw_event_handler,
+                            #[cfg(feature = "framework")]
+                            framework: &framework,
+                            shard_index: 0,
+                            shard_init: 0,
+                            shard_total: 0,
+                            #[cfg(feature = "voice")]
+                            voice_manager: &voice_manager,
+                            ws_url: &ws_url,
+                            cache_and_http: &cache_and_http,
+                            intents,
+                        },
+                        ShardSettings {
+                            compression,
+                            encoding,
+                            sessions,
+                            event_type_filter,
+                            supervisor_policy,
+                        },
+                    )
                     .await
                 };
 
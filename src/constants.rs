@@ -22,6 +22,10 @@ pub const MESSAGE_CODE_LIMIT: usize = 2000;
 /// The maximum number of members the bot can fetch at once
 pub const MEMBER_FETCH_LIMIT: u64 = 1000;
 
+/// The number of requests per second a bot can make across all routes, unless
+/// Discord granted it a higher global ratelimit.
+pub const GLOBAL_RATELIMIT: u32 = 50;
+
 /// The [UserAgent] sent along with every request.
 ///
 /// [UserAgent]: ::reqwest::header::USER_AGENT
@@ -1,6 +1,6 @@
 use std::error::Error as StdError;
 use std::fmt::{self, Error as FormatError};
-use std::io::Error as IoError;
+use std::io::{Error as IoError, ErrorKind as IoErrorKind};
 
 #[cfg(feature = "gateway")]
 use async_tungstenite::tungstenite::error::Error as TungsteniteError;
@@ -14,6 +14,8 @@ use crate::client::ClientError;
 #[cfg(feature = "collector")]
 use crate::collector::CollectorError;
 #[cfg(feature = "gateway")]
+use crate::gateway::etf::Error as EtfError;
+#[cfg(feature = "gateway")]
 use crate::gateway::GatewayError;
 #[cfg(feature = "http")]
 use crate::http::HttpError;
@@ -101,6 +103,88 @@ pub enum Error {
     Tungstenite(TungsteniteError),
 }
 
+impl Error {
+    /// Returns true when the current user lacks the permissions required for
+    /// the action, whether Discord rejected a request with
+    /// [`JsonErrorCode::MissingPermissions`] or [`JsonErrorCode::MissingAccess`],
+    /// the library checked the permissions beforehand, or the gateway
+    /// disallowed the requested intents.
+    ///
+    /// [`JsonErrorCode::MissingPermissions`]: crate::http::JsonErrorCode::MissingPermissions
+    /// [`JsonErrorCode::MissingAccess`]: crate::http::JsonErrorCode::MissingAccess
+    #[must_use]
+    pub fn is_missing_permissions(&self) -> bool {
+        match self {
+            Self::Model(ModelError::InvalidPermissions(_)) => true,
+            #[cfg(feature = "http")]
+            Self::Http(inner) => inner.is_missing_permissions(),
+            #[cfg(feature = "gateway")]
+            Self::Gateway(GatewayError::DisallowedGatewayIntents) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns true when a requested resource, such as a channel or a message,
+    /// does not exist.
+    ///
+    /// **Note**: Resources missing from the cache are not covered, see
+    /// [`ModelError::is_cache_err`] for those.
+    #[must_use]
+    pub fn is_unknown_resource(&self) -> bool {
+        match self {
+            #[cfg(feature = "http")]
+            Self::Http(inner) => inner.is_unknown_resource(),
+            _ => false,
+        }
+    }
+
+    /// Returns true when the error is transient, so that the failed action may
+    /// succeed if it is attempted again later.
+    ///
+    /// This covers ratelimited requests, unavailable servers and timeouts from
+    /// the HTTP API (see [`HttpError::is_retryable`]), dropped connections, and
+    /// gateway connections which were closed for a reason other than invalid
+    /// authentication, sharding or intents. Model errors are never retryable.
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Io(inner) => matches!(
+                inner.kind(),
+                IoErrorKind::ConnectionReset
+                    | IoErrorKind::ConnectionAborted
+                    | IoErrorKind::BrokenPipe
+                    | IoErrorKind::TimedOut
+                    | IoErrorKind::Interrupted
+                    | IoErrorKind::UnexpectedEof
+            ),
+            #[cfg(feature = "http")]
+            Self::Http(inner) => inner.is_retryable(),
+            #[cfg(feature = "gateway")]
+            Self::Gateway(inner) => match inner {
+                GatewayError::Closed(Some(frame)) => !matches!(
+                    u16::from(frame.code),
+                    // Authentication failed, invalid shard, sharding required,
+                    // invalid API version, invalid or disallowed intents.
+                    4004 | 4010 | 4011 | 4012 | 4013 | 4014
+                ),
+                GatewayError::Closed(None)
+                | GatewayError::ExpectedHello
+                | GatewayError::HeartbeatFailed
+                | GatewayError::InvalidHandshake => true,
+                _ => false,
+            },
+            #[cfg(feature = "gateway")]
+            Self::Tungstenite(inner) => matches!(
+                inner,
+                TungsteniteError::ConnectionClosed
+                    | TungsteniteError::AlreadyClosed
+                    | TungsteniteError::Io(_)
+            ),
+            _ => false,
+        }
+    }
+}
+
 #[cfg(feature = "simd-json")]
 impl From<simd_json::Error> for Error {
     fn from(e: simd_json::Error) -> Self {
@@ -121,6 +205,13 @@ impl From<GatewayError> for Error {
     }
 }
 
+#[cfg(feature = "gateway")]
+impl From<EtfError> for Error {
+    fn from(e: EtfError) -> Error {
+        Error::Gateway(GatewayError::Etf(e))
+    }
+}
+
 impl From<IoError> for Error {
     fn from(e: IoError) -> Error {
         Error::Io(e)
@@ -0,0 +1,226 @@
+use std::io::{Error as IoError, ErrorKind as IoErrorKind, Read, Result as IoResult};
+
+use flate2::read::ZlibDecoder;
+use flate2::{Decompress, FlushDecompress};
+#[cfg(feature = "zstd_stream")]
+use zstd::stream::raw::{Decoder as ZstdDecoder, InBuffer, Operation, OutBuffer};
+
+use crate::internal::prelude::*;
+
+/// The first byte of a zlib stream using deflate with a 32KiB window, which is
+/// what Discord compresses payloads with.
+const ZLIB_HEADER: u8 = 0x78;
+
+/// The suffix of every message of a zlib stream, as added by a `Z_SYNC_FLUSH`.
+const ZLIB_SUFFIX: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+/// The compression of the payloads received from the gateway.
+///
+/// Compressing the whole connection as a single stream, via
+/// [`Self::ZlibStream`] or `ZstdStream` (with the `zstd_stream` feature), uses
+/// considerably less bandwidth than compressing payloads individually, at the
+/// cost of keeping a decompression context of a few dozen KiB per shard.
+///
+/// Refer to [Discord's documentation][docs] for more information.
+///
+/// [docs]: https://discord.com/developers/docs/topics/gateway#encoding-and-compression
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum TransportCompression {
+    /// Large payloads, such as `GUILD_CREATE`s, are compressed individually
+    /// with zlib, while small ones are sent uncompressed.
+    ///
+    /// This is the default.
+    Payload,
+    /// The connection is compressed as a single zlib stream, which is inflated
+    /// using a context kept for the lifetime of the connection.
+    ZlibStream,
+    /// The connection is compressed as a single zstd stream, which is
+    /// decompressed using a context kept for the lifetime of the connection.
+    ///
+    /// Requires the `zstd_stream` feature.
+    #[cfg(feature = "zstd_stream")]
+    ZstdStream,
+    /// Payloads are not compressed.
+    None,
+}
+
+impl TransportCompression {
+    /// The value of the `compress` query parameter of the gateway URL, if any.
+    pub(crate) fn query_value(self) -> Option<&'static str> {
+        match self {
+            Self::ZlibStream => Some("zlib-stream"),
+            #[cfg(feature = "zstd_stream")]
+            Self::ZstdStream => Some("zstd-stream"),
+            Self::Payload | Self::None => None,
+        }
+    }
+}
+
+impl Default for TransportCompression {
+    fn default() -> Self {
+        Self::Payload
+    }
+}
+
+/// The decompression context of a single connection.
+pub(crate) enum Decompressor {
+    Payload,
+    ZlibStream {
+        inflater: Decompress,
+        /// The compressed bytes of the message being received, as a message
+        /// may be split across multiple WebSocket messages.
+        buffer: Vec<u8>,
+    },
+    #[cfg(feature = "zstd_stream")]
+    ZstdStream(Box<ZstdDecoder<'static>>),
+    None,
+}
+
+impl Decompressor {
+    /// Creates a context for a new connection.
+    pub(crate) fn new(compression: TransportCompression) -> Result<Self> {
+        Ok(match compression {
+            TransportCompression::Payload => Self::Payload,
+            TransportCompression::ZlibStream => Self::ZlibStream {
+                inflater: Decompress::new(true),
+                buffer: Vec::new(),
+            },
+            #[cfg(feature = "zstd_stream")]
+            TransportCompression::ZstdStream => Self::ZstdStream(Box::new(ZstdDecoder::new()?)),
+            TransportCompression::None => Self::None,
+        })
+    }
+
+    /// Decompresses a binary WebSocket message.
+    ///
+    /// Returns `None` if the message is only a part of a zlib stream message,
+    /// in which case it is buffered until the rest is received.
+    pub(crate) fn decompress(&mut self, bytes: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(Some(match self {
+            // Small payloads are not compressed, which are only sent as
+            // binary messages when using ETF.
+            Self::Payload if bytes.first() != Some(&ZLIB_HEADER) => bytes.to_vec(),
+            Self::Payload => {
+                let mut decompressed = Vec::with_capacity(bytes.len() * 3);
+                ZlibDecoder::new(bytes).read_to_end(&mut decompressed)?;
+
+                decompressed
+            },
+            Self::ZlibStream {
+                inflater,
+                buffer,
+            } => {
+                buffer.extend_from_slice(bytes);
+
+                if !buffer.ends_with(&ZLIB_SUFFIX) {
+                    return Ok(None);
+                }
+
+                let decompressed = inflate(inflater, buffer);
+                buffer.clear();
+
+                decompressed?
+            },
+            #[cfg(feature = "zstd_stream")]
+            Self::ZstdStream(decoder) => decompress_zstd(decoder, bytes)?,
+            Self::None => bytes.to_vec(),
+        }))
+    }
+}
+
+/// Inflates a complete message of a zlib stream.
+fn inflate(inflater: &mut Decompress, input: &[u8]) -> IoResult<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() * 4);
+    let mut consumed = 0;
+
+    loop {
+        if output.len() == output.capacity() {
+            output.reserve(input.len() * 2);
+        }
+
+        let total_in = inflater.total_in();
+        inflater
+            .decompress_vec(&input[consumed..], &mut output, FlushDecompress::Sync)
+            .map_err(|why| IoError::new(IoErrorKind::InvalidData, why))?;
+        consumed += (inflater.total_in() - total_in) as usize;
+
+        // The output is complete once all input was consumed without filling
+        // the output buffer, as a full buffer may hold back pending output.
+        if consumed == input.len() && output.len() < output.capacity() {
+            return Ok(output);
+        }
+    }
+}
+
+/// Decompresses a message of a zstd stream.
+#[cfg(feature = "zstd_stream")]
+fn decompress_zstd(decoder: &mut ZstdDecoder<'static>, input: &[u8]) -> IoResult<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() * 4);
+    let mut input = InBuffer::around(input);
+
+    loop {
+        if output.len() == output.capacity() {
+            output.reserve(input.src.len() * 2);
+        }
+
+        let position = output.len();
+        let mut buffer = OutBuffer::around_pos(&mut output, position);
+        decoder.run(&mut input, &mut buffer)?;
+        let full = buffer.pos() == buffer.capacity();
+
+        if input.pos() == input.src.len() && !full {
+            return Ok(output);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+
+    use super::{Decompressor, TransportCompression};
+
+    #[test]
+    fn test_zlib_stream() {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        let mut decompressor = Decompressor::new(TransportCompression::ZlibStream).unwrap();
+
+        for payload in &[r#"{"op":10}"#, r#"{"op":11}"#] {
+            let start = encoder.get_ref().len();
+            encoder.write_all(payload.as_bytes()).unwrap();
+            encoder.flush().unwrap();
+            let message = encoder.get_ref()[start..].to_vec();
+
+            // A message split across WebSocket messages is buffered until
+            // the sync flush suffix is received.
+            let (first, second) = message.split_at(2);
+            assert_eq!(decompressor.decompress(first).unwrap(), None);
+            let decompressed = decompressor.decompress(second).unwrap().unwrap();
+            assert_eq!(decompressed, payload.as_bytes());
+        }
+    }
+
+    #[cfg(feature = "zstd_stream")]
+    #[test]
+    fn test_zstd_stream() {
+        use zstd::stream::raw::{Encoder, InBuffer, Operation, OutBuffer};
+
+        let mut encoder = Encoder::new(3).unwrap();
+        let mut decompressor = Decompressor::new(TransportCompression::ZstdStream).unwrap();
+
+        for payload in &[r#"{"op":10}"#, r#"{"op":11}"#] {
+            let mut message = [0; 128];
+            let mut output = OutBuffer::around(&mut message[..]);
+            encoder.run(&mut InBuffer::around(payload.as_bytes()), &mut output).unwrap();
+            encoder.flush(&mut output).unwrap();
+            let length = output.pos();
+
+            let decompressed = decompressor.decompress(&message[..length]).unwrap().unwrap();
+            assert_eq!(decompressed, payload.as_bytes());
+        }
+    }
+}
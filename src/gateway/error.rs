@@ -3,6 +3,8 @@ use std::fmt;
 
 use async_tungstenite::tungstenite::protocol::CloseFrame;
 
+use super::etf::Error as EtfError;
+
 /// An error that occurred while attempting to deal with the gateway.
 ///
 /// Note that - from a user standpoint - there should be no situation in which
@@ -53,6 +55,20 @@ pub enum Error {
     /// If an connection has been established but privileged gateway intents
     /// were provided without enabling them prior.
     DisallowedGatewayIntents,
+    /// A payload could not be encoded or decoded using ETF.
+    Etf(EtfError),
+    /// Not all member chunks requested via
+    /// [`ShardMessenger::chunk_guild_members`] were received before the
+    /// timeout elapsed or the shard shut down.
+    ///
+    /// [`ShardMessenger::chunk_guild_members`]: crate::client::bridge::gateway::ShardMessenger::chunk_guild_members
+    ChunkGuildIncomplete,
+    /// Not all shards of the new set of shards started by
+    /// [`ShardManager::reshard`] received their READY and guilds before the
+    /// timeout elapsed, or one of them failed.
+    ///
+    /// [`ShardManager::reshard`]: crate::client::bridge::gateway::ShardManager::reshard
+    ReshardFailed,
 }
 
 impl fmt::Display for Error {
@@ -74,6 +90,9 @@ impl fmt::Display for Error {
             Self::DisallowedGatewayIntents => {
                 f.write_str("Disallowed gateway intents were provided")
             },
+            Self::Etf(inner) => fmt::Display::fmt(inner, f),
+            Self::ChunkGuildIncomplete => f.write_str("Not all member chunks were received"),
+            Self::ReshardFailed => f.write_str("The new shards failed to become ready"),
         }
     }
 }
@@ -0,0 +1,1002 @@
+//! Encoding and decoding of the [External Term Format] of Erlang, which the
+//! gateway speaks when connected to with [`TransportEncoding::Etf`].
+//!
+//! Terms are mapped to the serde data model as follows:
+//!
+//! - the atoms `nil`, `true` and `false` are unit and booleans, any other atom
+//!   is a string;
+//! - binaries are strings, or bytes if they are not valid UTF-8;
+//! - lists and tuples are sequences, maps are maps;
+//! - integers, including big integers that fit into 64 bits, and floats are
+//!   numbers. Discord sends snowflakes as big integers.
+//!
+//! When encoding, strings and map keys are binaries, unit and `None` are the
+//! `nil` atom, and sequences are lists.
+//!
+//! [External Term Format]: https://www.erlang.org/doc/apps/erts/erl_ext_dist.html
+//! [`TransportEncoding::Etf`]: super::TransportEncoding::Etf
+
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::fmt;
+
+use serde::de::value::SeqDeserializer;
+use serde::de::{
+    self,
+    DeserializeOwned,
+    DeserializeSeed,
+    EnumAccess,
+    IntoDeserializer,
+    MapAccess,
+    SeqAccess,
+    VariantAccess,
+    Visitor,
+};
+use serde::ser::{self, Serialize};
+
+/// The version byte every encoded term starts with.
+pub const VERSION: u8 = 131;
+
+const NEW_FLOAT_EXT: u8 = 70;
+const SMALL_INTEGER_EXT: u8 = 97;
+const INTEGER_EXT: u8 = 98;
+const FLOAT_EXT: u8 = 99;
+const ATOM_EXT: u8 = 100;
+const SMALL_TUPLE_EXT: u8 = 104;
+const LARGE_TUPLE_EXT: u8 = 105;
+const NIL_EXT: u8 = 106;
+const STRING_EXT: u8 = 107;
+const LIST_EXT: u8 = 108;
+const BINARY_EXT: u8 = 109;
+const SMALL_BIG_EXT: u8 = 110;
+const LARGE_BIG_EXT: u8 = 111;
+const SMALL_ATOM_EXT: u8 = 115;
+const MAP_EXT: u8 = 116;
+const ATOM_UTF8_EXT: u8 = 118;
+const SMALL_ATOM_UTF8_EXT: u8 = 119;
+
+/// An error that occurred while encoding or decoding a term.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The input ended in the middle of a term.
+    Eof,
+    /// The input did not start with the [`VERSION`] byte.
+    InvalidVersion(u8),
+    /// A term of an unknown or unsupported type was found, such as a
+    /// compressed term or a pid.
+    UnsupportedTag(u8),
+    /// An integer does not fit into 64 bits.
+    IntegerTooLarge,
+    /// A list was not terminated by an empty list.
+    ImproperList,
+    /// A float in the old string format could not be parsed.
+    InvalidFloat,
+    /// A sequence or map is too long to be encoded.
+    TooLong,
+    /// There were bytes left after the term.
+    TrailingBytes,
+    /// A custom error raised by a [`Serialize`] or [`Deserialize`]
+    /// implementation.
+    ///
+    /// [`Deserialize`]: serde::Deserialize
+    Message(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Eof => f.write_str("Unexpected end of input"),
+            Self::InvalidVersion(version) => write!(f, "Invalid version byte {}", version),
+            Self::UnsupportedTag(tag) => write!(f, "Unsupported term tag {}", tag),
+            Self::IntegerTooLarge => f.write_str("Integer does not fit into 64 bits"),
+            Self::ImproperList => f.write_str("List is not a proper list"),
+            Self::InvalidFloat => f.write_str("Invalid float"),
+            Self::TooLong => f.write_str("Sequence or map too long to be encoded"),
+            Self::TrailingBytes => f.write_str("Trailing bytes after term"),
+            Self::Message(message) => f.write_str(message),
+        }
+    }
+}
+
+impl StdError for Error {}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self::Message(msg.to_string())
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self::Message(msg.to_string())
+    }
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Decodes a term, which must start with the [`VERSION`] byte.
+///
+/// # Examples
+///
+/// Decode a gateway event:
+///
+/// ```rust
+/// use serenity::gateway::etf;
+/// use serenity::model::event::GatewayEvent;
+///
+/// // {op: 11} as encoded by the gateway
+/// let bytes = [131, 116, 0, 0, 0, 1, 119, 2, b'o', b'p', 97, 11];
+///
+/// let event: GatewayEvent = etf::from_slice(&bytes)?;
+/// assert!(matches!(event, GatewayEvent::HeartbeatAck));
+/// # Ok::<(), etf::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the input is not a single valid term, or if it
+/// does not match `T`.
+pub fn from_slice<T: DeserializeOwned>(input: &[u8]) -> Result<T> {
+    let mut deserializer = Deserializer::from_slice(input)?;
+    let value = T::deserialize(&mut deserializer)?;
+
+    if deserializer.input.is_empty() {
+        Ok(value)
+    } else {
+        Err(Error::TrailingBytes)
+    }
+}
+
+/// Encodes a value as a term, starting with the [`VERSION`] byte.
+///
+/// # Errors
+///
+/// Returns an [`Error`] if the value fails to serialize, or if a sequence or
+/// map has more than [`u32::MAX`] elements.
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    let mut serializer = Serializer {
+        output: vec![VERSION],
+    };
+    value.serialize(&mut serializer)?;
+
+    Ok(serializer.output)
+}
+
+/// A deserializer of a term.
+#[derive(Debug)]
+pub struct Deserializer<'de> {
+    input: &'de [u8],
+}
+
+impl<'de> Deserializer<'de> {
+    /// Creates a deserializer of the term in the input, which must start with
+    /// the [`VERSION`] byte.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidVersion`] if the input does not start with the
+    /// version byte.
+    pub fn from_slice(input: &'de [u8]) -> Result<Self> {
+        match input.split_first() {
+            Some((&VERSION, input)) => Ok(Self {
+                input,
+            }),
+            Some((&version, _)) => Err(Error::InvalidVersion(version)),
+            None => Err(Error::Eof),
+        }
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'de [u8]> {
+        if self.input.len() < len {
+            return Err(Error::Eof);
+        }
+
+        let (bytes, rest) = self.input.split_at(len);
+        self.input = rest;
+
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16> {
+        let bytes = self.read_bytes(2)?;
+
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        let bytes = self.read_bytes(4)?;
+
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn peek_tag(&self) -> Result<u8> {
+        self.input.first().copied().ok_or(Error::Eof)
+    }
+
+    /// Reads the name of an atom, after its tag.
+    fn read_atom(&mut self, tag: u8) -> Result<&'de [u8]> {
+        let len = match tag {
+            SMALL_ATOM_EXT | SMALL_ATOM_UTF8_EXT => usize::from(self.read_u8()?),
+            _ => usize::from(self.read_u16()?),
+        };
+
+        self.read_bytes(len)
+    }
+
+    /// Whether the next term is the `nil` atom, consuming it if it is.
+    fn next_is_nil(&mut self) -> Result<bool> {
+        let mut lookahead = Deserializer {
+            input: self.input,
+        };
+
+        let tag = lookahead.read_u8()?;
+        if !matches!(tag, ATOM_EXT | SMALL_ATOM_EXT | ATOM_UTF8_EXT | SMALL_ATOM_UTF8_EXT) {
+            return Ok(false);
+        }
+
+        if lookahead.read_atom(tag)? == b"nil" {
+            self.input = lookahead.input;
+
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Reads the tail of a list, which must be the empty list.
+    fn end_list(&mut self) -> Result<()> {
+        if self.read_u8()? == NIL_EXT {
+            Ok(())
+        } else {
+            Err(Error::ImproperList)
+        }
+    }
+
+    fn visit_big<V: Visitor<'de>>(&mut self, len: usize, visitor: V) -> Result<V::Value> {
+        let negative = self.read_u8()? != 0;
+        let digits = self.read_bytes(len)?;
+
+        if digits.iter().skip(8).any(|&digit| digit != 0) {
+            return Err(Error::IntegerTooLarge);
+        }
+
+        let value = digits
+            .iter()
+            .take(8)
+            .enumerate()
+            .fold(0_u64, |value, (i, &digit)| value | u64::from(digit) << (8 * i));
+
+        if !negative {
+            visitor.visit_u64(value)
+        } else if value <= i64::MAX as u64 + 1 {
+            visitor.visit_i64((value as i64).wrapping_neg())
+        } else {
+            Err(Error::IntegerTooLarge)
+        }
+    }
+}
+
+fn visit_atom<'de, V: Visitor<'de>>(name: &'de [u8], visitor: V) -> Result<V::Value> {
+    match name {
+        b"nil" => visitor.visit_unit(),
+        b"true" => visitor.visit_bool(true),
+        b"false" => visitor.visit_bool(false),
+        _ => match std::str::from_utf8(name) {
+            Ok(name) => visitor.visit_borrowed_str(name),
+            // Atoms not tagged as UTF-8 are Latin-1.
+            Err(_) => visitor.visit_string(name.iter().map(|&byte| char::from(byte)).collect()),
+        },
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let tag = self.read_u8()?;
+
+        match tag {
+            SMALL_INTEGER_EXT => visitor.visit_u8(self.read_u8()?),
+            INTEGER_EXT => visitor.visit_i32(self.read_u32()? as i32),
+            NEW_FLOAT_EXT => {
+                let bytes = self.read_bytes(8)?;
+                let mut float = [0; 8];
+                float.copy_from_slice(bytes);
+
+                visitor.visit_f64(f64::from_be_bytes(float))
+            },
+            FLOAT_EXT => {
+                let bytes = self.read_bytes(31)?;
+                let float = std::str::from_utf8(bytes)
+                    .ok()
+                    .and_then(|float| float.trim_end_matches('\0').parse().ok())
+                    .ok_or(Error::InvalidFloat)?;
+
+                visitor.visit_f64(float)
+            },
+            SMALL_BIG_EXT => {
+                let len = usize::from(self.read_u8()?);
+
+                self.visit_big(len, visitor)
+            },
+            LARGE_BIG_EXT => {
+                let len = self.read_u32()? as usize;
+
+                self.visit_big(len, visitor)
+            },
+            ATOM_EXT | SMALL_ATOM_EXT | ATOM_UTF8_EXT | SMALL_ATOM_UTF8_EXT => {
+                visit_atom(self.read_atom(tag)?, visitor)
+            },
+            BINARY_EXT => {
+                let len = self.read_u32()? as usize;
+                let bytes = self.read_bytes(len)?;
+
+                match std::str::from_utf8(bytes) {
+                    Ok(string) => visitor.visit_borrowed_str(string),
+                    Err(_) => visitor.visit_borrowed_bytes(bytes),
+                }
+            },
+            STRING_EXT => {
+                let len = usize::from(self.read_u16()?);
+                let bytes = self.read_bytes(len)?;
+
+                let mut elements = SeqDeserializer::new(bytes.iter().copied());
+                let value = visitor.visit_seq(&mut elements)?;
+                elements.end()?;
+
+                Ok(value)
+            },
+            NIL_EXT => visitor.visit_seq(Elements {
+                de: self,
+                remaining: 0,
+            }),
+            LIST_EXT => {
+                let remaining = self.read_u32()? as usize;
+                let value = visitor.visit_seq(Elements {
+                    de: self,
+                    remaining,
+                })?;
+                self.end_list()?;
+
+                Ok(value)
+            },
+            SMALL_TUPLE_EXT | LARGE_TUPLE_EXT => {
+                let remaining = if tag == SMALL_TUPLE_EXT {
+                    usize::from(self.read_u8()?)
+                } else {
+                    self.read_u32()? as usize
+                };
+
+                visitor.visit_seq(Elements {
+                    de: self,
+                    remaining,
+                })
+            },
+            MAP_EXT => {
+                let remaining = self.read_u32()? as usize;
+
+                visitor.visit_map(Elements {
+                    de: self,
+                    remaining,
+                })
+            },
+            _ => Err(Error::UnsupportedTag(tag)),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.next_is_nil()? {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        if self.peek_tag()? == MAP_EXT {
+            self.read_u8()?;
+
+            if self.read_u32()? != 1 {
+                return Err(de::Error::custom("expected a map with a single variant"));
+            }
+
+            return visitor.visit_enum(self);
+        }
+
+        // A unit variant, given by its name.
+        let name: &'de str = de::Deserialize::deserialize(&mut *self)?;
+
+        visitor.visit_enum(name.into_deserializer())
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+/// The elements of a list, tuple or map.
+struct Elements<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    remaining: usize,
+}
+
+impl<'de> SeqAccess<'de> for Elements<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de> MapAccess<'de> for Elements<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de> EnumAccess<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self)> {
+        let variant = seed.deserialize(&mut *self)?;
+
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
+
+/// A serializer of terms.
+#[derive(Debug)]
+pub struct Serializer {
+    output: Vec<u8>,
+}
+
+impl Serializer {
+    fn write_u32(&mut self, value: u32) {
+        self.output.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn write_atom(&mut self, name: &str) {
+        self.output.push(SMALL_ATOM_UTF8_EXT);
+        self.output.push(name.len() as u8);
+        self.output.extend_from_slice(name.as_bytes());
+    }
+
+    fn write_binary(&mut self, bytes: &[u8]) -> Result<()> {
+        let len = u32::try_from(bytes.len()).map_err(|_| Error::TooLong)?;
+
+        self.output.push(BINARY_EXT);
+        self.write_u32(len);
+        self.output.extend_from_slice(bytes);
+
+        Ok(())
+    }
+
+    fn write_big(&mut self, negative: bool, value: u64) {
+        let digits = value.to_le_bytes();
+        let len = 8 - value.leading_zeros() as usize / 8;
+
+        self.output.push(SMALL_BIG_EXT);
+        self.output.push(len as u8);
+        self.output.push(u8::from(negative));
+        self.output.extend_from_slice(&digits[..len]);
+    }
+
+    /// Writes the header of a single entry map, for an enum variant.
+    fn write_variant(&mut self, variant: &str) -> Result<()> {
+        self.output.push(MAP_EXT);
+        self.write_u32(1);
+
+        self.write_binary(variant.as_bytes())
+    }
+
+    fn begin(&mut self, kind: Kind) -> Compound<'_> {
+        let start = self.output.len();
+
+        self.output.push(match kind {
+            Kind::List => LIST_EXT,
+            Kind::Map => MAP_EXT,
+        });
+        // The length is written once all elements are known.
+        self.write_u32(0);
+
+        Compound {
+            ser: self,
+            kind,
+            start,
+            len: 0,
+        }
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut Serializer {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        self.write_atom(if v { "true" } else { "false" });
+
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<()> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        if let Ok(v) = u8::try_from(v) {
+            self.output.push(SMALL_INTEGER_EXT);
+            self.output.push(v);
+        } else if let Ok(v) = i32::try_from(v) {
+            self.output.push(INTEGER_EXT);
+            self.output.extend_from_slice(&v.to_be_bytes());
+        } else {
+            self.write_big(v < 0, v.unsigned_abs());
+        }
+
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        if let Ok(v) = i64::try_from(v) {
+            return self.serialize_i64(v);
+        }
+
+        self.write_big(false, v);
+
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<()> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<()> {
+        self.output.push(NEW_FLOAT_EXT);
+        self.output.extend_from_slice(&v.to_be_bytes());
+
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<()> {
+        self.write_binary(v.encode_utf8(&mut [0; 4]).as_bytes())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        self.write_binary(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        self.write_binary(v)
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        self.write_atom("nil");
+
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<()> {
+        self.write_binary(variant.as_bytes())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.write_variant(variant)?;
+
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a>> {
+        Ok(self.begin(Kind::List))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'a>> {
+        Ok(self.begin(Kind::List))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'a>> {
+        Ok(self.begin(Kind::List))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>> {
+        self.write_variant(variant)?;
+
+        Ok(self.begin(Kind::List))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a>> {
+        Ok(self.begin(Kind::Map))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'a>> {
+        Ok(self.begin(Kind::Map))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>> {
+        self.write_variant(variant)?;
+
+        Ok(self.begin(Kind::Map))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Kind {
+    List,
+    Map,
+}
+
+/// A list or map being serialized.
+#[derive(Debug)]
+pub struct Compound<'a> {
+    ser: &'a mut Serializer,
+    kind: Kind,
+    /// The position of the tag in the output.
+    start: usize,
+    len: usize,
+}
+
+impl Compound<'_> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.len += 1;
+
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<()> {
+        let len = u32::try_from(self.len).map_err(|_| Error::TooLong)?;
+
+        match self.kind {
+            // The empty list is encoded as a single tag.
+            Kind::List if len == 0 => {
+                self.ser.output.truncate(self.start);
+                self.ser.output.push(NIL_EXT);
+
+                return Ok(());
+            },
+            Kind::List => self.ser.output.push(NIL_EXT),
+            Kind::Map => {},
+        }
+
+        self.ser.output[self.start + 1..self.start + 5].copy_from_slice(&len.to_be_bytes());
+
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeTuple for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeTupleVariant for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeMap for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        self.element(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<()> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeStruct for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.element(key)?;
+
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<()> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeStructVariant for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.element(key)?;
+
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<()> {
+        Compound::end(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_slice, to_vec, Error};
+    use crate::json::{json, Value};
+    use crate::model::event::{Event, GatewayEvent};
+    use crate::model::id::{ChannelId, GuildId, MessageId, UserId};
+
+    const HELLO: &[u8] = include_bytes!("fixtures/hello.etf");
+    const READY: &[u8] = include_bytes!("fixtures/ready.etf");
+    const MESSAGE_CREATE: &[u8] = include_bytes!("fixtures/message_create.etf");
+    const INVALID_SESSION: &[u8] = include_bytes!("fixtures/invalid_session.etf");
+
+    #[test]
+    fn test_decode_hello() {
+        match from_slice(HELLO).unwrap() {
+            GatewayEvent::Hello(interval) => assert_eq!(interval, 41250),
+            other => panic!("expected hello, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_ready() {
+        let (seq, ready) = match from_slice(READY).unwrap() {
+            GatewayEvent::Dispatch(seq, Event::Ready(event)) => (seq, event.ready),
+            other => panic!("expected ready, got {:?}", other),
+        };
+
+        assert_eq!(seq, 1);
+        assert_eq!(ready.version, 10);
+        assert_eq!(ready.session_id, "c4f3b7a1e2d94f0a8b6e5d3c2a1f0e9d");
+        assert_eq!(
+            ready.resume_gateway_url.as_deref(),
+            Some("wss://gateway-us-east1-b.discord.gg")
+        );
+        assert_eq!(ready.shard, Some([0, 1]));
+        assert_eq!(ready.user.id, UserId(1029384756102938475));
+        assert!(ready.user.bot);
+        assert_eq!(ready.guilds.iter().map(|guild| guild.id).collect::<Vec<_>>(), [
+            GuildId(381880193251409931),
+            GuildId(881207955029110855)
+        ],);
+    }
+
+    #[test]
+    fn test_decode_dispatch() {
+        let (seq, message) = match from_slice(MESSAGE_CREATE).unwrap() {
+            GatewayEvent::Dispatch(seq, Event::MessageCreate(event)) => (seq, event.message),
+            other => panic!("expected a message create, got {:?}", other),
+        };
+
+        assert_eq!(seq, 5);
+        assert_eq!(message.id, MessageId(1031646874382549032));
+        assert_eq!(message.channel_id, ChannelId(381880193700069377));
+        assert_eq!(message.guild_id, Some(GuildId(381880193251409931)));
+        assert_eq!(message.author.id, UserId(114941315417899012));
+        assert_eq!(message.content, "caf\u{e9} \u{1f44d}");
+        assert_eq!(message.edited_timestamp, None);
+        assert!(message.embeds.is_empty());
+    }
+
+    #[test]
+    fn test_decode_invalid_session() {
+        assert!(matches!(
+            from_slice(INVALID_SESSION).unwrap(),
+            GatewayEvent::InvalidateSession(false)
+        ));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let payload = json!({
+            "op": 2,
+            "d": {
+                "token": "token",
+                "intents": 513,
+                "shard": [0, 1],
+                "large_threshold": 250,
+                "presence": null,
+                "compress": false,
+                "guild_id": 81384788765712384_u64,
+                "afk": -1.5,
+                "empty": [],
+            },
+        });
+
+        let encoded = to_vec(&payload).unwrap();
+        let decoded: Value = from_slice(&encoded).unwrap();
+
+        assert_eq!(decoded, payload);
+        assert_eq!(from_slice::<Value>(&encoded[1..]), Err(Error::InvalidVersion(116)));
+    }
+}
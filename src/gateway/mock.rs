@@ -0,0 +1,841 @@
+//! A local gateway server for testing shards and clients without a network.
+//!
+//! A [`MockGateway`] accepts WebSocket connections and speaks the gateway
+//! protocol: it sends a HELLO, validates IDENTIFYs and RESUMEs, acknowledges
+//! heartbeats, and then performs the scripted [`MockAction`]s, such as
+//! sending dispatches, INVALID_SESSIONs or closing the connection with a
+//! close code. Sessions can be resumed, in which case the dispatches the
+//! client missed are replayed.
+//!
+//! A [`Client`] is pointed at the server via
+//! [`ClientBuilder::gateway_url`], and a [`Shard`] via the URL it is
+//! created with.
+//!
+//! Payloads are sent in the encoding requested by the client, but are never
+//! compressed, so [`TransportCompression::ZlibStream`] is not supported.
+//!
+//! # Examples
+//!
+//! Test that an event handler receives a message:
+//!
+//! ```rust,no_run
+//! # async fn run() -> serenity::Result<()> {
+//! use serenity::gateway::mock::{MockAction, MockGateway};
+//! use serenity::json::json;
+//! use serenity::prelude::*;
+//!
+//! struct Handler;
+//!
+//! impl EventHandler for Handler {}
+//!
+//! let gateway = MockGateway::builder()
+//!     .token("token")
+//!     .action(MockAction::dispatch("MESSAGE_CREATE", json!({ /* ... */ })))
+//!     .start()
+//!     .await?;
+//!
+//! let mut client = Client::builder("token", GatewayIntents::default())
+//!     .event_handler(Handler)
+//!     .gateway_url(gateway.url())
+//!     .await?;
+//!
+//! client.start().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Client`]: crate::Client
+//! [`ClientBuilder::gateway_url`]: crate::client::ClientBuilder::gateway_url
+//! [`Shard`]: super::Shard
+//! [`TransportCompression::ZlibStream`]: super::TransportCompression::ZlibStream
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use async_tungstenite::tokio::{accept_hdr_async, TokioAdapter};
+use async_tungstenite::tungstenite::handshake::server::{Request, Response};
+use async_tungstenite::tungstenite::protocol::frame::CloseFrame;
+use async_tungstenite::tungstenite::Message;
+use async_tungstenite::WebSocketStream;
+use futures::channel::mpsc::{self, UnboundedReceiver as Receiver, UnboundedSender as Sender};
+use futures::future::{select, Either};
+use futures::{SinkExt, StreamExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration};
+use tracing::debug;
+
+use super::{etf, TransportEncoding};
+use crate::constants::{close_codes, OpCode};
+use crate::internal::prelude::*;
+use crate::internal::tokio::spawn_named;
+use crate::json::{from_str, json, to_string};
+
+/// An action performed by a [`MockGateway`] on the connection of an
+/// identified or resumed client.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum MockAction {
+    /// Sends a dispatch with the given event name and data, with the next
+    /// sequence number of the session.
+    Dispatch(String, Value),
+    /// Requests a heartbeat from the client.
+    Heartbeat,
+    /// Sends an INVALID_SESSION, indicating whether the session is
+    /// resumable. If it is not, the session is discarded.
+    InvalidSession(bool),
+    /// Tells the client to reconnect and resume.
+    Reconnect,
+    /// Closes the connection with the given close code.
+    Close(u16),
+    /// Waits before performing the next action.
+    Wait(Duration),
+}
+
+impl MockAction {
+    /// Creates a [`Self::Dispatch`] of the event with the given name.
+    pub fn dispatch(name: impl Into<String>, data: Value) -> Self {
+        Self::Dispatch(name.into(), data)
+    }
+}
+
+/// A builder for a [`MockGateway`].
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct MockGatewayBuilder {
+    token: Option<String>,
+    heartbeat_interval: Duration,
+    resume_gateway_url: Option<String>,
+    actions: Vec<MockAction>,
+}
+
+impl MockGatewayBuilder {
+    /// Sets the token IDENTIFYs and RESUMEs must be sent with. Connections
+    /// sending another token are closed with
+    /// [`close_codes::AUTHENTICATION_FAILED`].
+    ///
+    /// By default, any token is accepted.
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+
+        self
+    }
+
+    /// Sets the heartbeat interval sent in the HELLO, 41.25 seconds by
+    /// default.
+    pub fn heartbeat_interval(mut self, heartbeat_interval: Duration) -> Self {
+        self.heartbeat_interval = heartbeat_interval;
+
+        self
+    }
+
+    /// Sets the URL sent as the `resume_gateway_url` of READY payloads, which
+    /// is the URL of the server by default.
+    pub fn resume_gateway_url(mut self, url: impl Into<String>) -> Self {
+        self.resume_gateway_url = Some(url.into());
+
+        self
+    }
+
+    /// Adds an action to perform once a client identified or resumed. More
+    /// actions can be added later via [`MockGateway::send`].
+    pub fn action(mut self, action: MockAction) -> Self {
+        self.actions.push(action);
+
+        self
+    }
+
+    /// Binds the server to a free local port and starts accepting
+    /// connections.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if binding the port failed.
+    pub async fn start(self) -> Result<MockGateway> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+
+        let (actions_tx, actions_rx) = mpsc::unbounded();
+
+        for action in self.actions {
+            drop(actions_tx.unbounded_send(action));
+        }
+
+        let url = format!("ws://{}", addr);
+        let server = Arc::new(Server {
+            resume_gateway_url: self.resume_gateway_url.unwrap_or_else(|| url.clone()),
+            url,
+            token: self.token,
+            heartbeat_interval: self.heartbeat_interval,
+            actions: Mutex::new(actions_rx),
+            state: Mutex::new(State::default()),
+        });
+
+        let task = spawn_named("gateway::mock::run", Arc::clone(&server).run(listener));
+
+        Ok(MockGateway {
+            addr,
+            actions: actions_tx,
+            server,
+            task,
+        })
+    }
+}
+
+impl Default for MockGatewayBuilder {
+    fn default() -> Self {
+        Self {
+            token: None,
+            heartbeat_interval: Duration::from_millis(41_250),
+            resume_gateway_url: None,
+            actions: Vec::new(),
+        }
+    }
+}
+
+/// A local gateway server, which stops when dropped.
+///
+/// Refer to the [module-level documentation][module docs] for more
+/// information.
+///
+/// [module docs]: self
+#[derive(Debug)]
+pub struct MockGateway {
+    addr: SocketAddr,
+    actions: Sender<MockAction>,
+    server: Arc<Server>,
+    task: JoinHandle<()>,
+}
+
+impl MockGateway {
+    /// Creates a builder for a server.
+    pub fn builder() -> MockGatewayBuilder {
+        MockGatewayBuilder::default()
+    }
+
+    /// The address the server is bound to.
+    #[must_use]
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The URL to connect to the server at.
+    #[must_use]
+    pub fn url(&self) -> String {
+        self.server.url.clone()
+    }
+
+    /// Queues an action to perform on the connection of the client, after
+    /// the previously queued ones.
+    pub fn send(&self, action: MockAction) {
+        drop(self.actions.unbounded_send(action));
+    }
+
+    /// Returns the payloads received from clients so far, in order.
+    pub async fn received(&self) -> Vec<Value> {
+        self.server.state.lock().await.received.clone()
+    }
+
+    /// Returns the number of connections accepted so far.
+    pub async fn connections(&self) -> u64 {
+        self.server.state.lock().await.connections
+    }
+}
+
+impl Drop for MockGateway {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[derive(Debug)]
+struct Server {
+    url: String,
+    resume_gateway_url: String,
+    token: Option<String>,
+    heartbeat_interval: Duration,
+    /// The queued actions, taken by the connection of the client that
+    /// identified or resumed most recently.
+    actions: Mutex<Receiver<MockAction>>,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    received: Vec<Value>,
+    connections: u64,
+    /// The dispatches sent per session ID, which are replayed on RESUME.
+    sessions: HashMap<String, Vec<Value>>,
+}
+
+/// The session of a connection, once it identified or resumed.
+struct Session {
+    id: String,
+    seq: u64,
+}
+
+enum Incoming {
+    Payload(Value),
+    Closed(Option<u16>),
+}
+
+struct Connection {
+    stream: WebSocketStream<TokioAdapter<TcpStream>>,
+    encoding: TransportEncoding,
+}
+
+impl Connection {
+    async fn send(&mut self, payload: &Value) -> Result<()> {
+        let message = match self.encoding {
+            TransportEncoding::Json => Message::Text(to_string(payload)?),
+            TransportEncoding::Etf => Message::Binary(etf::to_vec(payload)?),
+        };
+
+        Ok(self.stream.send(message).await?)
+    }
+
+    async fn recv(&mut self) -> Result<Incoming> {
+        loop {
+            return Ok(match self.stream.next().await {
+                Some(Ok(Message::Text(mut text))) => Incoming::Payload(from_str(&mut text)?),
+                Some(Ok(Message::Binary(bytes))) => Incoming::Payload(etf::from_slice(&bytes)?),
+                Some(Ok(Message::Close(frame))) => {
+                    Incoming::Closed(frame.map(|frame| frame.code.into()))
+                },
+                Some(Ok(_)) => continue,
+                Some(Err(_)) | None => Incoming::Closed(None),
+            });
+        }
+    }
+
+    async fn close(&mut self, code: u16) -> Result<()> {
+        let frame = CloseFrame {
+            code: code.into(),
+            reason: "".into(),
+        };
+
+        Ok(self.stream.close(Some(frame)).await?)
+    }
+}
+
+impl Server {
+    async fn run(self: Arc<Self>, listener: TcpListener) {
+        while let Ok((stream, _)) = listener.accept().await {
+            let server = Arc::clone(&self);
+
+            spawn_named("gateway::mock::connection", async move {
+                if let Err(why) = server.handle(stream).await {
+                    debug!("[Mock Gateway] Connection failed: {:?}", why);
+                }
+            });
+        }
+    }
+
+    async fn handle(&self, stream: TcpStream) -> Result<()> {
+        let mut encoding = TransportEncoding::Json;
+        let callback = |request: &Request, response: Response| {
+            if request.uri().query().map_or(false, |query| query.contains("encoding=etf")) {
+                encoding = TransportEncoding::Etf;
+            }
+
+            Ok(response)
+        };
+
+        let stream = accept_hdr_async(stream, callback).await?;
+        let mut connection = Connection {
+            stream,
+            encoding,
+        };
+
+        self.state.lock().await.connections += 1;
+
+        let heartbeat_interval = self.heartbeat_interval.as_millis() as u64;
+        connection
+            .send(&json!({
+                "op": OpCode::Hello.num(),
+                "d": {"heartbeat_interval": heartbeat_interval},
+            }))
+            .await?;
+
+        let mut session = match self.handshake(&mut connection).await? {
+            Some(session) => session,
+            None => return Ok(()),
+        };
+
+        // Only the connection of the latest client performs actions.
+        let mut actions = self.actions.lock().await;
+
+        loop {
+            let next = {
+                let incoming = Box::pin(connection.recv());
+
+                match select(incoming, actions.next()).await {
+                    Either::Left((incoming, _)) => Either::Left(incoming),
+                    Either::Right((action, _)) => Either::Right(action),
+                }
+            };
+
+            match next {
+                Either::Left(incoming) => match incoming? {
+                    Incoming::Payload(payload) => {
+                        let op = self.record(&payload).await;
+
+                        if op == Some(OpCode::Heartbeat.num()) {
+                            connection.send(&json!({"op": OpCode::HeartbeatAck.num()})).await?;
+                        } else if op == Some(OpCode::Identify.num())
+                            || op == Some(OpCode::Resume.num())
+                        {
+                            connection.close(close_codes::ALREADY_AUTHENTICATED).await?;
+
+                            return Ok(());
+                        }
+                    },
+                    Incoming::Closed(code) => {
+                        // Closing with 1000 or 1001 ends the session.
+                        if matches!(code, Some(1000 | 1001)) {
+                            self.state.lock().await.sessions.remove(&session.id);
+                        }
+
+                        return Ok(());
+                    },
+                },
+                Either::Right(Some(action)) => {
+                    if !self.perform(&mut connection, &mut session, action).await? {
+                        return Ok(());
+                    }
+                },
+                Either::Right(None) => return Ok(()),
+            }
+        }
+    }
+
+    /// Waits for an IDENTIFY or RESUME, returning the session if the client
+    /// identified or resumed.
+    async fn handshake(&self, connection: &mut Connection) -> Result<Option<Session>> {
+        loop {
+            let payload = match connection.recv().await? {
+                Incoming::Payload(payload) => payload,
+                Incoming::Closed(_) => return Ok(None),
+            };
+
+            let op = self.record(&payload).await;
+            let data = payload.get("d");
+            let token = data.and_then(|data| data.get("token")).and_then(Value::as_str);
+
+            if op == Some(OpCode::Heartbeat.num()) {
+                connection.send(&json!({"op": OpCode::HeartbeatAck.num()})).await?;
+
+                continue;
+            }
+
+            if op != Some(OpCode::Identify.num()) && op != Some(OpCode::Resume.num()) {
+                connection.close(close_codes::NOT_AUTHENTICATED).await?;
+
+                return Ok(None);
+            }
+
+            let token = if let Some(token) = token {
+                token.trim_start_matches("Bot ")
+            } else {
+                connection.close(close_codes::DECODE_ERROR).await?;
+
+                return Ok(None);
+            };
+
+            if self.token.as_ref().map_or(false, |expected| expected != token) {
+                connection.close(close_codes::AUTHENTICATION_FAILED).await?;
+
+                return Ok(None);
+            }
+
+            if op == Some(OpCode::Identify.num()) {
+                return self.identify(connection, data).await.map(Some);
+            }
+
+            let session_id = data.and_then(|data| data.get("session_id")).and_then(Value::as_str);
+            let seq = data.and_then(|data| data.get("seq")).and_then(Value::as_u64).unwrap_or(0);
+
+            if let Some(session) = self.resume(connection, session_id, seq).await? {
+                return Ok(Some(session));
+            }
+        }
+    }
+
+    async fn identify(&self, connection: &mut Connection, data: Option<&Value>) -> Result<Session> {
+        let mut session = {
+            let mut state = self.state.lock().await;
+            let id = format!("mock-session-{}", state.connections);
+            state.sessions.insert(id.clone(), Vec::new());
+
+            Session {
+                id,
+                seq: 0,
+            }
+        };
+
+        let shard =
+            data.and_then(|data| data.get("shard")).cloned().unwrap_or_else(|| json!([0, 1]));
+        let ready = json!({
+            "v": crate::constants::GATEWAY_VERSION,
+            "user": {
+                "id": "1",
+                "username": "Mock",
+                "discriminator": "0000",
+                "avatar": null,
+                "bot": true,
+                "email": null,
+                "mfa_enabled": false,
+                "verified": true,
+            },
+            "guilds": [],
+            "session_id": session.id,
+            "resume_gateway_url": self.resume_gateway_url,
+            "shard": shard,
+            "application": {"id": "1", "flags": 0},
+        });
+
+        self.dispatch(connection, &mut session, "READY", ready).await?;
+
+        Ok(session)
+    }
+
+    /// Resumes the session, replaying the missed dispatches. If the session
+    /// does not exist, an INVALID_SESSION is sent instead.
+    async fn resume(
+        &self,
+        connection: &mut Connection,
+        session_id: Option<&str>,
+        seq: u64,
+    ) -> Result<Option<Session>> {
+        let missed = match session_id {
+            Some(id) => self.state.lock().await.sessions.get(id).map(|dispatches| {
+                dispatches
+                    .iter()
+                    .filter(|dispatch| dispatch["s"].as_u64() > Some(seq))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            }),
+            None => None,
+        };
+
+        let (session_id, missed) = if let (Some(session_id), Some(missed)) = (session_id, missed) {
+            (session_id, missed)
+        } else {
+            connection.send(&json!({"op": OpCode::InvalidSession.num(), "d": false})).await?;
+
+            return Ok(None);
+        };
+
+        let mut session = Session {
+            id: session_id.to_string(),
+            seq,
+        };
+
+        for dispatch in missed {
+            session.seq = dispatch["s"].as_u64().unwrap_or(session.seq);
+            connection.send(&dispatch).await?;
+        }
+
+        self.dispatch(connection, &mut session, "RESUMED", json!({"_trace": []})).await?;
+
+        Ok(Some(session))
+    }
+
+    /// Performs an action, returning whether the connection is still open.
+    async fn perform(
+        &self,
+        connection: &mut Connection,
+        session: &mut Session,
+        action: MockAction,
+    ) -> Result<bool> {
+        match action {
+            MockAction::Dispatch(name, data) => {
+                self.dispatch(connection, session, &name, data).await?;
+            },
+            MockAction::Heartbeat => {
+                connection.send(&json!({"op": OpCode::Heartbeat.num(), "d": session.seq})).await?;
+            },
+            MockAction::InvalidSession(resumable) => {
+                if !resumable {
+                    self.state.lock().await.sessions.remove(&session.id);
+                }
+
+                connection
+                    .send(&json!({"op": OpCode::InvalidSession.num(), "d": resumable}))
+                    .await?;
+            },
+            MockAction::Reconnect => {
+                connection.send(&json!({"op": OpCode::Reconnect.num()})).await?;
+            },
+            MockAction::Close(code) => {
+                connection.close(code).await?;
+
+                return Ok(false);
+            },
+            MockAction::Wait(duration) => sleep(duration).await,
+        }
+
+        Ok(true)
+    }
+
+    async fn dispatch(
+        &self,
+        connection: &mut Connection,
+        session: &mut Session,
+        name: &str,
+        data: Value,
+    ) -> Result<()> {
+        session.seq += 1;
+
+        let payload = json!({
+            "op": OpCode::Event.num(),
+            "t": name,
+            "s": session.seq,
+            "d": data,
+        });
+
+        if let Some(dispatches) = self.state.lock().await.sessions.get_mut(&session.id) {
+            dispatches.push(payload.clone());
+        }
+
+        connection.send(&payload).await
+    }
+
+    /// Records a payload received from a client, returning its opcode.
+    async fn record(&self, payload: &Value) -> Option<u64> {
+        self.state.lock().await.received.push(payload.clone());
+
+        payload.get("op").and_then(Value::as_u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tokio::sync::Mutex;
+    use tokio::time::{timeout, Duration};
+
+    use super::{MockAction, MockGateway};
+    use crate::gateway::{
+        Shard,
+        ShardAction,
+        ShardSession,
+        TransportCompression,
+        TransportEncoding,
+    };
+    use crate::json::{from_str, json, to_string};
+    use crate::model::event::{Event, GatewayEvent};
+    use crate::model::gateway::GatewayIntents;
+
+    /// Receives payloads, performing the shard's actions, until a dispatch
+    /// is received.
+    async fn next_event(shard: &mut Shard) -> Event {
+        loop {
+            let payload = match shard.recv_json().await.unwrap() {
+                Some(payload) => payload,
+                None => continue,
+            };
+            let event = payload.deserialize();
+
+            if let Some(ShardAction::Identify) = shard.handle_event(&event).unwrap() {
+                shard.identify().await.unwrap();
+            }
+
+            if let Ok(GatewayEvent::Dispatch(_, event)) = event {
+                return event;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_identify_and_resume() {
+        let gateway = MockGateway::builder()
+            .token("token")
+            .action(MockAction::dispatch("CUSTOM", json!({})))
+            .action(MockAction::Close(4000))
+            .action(MockAction::dispatch("MISSED", json!({})))
+            .start()
+            .await
+            .unwrap();
+
+        let url = Arc::new(Mutex::new(gateway.url()));
+        let mut shard =
+            Shard::new(url, "token", [0, 1], GatewayIntents::non_privileged()).await.unwrap();
+
+        timeout(Duration::from_secs(5), async {
+            assert!(matches!(next_event(&mut shard).await, Event::Ready(_)));
+            assert!(matches!(next_event(&mut shard).await, Event::Unknown(_)));
+            assert_eq!(shard.seq(), 2);
+            assert_eq!(shard.resume_ws_url(), Some(&*gateway.url()));
+
+            // The connection is closed, after which the session is resumed.
+            assert!(shard.recv_json().await.is_err());
+            shard.resume().await.unwrap();
+
+            assert!(matches!(next_event(&mut shard).await, Event::Resumed(_)));
+            assert!(matches!(next_event(&mut shard).await, Event::Unknown(_)));
+            assert_eq!(shard.seq(), 4);
+        })
+        .await
+        .unwrap();
+
+        let ops = gateway
+            .received()
+            .await
+            .iter()
+            .filter_map(|payload| payload["op"].as_u64())
+            .filter(|op| *op != 1)
+            .collect::<Vec<_>>();
+
+        assert_eq!(ops, [2, 6]);
+        assert_eq!(gateway.connections().await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_resume_at_resume_gateway_url() {
+        let resume_gateway = MockGateway::builder().token("token").start().await.unwrap();
+        let gateway = MockGateway::builder()
+            .token("token")
+            .resume_gateway_url(resume_gateway.url())
+            .action(MockAction::Close(4000))
+            .start()
+            .await
+            .unwrap();
+
+        let url = Arc::new(Mutex::new(gateway.url()));
+        let mut shard =
+            Shard::new(url, "token", [0, 1], GatewayIntents::non_privileged()).await.unwrap();
+
+        timeout(Duration::from_secs(5), async {
+            assert!(matches!(next_event(&mut shard).await, Event::Ready(_)));
+            assert_eq!(shard.resume_ws_url(), Some(&*resume_gateway.url()));
+
+            assert!(shard.recv_json().await.is_err());
+            shard.resume().await.unwrap();
+
+            // The session is unknown to the other server, which invalidates it.
+            loop {
+                if let Some(payload) = shard.recv_json().await.unwrap() {
+                    if let GatewayEvent::InvalidateSession(resumable) =
+                        payload.deserialize().unwrap()
+                    {
+                        assert!(!resumable);
+                        break;
+                    }
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        let resume = resume_gateway
+            .received()
+            .await
+            .into_iter()
+            .find(|payload| payload["op"].as_u64() == Some(6))
+            .unwrap();
+
+        assert_eq!(resume["d"]["session_id"], "mock-session-1");
+        assert_eq!(resume["d"]["seq"], 1);
+        assert_eq!(gateway.connections().await, 1);
+        assert_eq!(resume_gateway.connections().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_resume_falls_back_to_gateway_url() {
+        // Nothing listens at the port once the listener is dropped.
+        let unreachable = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+
+            format!("ws://{}", listener.local_addr().unwrap())
+        };
+        let gateway = MockGateway::builder()
+            .token("token")
+            .resume_gateway_url(&*unreachable)
+            .action(MockAction::Close(4000))
+            .start()
+            .await
+            .unwrap();
+
+        let url = Arc::new(Mutex::new(gateway.url()));
+        let mut shard =
+            Shard::new(url, "token", [0, 1], GatewayIntents::non_privileged()).await.unwrap();
+
+        timeout(Duration::from_secs(5), async {
+            assert!(matches!(next_event(&mut shard).await, Event::Ready(_)));
+            assert_eq!(shard.resume_ws_url(), Some(&*unreachable));
+
+            assert!(shard.recv_json().await.is_err());
+            shard.resume().await.unwrap();
+
+            assert!(matches!(next_event(&mut shard).await, Event::Resumed(_)));
+            assert_eq!(shard.resume_ws_url(), None);
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(gateway.connections().await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_restored_session_resumes() {
+        let gateway = MockGateway::builder()
+            .token("token")
+            .action(MockAction::dispatch("CUSTOM", json!({})))
+            .start()
+            .await
+            .unwrap();
+
+        let url = Arc::new(Mutex::new(gateway.url()));
+
+        timeout(Duration::from_secs(5), async {
+            let session = {
+                let mut shard =
+                    Shard::new(Arc::clone(&url), "token", [0, 1], GatewayIntents::non_privileged())
+                        .await
+                        .unwrap();
+
+                assert!(matches!(next_event(&mut shard).await, Event::Ready(_)));
+                assert!(matches!(next_event(&mut shard).await, Event::Unknown(_)));
+
+                shard.session().unwrap()
+            };
+
+            // The session is stored, such as by another process.
+            let mut stored = to_string(&session).unwrap();
+            let session: ShardSession = from_str(&mut stored).unwrap();
+
+            let mut shard = Shard::resuming(
+                url,
+                "token",
+                GatewayIntents::non_privileged(),
+                TransportCompression::default(),
+                TransportEncoding::default(),
+                session,
+            )
+            .await
+            .unwrap();
+
+            assert!(matches!(next_event(&mut shard).await, Event::Resumed(_)));
+            assert_eq!(shard.seq(), 3);
+        })
+        .await
+        .unwrap();
+
+        // The restored session is resumed without connecting twice.
+        assert_eq!(gateway.connections().await, 2);
+
+        let resume = gateway
+            .received()
+            .await
+            .into_iter()
+            .find(|payload| payload["op"].as_u64() == Some(6))
+            .unwrap();
+
+        assert_eq!(resume["d"]["session_id"], "mock-session-1");
+        assert_eq!(resume["d"]["seq"], 2);
+    }
+}
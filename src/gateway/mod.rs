@@ -46,17 +46,25 @@
 //! [`Client::start_shards`]: crate::Client::start_shards
 //! [docs]: https://discordapp.com/developers/docs/topics/gateway#sharding
 
+mod compression;
 mod error;
+pub mod etf;
+#[cfg(feature = "mock_gateway")]
+pub mod mock;
+mod ratelimiter;
 mod shard;
 mod ws_client_ext;
 
 use std::fmt;
 
+pub(crate) use self::compression::Decompressor;
+pub use self::compression::TransportCompression;
 pub use self::error::Error as GatewayError;
-pub use self::shard::Shard;
+pub(crate) use self::ratelimiter::CommandKind;
+pub use self::shard::{Shard, ShardSession};
 pub use self::ws_client_ext::WebSocketGatewayClientExt;
 #[cfg(feature = "client")]
-use crate::client::bridge::gateway::ShardClientMessage;
+use crate::client::bridge::gateway::{CollectGuildMembers, ShardClientMessage};
 use crate::json::Value;
 use crate::model::gateway::Activity;
 use crate::model::user::OnlineStatus;
@@ -157,8 +165,18 @@ pub enum InterMessage {
     #[cfg(feature = "client")]
     Client(Box<ShardClientMessage>),
     Json(Value),
+    #[cfg(feature = "client")]
+    #[doc(hidden)]
+    CollectGuildMembers(CollectGuildMembersRequest),
 }
 
+/// The payload of [`InterMessage::CollectGuildMembers`], which can't be
+/// constructed or inspected outside of the library.
+#[cfg(feature = "client")]
+#[doc(hidden)]
+#[derive(Clone, Debug)]
+pub struct CollectGuildMembersRequest(pub(crate) Box<CollectGuildMembers>);
+
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum ShardAction {
@@ -176,3 +194,36 @@ pub enum ReconnectType {
     /// Indicator that a new connection should be made by sending a RESUME.
     Resume,
 }
+
+/// The encoding of the payloads sent to and received from the gateway.
+///
+/// Refer to [Discord's documentation][docs] for more information.
+///
+/// [docs]: https://discord.com/developers/docs/topics/gateway#encoding-and-compression
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum TransportEncoding {
+    /// Payloads are encoded as JSON.
+    ///
+    /// This is the default.
+    Json,
+    /// Payloads are encoded in the External Term Format of Erlang, which is
+    /// more compact and faster to decode. See the [`etf`] module.
+    Etf,
+}
+
+impl TransportEncoding {
+    /// The value of the `encoding` query parameter of the gateway URL.
+    pub(crate) fn query_value(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Etf => "etf",
+        }
+    }
+}
+
+impl Default for TransportEncoding {
+    fn default() -> Self {
+        Self::Json
+    }
+}
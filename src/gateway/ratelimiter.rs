@@ -0,0 +1,145 @@
+use std::collections::VecDeque;
+
+use tokio::time::{Duration, Instant};
+
+use crate::json::Value;
+
+/// The number of commands Discord allows per connection per [`WINDOW`].
+const COMMANDS_PER_WINDOW: usize = 120;
+
+/// The window in which at most [`COMMANDS_PER_WINDOW`] commands may be sent.
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// The heartbeat interval assumed before Discord sent a HELLO.
+const DEFAULT_HEARTBEAT_INTERVAL: u64 = 41_250;
+
+/// The kind of a command, determining how it is ratelimited.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum CommandKind {
+    /// A heartbeat, which is always sent immediately, using the headroom
+    /// reserved for heartbeats.
+    Heartbeat,
+    /// An IDENTIFY or RESUME, which is always sent immediately, ahead of any
+    /// queued commands.
+    Session,
+    /// Any other command, such as a presence update or a request for guild
+    /// members, which is queued once the budget of the window is used up.
+    Other,
+}
+
+/// Limits the commands sent over a connection to Discord's budget of 120
+/// commands per 60 seconds, reserving headroom for heartbeats, IDENTIFYs and
+/// RESUMEs.
+#[derive(Debug, Default)]
+pub(crate) struct GatewayRatelimiter {
+    /// The instants at which the commands of the current window were sent.
+    sent: VecDeque<Instant>,
+    /// The commands waiting for budget to be sent, in order.
+    queue: VecDeque<Value>,
+}
+
+impl GatewayRatelimiter {
+    /// Resets the budget for a new connection. Queued commands are kept.
+    pub(crate) fn reset(&mut self) {
+        self.sent.clear();
+    }
+
+    /// The number of commands waiting to be sent.
+    pub(crate) fn backlog(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Queues a command to be sent once there is budget for it.
+    pub(crate) fn enqueue(&mut self, payload: Value) {
+        self.queue.push_back(payload);
+    }
+
+    /// Takes the next queued command if it may be sent now, recording it as
+    /// sent.
+    pub(crate) fn next_queued(&mut self, heartbeat_interval: Option<u64>) -> Option<Value> {
+        if self.queue.is_empty() || !self.acquire(CommandKind::Other, heartbeat_interval) {
+            return None;
+        }
+
+        self.queue.pop_front()
+    }
+
+    /// Records a command of the given kind as sent, if it may be sent now.
+    ///
+    /// Commands other than heartbeats, IDENTIFYs and RESUMEs may only be sent
+    /// if none are queued.
+    pub(crate) fn try_acquire(
+        &mut self,
+        kind: CommandKind,
+        heartbeat_interval: Option<u64>,
+    ) -> bool {
+        if kind == CommandKind::Other && !self.queue.is_empty() {
+            return false;
+        }
+
+        self.acquire(kind, heartbeat_interval)
+    }
+
+    fn acquire(&mut self, kind: CommandKind, heartbeat_interval: Option<u64>) -> bool {
+        let now = Instant::now();
+
+        while self.sent.front().map_or(false, |sent| now.duration_since(*sent) >= WINDOW) {
+            self.sent.pop_front();
+        }
+
+        // Heartbeats, IDENTIFYs and RESUMEs must never be held back, and the
+        // reserved headroom makes sure they fit within the budget.
+        if kind == CommandKind::Other
+            && self.sent.len() >= COMMANDS_PER_WINDOW - reserved(heartbeat_interval)
+        {
+            return false;
+        }
+
+        self.sent.push_back(now);
+
+        true
+    }
+}
+
+/// The number of commands per window reserved for heartbeats, IDENTIFYs and
+/// RESUMEs.
+fn reserved(heartbeat_interval: Option<u64>) -> usize {
+    let interval = heartbeat_interval.unwrap_or(DEFAULT_HEARTBEAT_INTERVAL).max(1);
+    let window = WINDOW.as_millis() as u64;
+
+    // Heartbeats requested by Discord and the one sent after HELLO may be sent
+    // in addition to the regular ones, as may a single IDENTIFY or RESUME.
+    let heartbeats = (window + interval - 1) / interval + 2;
+
+    (heartbeats as usize + 1).min(COMMANDS_PER_WINDOW)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommandKind, GatewayRatelimiter, COMMANDS_PER_WINDOW};
+    use crate::json::json;
+
+    #[test]
+    fn test_reserves_headroom() {
+        let mut ratelimiter = GatewayRatelimiter::default();
+        let interval = Some(41_250);
+
+        let sent = (0..COMMANDS_PER_WINDOW)
+            .take_while(|_| ratelimiter.try_acquire(CommandKind::Other, interval))
+            .count();
+        assert_eq!(sent, COMMANDS_PER_WINDOW - 5);
+
+        // Other commands are queued, while heartbeats and identifies are not.
+        ratelimiter.enqueue(json!({"op": 3}));
+        assert!(!ratelimiter.try_acquire(CommandKind::Other, interval));
+        assert!(ratelimiter.try_acquire(CommandKind::Heartbeat, interval));
+        assert!(ratelimiter.try_acquire(CommandKind::Session, interval));
+        assert_eq!(ratelimiter.next_queued(interval), None);
+        assert_eq!(ratelimiter.backlog(), 1);
+
+        // A new connection has a new budget.
+        ratelimiter.reset();
+        assert_eq!(ratelimiter.next_queued(interval), Some(json!({"op": 3})));
+        assert_eq!(ratelimiter.backlog(), 0);
+    }
+}
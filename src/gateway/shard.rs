@@ -7,20 +7,31 @@ use tokio::sync::Mutex;
 use tracing::{debug, error, info, instrument, trace, warn};
 use url::Url;
 
+use super::ratelimiter::GatewayRatelimiter;
+use super::ws_client_ext::{
+    chunk_guild_payload,
+    heartbeat_payload,
+    identify_payload,
+    presence_update_payload,
+    resume_payload,
+};
 use super::{
+    CommandKind,
     ConnectionStage,
     CurrentPresence,
+    Decompressor,
     GatewayError,
     ReconnectType,
     ShardAction,
-    WebSocketGatewayClientExt,
+    TransportCompression,
+    TransportEncoding,
     WsStream,
 };
 use crate::client::bridge::gateway::ChunkGuildFilter;
 use crate::constants::{self, close_codes};
 use crate::http::Http;
 use crate::internal::prelude::*;
-use crate::internal::ws_impl::create_client;
+use crate::internal::ws_impl::{create_client, Payload, ReceiverExt, SenderExt};
 use crate::model::event::{Event, GatewayEvent};
 use crate::model::gateway::{Activity, GatewayIntents};
 use crate::model::id::GuildId;
@@ -77,8 +88,16 @@ pub struct Shard {
     // This _must_ be set to `true` in `Shard::handle_event`'s
     // `Ok(GatewayEvent::HeartbeatAck)` arm.
     last_heartbeat_acknowledged: bool,
+    /// The number of consecutive heartbeats which were not acknowledged.
+    missed_heartbeat_acks: u32,
+    /// The number of consecutive unacknowledged heartbeats after which the
+    /// connection is considered zombied.
+    max_missed_heartbeat_acks: u32,
     seq: u64,
     session_id: Option<String>,
+    /// The URL to connect to when resuming the session, as given in the
+    /// READY payload.
+    resume_ws_url: Option<String>,
     shard_info: [u64; 2],
     stage: ConnectionStage,
     /// Instant of when the shard was started.
@@ -88,6 +107,32 @@ pub struct Shard {
     pub token: String,
     ws_url: Arc<Mutex<String>>,
     pub intents: GatewayIntents,
+    compression: TransportCompression,
+    decompressor: Decompressor,
+    encoding: TransportEncoding,
+    ratelimiter: GatewayRatelimiter,
+}
+
+/// The state of a [`Shard`]'s session, which allows resuming it from another
+/// process, e.g. after a restart for a deployment.
+///
+/// Sessions are exported via [`ShardManager::shutdown_all_resumable`], and
+/// given back to a new client via [`ClientBuilder::shard_sessions`].
+///
+/// [`ShardManager::shutdown_all_resumable`]: crate::client::bridge::gateway::ShardManager::shutdown_all_resumable
+/// [`ClientBuilder::shard_sessions`]: crate::client::ClientBuilder::shard_sessions
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ShardSession {
+    /// The ID of the shard and the total number of shards. A session can only
+    /// be resumed by a shard with the same shard information.
+    pub shard_info: [u64; 2],
+    /// The ID of the session.
+    pub session_id: String,
+    /// The sequence number of the last event received.
+    pub seq: u64,
+    /// The URL to resume the session at, as given in the READY payload.
+    pub resume_ws_url: Option<String>,
 }
 
 impl Shard {
@@ -131,9 +176,94 @@ impl Shard {
         token: &str,
         shard_info: [u64; 2],
         intents: GatewayIntents,
+    ) -> Result<Shard> {
+        Self::with_transport(
+            ws_url,
+            token,
+            shard_info,
+            intents,
+            TransportCompression::default(),
+            TransportEncoding::default(),
+        )
+        .await
+    }
+
+    /// Instantiates a new instance of a Shard like [`Self::new`], receiving
+    /// payloads with the given compression and exchanging them in the given
+    /// encoding.
+    ///
+    /// # Errors
+    ///
+    /// On Error, will return either [`Error::Gateway`], [`Error::Tungstenite`]
+    /// or a Rustls/native TLS error.
+    pub async fn with_transport(
+        ws_url: Arc<Mutex<String>>,
+        token: &str,
+        shard_info: [u64; 2],
+        intents: GatewayIntents,
+        compression: TransportCompression,
+        encoding: TransportEncoding,
     ) -> Result<Shard> {
         let url = ws_url.lock().await.clone();
-        let client = connect(&url).await?;
+        let client = connect(&url, compression, encoding).await?;
+
+        Self::with_client(client, ws_url, token, shard_info, intents, compression, encoding)
+    }
+
+    /// Instantiates a new instance of a Shard like [`Self::with_transport`],
+    /// which resumes the given session instead of identifying.
+    ///
+    /// The shard connects straight to the URL the session is resumed at,
+    /// falling back to the gateway URL if connecting to it fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if connecting to the gateway or sending the RESUME
+    /// failed.
+    pub(crate) async fn resuming(
+        ws_url: Arc<Mutex<String>>,
+        token: &str,
+        intents: GatewayIntents,
+        compression: TransportCompression,
+        encoding: TransportEncoding,
+        session: ShardSession,
+    ) -> Result<Shard> {
+        let gateway_url = ws_url.lock().await.clone();
+        let client = match &session.resume_ws_url {
+            Some(url) => match connect(url, compression, encoding).await {
+                Ok(client) => client,
+                Err(why) => {
+                    warn!(
+                        "[Shard {:?}] Failed to connect to resume URL {}: {:?}",
+                        session.shard_info, url, why
+                    );
+
+                    connect(&gateway_url, compression, encoding).await?
+                },
+            },
+            None => connect(&gateway_url, compression, encoding).await?,
+        };
+
+        let shard_info = session.shard_info;
+        let mut shard =
+            Self::with_client(client, ws_url, token, shard_info, intents, compression, encoding)?;
+        shard.restore_session(session);
+        shard.send_resume().await?;
+
+        Ok(shard)
+    }
+
+    /// Instantiates a new instance of a Shard connected via the client.
+    fn with_client(
+        client: WsStream,
+        ws_url: Arc<Mutex<String>>,
+        token: &str,
+        shard_info: [u64; 2],
+        intents: GatewayIntents,
+        compression: TransportCompression,
+        encoding: TransportEncoding,
+    ) -> Result<Shard> {
+        let decompressor = Decompressor::new(compression)?;
 
         let current_presence = (None, OnlineStatus::Online);
         let heartbeat_instants = (None, None);
@@ -150,14 +280,21 @@ impl Shard {
             heartbeat_interval,
             http: None,
             last_heartbeat_acknowledged,
+            missed_heartbeat_acks: 0,
+            max_missed_heartbeat_acks: 1,
             seq,
             stage,
             started: Instant::now(),
             token: token.to_string(),
             session_id,
+            resume_ws_url: None,
             shard_info,
             ws_url,
             intents,
+            compression,
+            decompressor,
+            encoding,
+            ratelimiter: GatewayRatelimiter::default(),
         })
     }
 
@@ -206,7 +343,9 @@ impl Shard {
     /// a heartbeat.
     #[instrument(skip(self))]
     pub async fn heartbeat(&mut self) -> Result<()> {
-        match self.client.send_heartbeat(&self.shard_info, Some(self.seq)).await {
+        trace!("[Shard {:?}] Sending heartbeat d: {:?}", self.shard_info, self.seq);
+
+        match self.send_command(CommandKind::Heartbeat, heartbeat_payload(Some(self.seq))).await {
             Ok(()) => {
                 self.heartbeat_instants.0 = Some(Instant::now());
                 self.last_heartbeat_acknowledged = false;
@@ -240,6 +379,40 @@ impl Shard {
         self.last_heartbeat_acknowledged
     }
 
+    /// Retrieves the number of consecutive heartbeats which were not
+    /// acknowledged by the gateway.
+    #[inline]
+    pub fn missed_heartbeat_acks(&self) -> u32 {
+        self.missed_heartbeat_acks
+    }
+
+    /// Sets the number of consecutive heartbeats which may go unacknowledged
+    /// before the connection is considered zombied, and
+    /// [`Self::check_heartbeat`] fails. Defaults to 1.
+    #[inline]
+    pub fn set_max_missed_heartbeat_acks(&mut self, max_missed_heartbeat_acks: u32) {
+        self.max_missed_heartbeat_acks = max_missed_heartbeat_acks.max(1);
+    }
+
+    /// Whether the connection is considered zombied, as too many consecutive
+    /// heartbeats were not acknowledged.
+    #[inline]
+    pub fn is_zombied(&self) -> bool {
+        self.missed_heartbeat_acks >= self.max_missed_heartbeat_acks
+    }
+
+    /// Retrieves the compression of the payloads received from the gateway.
+    #[inline]
+    pub fn compression(&self) -> TransportCompression {
+        self.compression
+    }
+
+    /// Retrieves the encoding of the payloads exchanged with the gateway.
+    #[inline]
+    pub fn encoding(&self) -> TransportEncoding {
+        self.encoding
+    }
+
     #[inline]
     pub fn seq(&self) -> u64 {
         self.seq
@@ -250,6 +423,34 @@ impl Shard {
         self.session_id.as_ref()
     }
 
+    /// Retrieves the URL the session is resumed at, if Discord provided one.
+    #[inline]
+    pub fn resume_ws_url(&self) -> Option<&str> {
+        self.resume_ws_url.as_deref()
+    }
+
+    /// Exports the state needed to resume the shard's session, if it has one.
+    ///
+    /// The session stays resumable after the connection is closed, unless it
+    /// was closed with a close code of `1000` or `1001`.
+    #[must_use]
+    pub fn session(&self) -> Option<ShardSession> {
+        Some(ShardSession {
+            shard_info: self.shard_info,
+            session_id: self.session_id.clone()?,
+            seq: self.seq,
+            resume_ws_url: self.resume_ws_url.clone(),
+        })
+    }
+
+    /// Restores a session exported via [`Self::session`], so that the next
+    /// call to [`Self::resume`] resumes it.
+    pub fn restore_session(&mut self, session: ShardSession) {
+        self.session_id = Some(session.session_id);
+        self.seq = session.seq;
+        self.resume_ws_url = session.resume_ws_url;
+    }
+
     #[inline]
     #[instrument(skip(self))]
     pub fn set_activity(&mut self, activity: Option<Activity>) {
@@ -320,15 +521,12 @@ impl Shard {
 
     #[instrument(skip(self))]
     fn handle_gateway_dispatch(&mut self, seq: u64, event: &Event) -> Option<ShardAction> {
-        if seq > self.seq + 1 {
-            warn!("[Shard {:?}] Sequence off; them: {}, us: {}", self.shard_info, seq, self.seq);
-        }
-
         match event {
             Event::Ready(ref ready) => {
                 debug!("[Shard {:?}] Received Ready", self.shard_info);
 
                 self.session_id = Some(ready.ready.session_id.clone());
+                self.resume_ws_url.clone_from(&ready.ready.resume_gateway_url);
                 self.stage = ConnectionStage::Connected;
 
                 if let Some(ref http) = self.http {
@@ -340,16 +538,29 @@ impl Shard {
 
                 self.stage = ConnectionStage::Connected;
                 self.last_heartbeat_acknowledged = true;
+                self.missed_heartbeat_acks = 0;
                 self.heartbeat_instants = (Some(Instant::now()), None);
             },
             _ => {},
         }
 
-        self.seq = seq;
+        self.update_seq(seq);
 
         None
     }
 
+    /// Updates the sequence number to that of a received dispatch, including
+    /// dispatches skipped by an [`EventTypeFilter`].
+    ///
+    /// [`EventTypeFilter`]: crate::client::bridge::gateway::EventTypeFilter
+    pub(crate) fn update_seq(&mut self, seq: u64) {
+        if seq > self.seq + 1 {
+            warn!("[Shard {:?}] Sequence off; them: {}, us: {}", self.shard_info, seq, self.seq);
+        }
+
+        self.seq = seq;
+    }
+
     #[instrument(skip(self))]
     fn handle_heartbeat_event(&mut self, s: u64) -> ShardAction {
         info!("[Shard {:?}] Received shard heartbeat", self.shard_info);
@@ -430,6 +641,7 @@ impl Shard {
                 info!("[Shard {:?}] Invalid session.", self.shard_info);
 
                 self.session_id = None;
+                self.resume_ws_url = None;
             },
             Some(close_codes::INVALID_GATEWAY_INTENTS) => {
                 error!("[Shard {:?}] Invalid gateway intents have been provided.", self.shard_info);
@@ -499,6 +711,7 @@ impl Shard {
             Ok(GatewayEvent::HeartbeatAck) => {
                 self.heartbeat_instants.1 = Some(Instant::now());
                 self.last_heartbeat_acknowledged = true;
+                self.missed_heartbeat_acks = 0;
 
                 trace!("[Shard {:?}] Received heartbeat ack", self.shard_info);
 
@@ -559,7 +772,8 @@ impl Shard {
     ///
     /// `false` is returned under one of the following conditions:
     ///
-    /// - a heartbeat acknowledgement was not received in time
+    /// - too many consecutive heartbeat acknowledgements were not received in
+    ///   time, as set by [`Self::set_max_missed_heartbeat_acks`]
     /// - an error occurred while heartbeating
     #[instrument(skip(self))]
     pub async fn check_heartbeat(&mut self) -> bool {
@@ -582,12 +796,19 @@ impl Shard {
             }
         }
 
-        // If the last heartbeat didn't receive an acknowledgement, then
-        // auto-reconnect.
+        // If too many heartbeats in a row didn't receive an acknowledgement,
+        // then auto-reconnect.
         if !self.last_heartbeat_acknowledged {
-            debug!("[Shard {:?}] Last heartbeat not acknowledged", self.shard_info,);
+            self.missed_heartbeat_acks += 1;
 
-            return false;
+            debug!(
+                "[Shard {:?}] Last heartbeat not acknowledged ({} missed)",
+                self.shard_info, self.missed_heartbeat_acks,
+            );
+
+            if self.is_zombied() {
+                return false;
+            }
         }
 
         // Otherwise, we're good to heartbeat.
@@ -719,10 +940,37 @@ impl Shard {
         limit: Option<u16>,
         filter: ChunkGuildFilter,
         nonce: Option<&str>,
+    ) -> Result<()> {
+        self.chunk_guild_with_presences(guild_id, limit, false, filter, nonce).await
+    }
+
+    /// Requests that a [`Guild`] be chunked, like [`Self::chunk_guild`], and
+    /// optionally that the [`Presence`]s of the members be sent along with
+    /// them.
+    ///
+    /// Requesting presences requires the [`GatewayIntents::GUILD_PRESENCES`]
+    /// intent.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GatewayError`] if the request could not be sent.
+    ///
+    /// [`Guild`]: crate::model::guild::Guild
+    /// [`Presence`]: crate::model::gateway::Presence
+    #[instrument(skip(self))]
+    pub async fn chunk_guild_with_presences(
+        &mut self,
+        guild_id: GuildId,
+        limit: Option<u16>,
+        presences: bool,
+        filter: ChunkGuildFilter,
+        nonce: Option<&str>,
     ) -> Result<()> {
         debug!("[Shard {:?}] Requesting member chunks", self.shard_info);
 
-        self.client.send_chunk_guild(guild_id, &self.shard_info, limit, filter, nonce).await
+        let payload = chunk_guild_payload(guild_id, limit, presences, filter, nonce);
+
+        self.send_command(CommandKind::Other, payload).await
     }
 
     /// Sets the shard as going into identifying stage, which sets:
@@ -731,7 +979,13 @@ impl Shard {
     /// - the `stage` to [`ConnectionStage::Identifying`]
     #[instrument(skip(self))]
     pub async fn identify(&mut self) -> Result<()> {
-        self.client.send_identify(&self.shard_info, &self.token, self.intents).await?;
+        // Payloads must not be compressed individually when the whole
+        // connection is already compressed.
+        let compress = self.compression == TransportCompression::Payload;
+        debug!("[Shard {:?}] Identifying", self.shard_info);
+
+        let payload = identify_payload(&self.shard_info, &self.token, self.intents, compress);
+        self.send_command(CommandKind::Session, payload).await?;
 
         self.heartbeat_instants.0 = Some(Instant::now());
         self.stage = ConnectionStage::Identifying;
@@ -745,6 +999,14 @@ impl Shard {
     /// the client.
     #[instrument(skip(self))]
     pub async fn initialize(&mut self) -> Result<WsStream> {
+        let url = self.ws_url.lock().await.clone();
+
+        self.initialize_at(&url).await
+    }
+
+    /// Initializes a new WebSocket client connected to the given URL, like
+    /// [`Self::initialize`].
+    async fn initialize_at(&mut self, url: &str) -> Result<WsStream> {
         debug!("[Shard {:?}] Initializing.", self.shard_info);
 
         // We need to do two, sort of three things here:
@@ -757,8 +1019,13 @@ impl Shard {
         // accurate when a Hello is received.
         self.stage = ConnectionStage::Connecting;
         self.started = Instant::now();
-        let url = &self.ws_url.lock().await.clone();
-        let client = connect(url).await?;
+        let client = connect(url, self.compression, self.encoding).await?;
+        // Every connection is a new stream, which must not be decompressed
+        // with the context of the previous one.
+        self.decompressor = Decompressor::new(self.compression)?;
+        // The ratelimit applies per connection.
+        self.ratelimiter.reset();
+        self.missed_heartbeat_acks = 0;
         self.stage = ConnectionStage::Handshake;
 
         Ok(client)
@@ -769,24 +1036,57 @@ impl Shard {
         self.heartbeat_instants = (Some(Instant::now()), None);
         self.heartbeat_interval = None;
         self.last_heartbeat_acknowledged = true;
+        self.missed_heartbeat_acks = 0;
         self.session_id = None;
+        self.resume_ws_url = None;
         self.stage = ConnectionStage::Disconnected;
         self.seq = 0;
     }
 
+    /// Reconnects and sends a RESUME.
+    ///
+    /// The session is resumed at the URL Discord provided in the READY
+    /// payload, falling back to the gateway URL if connecting to it fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GatewayError::NoSessionId`] if there is no session to resume,
+    /// or an error if connecting to the gateway failed.
     #[instrument(skip(self))]
     pub async fn resume(&mut self) -> Result<()> {
         debug!("[Shard {:?}] Attempting to resume", self.shard_info);
 
-        self.client = self.initialize().await?;
+        self.client = match self.resume_ws_url.clone() {
+            Some(url) => match self.initialize_at(&url).await {
+                Ok(client) => client,
+                Err(why) => {
+                    warn!(
+                        "[Shard {:?}] Failed to connect to resume URL {}: {:?}",
+                        self.shard_info, url, why
+                    );
+
+                    self.resume_ws_url = None;
+                    self.initialize().await?
+                },
+            },
+            None => self.initialize().await?,
+        };
+
+        self.send_resume().await
+    }
+
+    /// Sends a RESUME for the session over the current connection.
+    async fn send_resume(&mut self) -> Result<()> {
         self.stage = ConnectionStage::Resuming;
 
-        match self.session_id.as_ref() {
-            Some(session_id) => {
-                self.client.send_resume(&self.shard_info, session_id, self.seq, &self.token).await
-            },
-            None => Err(Error::Gateway(GatewayError::NoSessionId)),
-        }
+        let payload = match self.session_id.as_ref() {
+            Some(session_id) => resume_payload(session_id, self.seq, &self.token),
+            None => return Err(Error::Gateway(GatewayError::NoSessionId)),
+        };
+
+        debug!("[Shard {:?}] Sending resume; seq: {}", self.shard_info, self.seq);
+
+        self.send_command(CommandKind::Session, payload).await
     }
 
     #[instrument(skip(self))]
@@ -801,17 +1101,95 @@ impl Shard {
 
     #[instrument(skip(self))]
     pub async fn update_presence(&mut self) -> Result<()> {
-        self.client.send_presence_update(&self.shard_info, &self.current_presence).await
+        debug!("[Shard {:?}] Sending presence update", self.shard_info);
+
+        self.send_command(CommandKind::Other, presence_update_payload(&self.current_presence)).await
+    }
+
+    /// Receives the next payload from the gateway, decompressing it according
+    /// to the shard's [`TransportCompression`].
+    pub(crate) async fn recv_json(&mut self) -> Result<Option<Payload>> {
+        self.client.recv_json(&mut self.decompressor, self.encoding).await
+    }
+
+    /// Retrieves the number of commands waiting to be sent, as the shard sent
+    /// as many commands as Discord allows within the last 60 seconds.
+    ///
+    /// Heartbeats, IDENTIFYs and RESUMEs are never queued, as the shard
+    /// reserves part of its budget for them.
+    #[inline]
+    pub fn command_backlog(&self) -> usize {
+        self.ratelimiter.backlog()
+    }
+
+    /// Sends the commands which were queued due to the ratelimit, as far as
+    /// the budget allows. Commands are only sent once the shard is connected.
+    ///
+    /// **Note**: This is called by the [`ShardRunner`], and only needs to be
+    /// called when managing the shard yourself.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if sending a command failed.
+    ///
+    /// [`ShardRunner`]: crate::client::bridge::gateway::ShardRunner
+    pub async fn send_queued_commands(&mut self) -> Result<()> {
+        if self.stage != ConnectionStage::Connected {
+            return Ok(());
+        }
+
+        while let Some(payload) = self.ratelimiter.next_queued(self.heartbeat_interval) {
+            self.send_payload(&payload).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Sends a command to the gateway, or queues it if the shard sent as many
+    /// commands as Discord allows.
+    pub(crate) async fn send_command(&mut self, kind: CommandKind, payload: Value) -> Result<()> {
+        if self.ratelimiter.try_acquire(kind, self.heartbeat_interval) {
+            return self.send_payload(&payload).await;
+        }
+
+        debug!(
+            "[Shard {:?}] Ratelimited, queueing command; backlog: {}",
+            self.shard_info,
+            self.ratelimiter.backlog() + 1
+        );
+
+        self.ratelimiter.enqueue(payload);
+
+        Ok(())
+    }
+
+    /// Sends a payload to the gateway, encoded according to the shard's
+    /// [`TransportEncoding`].
+    async fn send_payload(&mut self, payload: &Value) -> Result<()> {
+        match self.encoding {
+            TransportEncoding::Json => self.client.send_json(payload).await,
+            TransportEncoding::Etf => self.client.send_etf(payload).await,
+        }
     }
 }
 
-async fn connect(base_url: &str) -> Result<WsStream> {
-    let url =
+async fn connect(
+    base_url: &str,
+    compression: TransportCompression,
+    encoding: TransportEncoding,
+) -> Result<WsStream> {
+    let mut url =
         Url::parse(&format!("{}?v={}", base_url, constants::GATEWAY_VERSION)).map_err(|why| {
             warn!("Error building gateway URL with base `{}`: {:?}", base_url, why);
 
             Error::Gateway(GatewayError::BuildingUrl)
         })?;
 
+    url.query_pairs_mut().append_pair("encoding", encoding.query_value());
+
+    if let Some(compress) = compression.query_value() {
+        url.query_pairs_mut().append_pair("compress", compress);
+    }
+
     create_client(url).await
 }
@@ -61,37 +61,14 @@ impl WebSocketGatewayClientExt for WsStream {
     ) -> Result<()> {
         debug!("[Shard {:?}] Requesting member chunks", shard_info);
 
-        let mut payload = json!({
-            "op": OpCode::GetGuildMembers.num(),
-            "d": {
-                "guild_id": guild_id.as_ref().0.to_string(),
-                "limit": limit.unwrap_or(0),
-                "nonce": nonce.unwrap_or(""),
-            },
-        });
-
-        match filter {
-            ChunkGuildFilter::None => payload["d"]["query"] = json!(""),
-            ChunkGuildFilter::Query(query) => payload["d"]["query"] = json!(query),
-            ChunkGuildFilter::UserIds(user_ids) => {
-                let ids = user_ids.iter().map(|x| x.0).collect::<Vec<u64>>();
-                payload["d"]["user_ids"] = json!(ids);
-            },
-        };
-
-        self.send_json(&payload).await.map_err(From::from)
+        self.send_json(&chunk_guild_payload(guild_id, limit, false, filter, nonce)).await
     }
 
     #[instrument(skip(self))]
     async fn send_heartbeat(&mut self, shard_info: &[u64; 2], seq: Option<u64>) -> Result<()> {
         trace!("[Shard {:?}] Sending heartbeat d: {:?}", shard_info, seq);
 
-        self.send_json(&json!({
-            "d": seq,
-            "op": OpCode::Heartbeat.num(),
-        }))
-        .await
-        .map_err(From::from)
+        self.send_json(&heartbeat_payload(seq)).await
     }
 
     #[instrument(skip(self, token))]
@@ -103,23 +80,7 @@ impl WebSocketGatewayClientExt for WsStream {
     ) -> Result<()> {
         debug!("[Shard {:?}] Identifying", shard_info);
 
-        self.send_json(&json!({
-            "op": OpCode::Identify.num(),
-            "d": {
-                "compress": true,
-                "large_threshold": constants::LARGE_THRESHOLD,
-                "shard": shard_info,
-                "token": token,
-                "intents": intents,
-                "v": constants::GATEWAY_VERSION,
-                "properties": {
-                    "$browser": "serenity",
-                    "$device": "serenity",
-                    "$os": consts::OS,
-                },
-            },
-        }))
-        .await
+        self.send_json(&identify_payload(shard_info, token, intents, true)).await
     }
 
     #[instrument(skip(self))]
@@ -128,25 +89,9 @@ impl WebSocketGatewayClientExt for WsStream {
         shard_info: &[u64; 2],
         current_presence: &CurrentPresence,
     ) -> Result<()> {
-        let (activity, status) = current_presence;
-        let now = SystemTime::now();
-
         debug!("[Shard {:?}] Sending presence update", shard_info);
 
-        self.send_json(&json!({
-            "op": OpCode::StatusUpdate.num(),
-            "d": {
-                "afk": false,
-                "since": now,
-                "status": status.name(),
-                "game": activity.as_ref().map(|x| json!({
-                    "name": x.name,
-                    "type": x.kind,
-                    "url": x.url,
-                })),
-            },
-        }))
-        .await
+        self.send_json(&presence_update_payload(current_presence)).await
     }
 
     #[instrument(skip(self, token))]
@@ -159,15 +104,99 @@ impl WebSocketGatewayClientExt for WsStream {
     ) -> Result<()> {
         debug!("[Shard {:?}] Sending resume; seq: {}", shard_info, seq);
 
-        self.send_json(&json!({
-            "op": OpCode::Resume.num(),
-            "d": {
-                "session_id": session_id,
-                "seq": seq,
-                "token": token,
-            },
-        }))
-        .await
-        .map_err(From::from)
+        self.send_json(&resume_payload(session_id, seq, token)).await
     }
 }
+
+// The payloads are built separately from sending them, so that a `Shard` can
+// send them in its own encoding.
+
+pub(crate) fn chunk_guild_payload(
+    guild_id: GuildId,
+    limit: Option<u16>,
+    presences: bool,
+    filter: ChunkGuildFilter,
+    nonce: Option<&str>,
+) -> Value {
+    let mut payload = json!({
+        "op": OpCode::GetGuildMembers.num(),
+        "d": {
+            "guild_id": guild_id.as_ref().0.to_string(),
+            "limit": limit.unwrap_or(0),
+            "presences": presences,
+            "nonce": nonce.unwrap_or(""),
+        },
+    });
+
+    match filter {
+        ChunkGuildFilter::None => payload["d"]["query"] = json!(""),
+        ChunkGuildFilter::Query(query) => payload["d"]["query"] = json!(query),
+        ChunkGuildFilter::UserIds(user_ids) => {
+            let ids = user_ids.iter().map(|x| x.0).collect::<Vec<u64>>();
+            payload["d"]["user_ids"] = json!(ids);
+        },
+    };
+
+    payload
+}
+
+pub(crate) fn heartbeat_payload(seq: Option<u64>) -> Value {
+    json!({
+        "d": seq,
+        "op": OpCode::Heartbeat.num(),
+    })
+}
+
+pub(crate) fn identify_payload(
+    shard_info: &[u64; 2],
+    token: &str,
+    intents: GatewayIntents,
+    compress: bool,
+) -> Value {
+    json!({
+        "op": OpCode::Identify.num(),
+        "d": {
+            "compress": compress,
+            "large_threshold": constants::LARGE_THRESHOLD,
+            "shard": shard_info,
+            "token": token,
+            "intents": intents,
+            "v": constants::GATEWAY_VERSION,
+            "properties": {
+                "$browser": "serenity",
+                "$device": "serenity",
+                "$os": consts::OS,
+            },
+        },
+    })
+}
+
+pub(crate) fn presence_update_payload(current_presence: &CurrentPresence) -> Value {
+    let (activity, status) = current_presence;
+    let now = SystemTime::now();
+
+    json!({
+        "op": OpCode::StatusUpdate.num(),
+        "d": {
+            "afk": false,
+            "since": now,
+            "status": status.name(),
+            "game": activity.as_ref().map(|x| json!({
+                "name": x.name,
+                "type": x.kind,
+                "url": x.url,
+            })),
+        },
+    })
+}
+
+pub(crate) fn resume_payload(session_id: &str, seq: u64, token: &str) -> Value {
+    json!({
+        "op": OpCode::Resume.num(),
+        "d": {
+            "session_id": session_id,
+            "seq": seq,
+            "token": token,
+        },
+    })
+}
@@ -0,0 +1,435 @@
+//! Recording requests and their responses to a file, and replaying them later.
+//!
+//! An [`Http`] client given a [`Cassette`] via [`HttpBuilder::cassette`]
+//! either records every request it performs, along with the response, or
+//! answers every request with a previously recorded response instead of
+//! touching the network. This allows integration tests to run against real
+//! Discord responses without network access.
+//!
+//! [`Http`]: super::Http
+//! [`HttpBuilder::cassette`]: super::HttpBuilder::cassette
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use http_crate::response::Builder;
+use reqwest::header::HeaderMap;
+use reqwest::{Response, ResponseBuilderExt, Url};
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use super::request::Request;
+use super::{AttachmentType, HttpError};
+use crate::internal::prelude::*;
+use crate::json;
+
+/// Whether a [`Cassette`] records or replays requests.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum CassetteMode {
+    /// Requests are sent as usual, and recorded along with their responses.
+    Record,
+    /// Requests are answered with recorded responses, without being sent.
+    Replay,
+}
+
+/// A file of recorded requests and their responses.
+///
+/// The file holds one JSON [`Recording`] per line. When recording, each
+/// recording is appended as soon as its response is received, so the file is
+/// complete even if the process does not exit cleanly. Responses are
+/// recorded after the [`Ratelimiter`] handled them, so ratelimited attempts
+/// are not part of the cassette.
+///
+/// When replaying, each request is answered with the response of the first
+/// recorded request with the same method and path that was not replayed yet.
+/// Replayed requests bypass the ratelimiter. A request without a matching
+/// recording fails with [`HttpError::UnrecordedRequest`].
+///
+/// **Note**: The token is never recorded, as it is not part of the request
+/// headers stored in the cassette.
+///
+/// # Examples
+///
+/// Record the requests while running against Discord, and replay them in CI:
+///
+/// ```rust,no_run
+/// # fn run() -> serenity::Result<()> {
+/// use serenity::http::{Cassette, HttpBuilder};
+///
+/// let cassette = if std::env::var("RECORD").is_ok() {
+///     Cassette::record("tests/cassettes/get_guild.jsonl")
+/// } else {
+///     Cassette::replay("tests/cassettes/get_guild.jsonl")?
+/// };
+///
+/// let http = HttpBuilder::new("token").cassette(cassette).build();
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Ratelimiter`]: super::ratelimiting::Ratelimiter
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    recordings: Mutex<Vec<Recording>>,
+    file: Mutex<Option<File>>,
+}
+
+impl Cassette {
+    /// Creates a cassette which records to the file at the given path. Its
+    /// contents are replaced once the first request is recorded.
+    #[must_use]
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            mode: CassetteMode::Record,
+            recordings: Mutex::new(Vec::new()),
+            file: Mutex::new(None),
+        }
+    }
+
+    /// Creates a cassette which replays the recordings in the file at the
+    /// given path.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the file could not be read, and [`Error::Json`]
+    /// if it is not a valid cassette.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let contents = std::fs::read_to_string(&path)?;
+        let recordings = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| json::from_str(&mut line.to_string()))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            path,
+            mode: CassetteMode::Replay,
+            recordings: Mutex::new(recordings),
+            file: Mutex::new(None),
+        })
+    }
+
+    /// Whether the cassette records or replays requests.
+    #[must_use]
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Returns the recordings. When replaying, these are the ones
+    /// that were not replayed yet.
+    pub async fn recordings(&self) -> Vec<Recording> {
+        self.recordings.lock().await.clone()
+    }
+
+    /// Answers the request with the first matching recorded response.
+    pub(crate) async fn replay_request(&self, request: &RecordedRequest) -> Result<Response> {
+        let mut recordings = self.recordings.lock().await;
+
+        let index = recordings
+            .iter()
+            .position(|recording| {
+                recording.request.method == request.method && recording.request.path == request.path
+            })
+            .ok_or_else(|| HttpError::UnrecordedRequest {
+                method: request.method.clone(),
+                path: request.path.clone(),
+            })?;
+
+        let recording = recordings.remove(index);
+
+        recording.response.to_response(&request.path)
+    }
+
+    /// Records the request along with its response, returning a response
+    /// equal to the original one.
+    pub(crate) async fn record_response(
+        &self,
+        request: RecordedRequest,
+        response: Response,
+    ) -> Result<Response> {
+        let status = response.status().as_u16();
+        let headers = header_map(response.headers());
+        let body = response.bytes().await?;
+
+        let recording = Recording {
+            response: RecordedResponse {
+                status,
+                headers,
+                body: body.to_vec(),
+            },
+            request,
+        };
+        let response = recording.response.to_response(&recording.request.path)?;
+
+        let mut line = json::to_string(&recording)?;
+        line.push('\n');
+
+        {
+            let mut file = self.file.lock().await;
+            let file = match &mut *file {
+                Some(file) => file,
+                None => file.insert(File::create(&self.path).await?),
+            };
+
+            file.write_all(line.as_bytes()).await?;
+            file.flush().await?;
+        }
+
+        self.recordings.lock().await.push(recording);
+
+        Ok(response)
+    }
+}
+
+/// A request and the response it resulted in.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Recording {
+    /// The request that was sent.
+    pub request: RecordedRequest,
+    /// The response that was received.
+    pub response: RecordedResponse,
+}
+
+/// A request as stored in a [`Cassette`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct RecordedRequest {
+    /// The HTTP method, e.g. `GET`.
+    pub method: String,
+    /// The URL of the route, including the query string.
+    pub path: String,
+    /// The headers specific to the request, such as `X-Audit-Log-Reason`.
+    /// Headers sent along with every request, including `Authorization`, are
+    /// not recorded.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// The JSON body, if any. It is stored base64-encoded.
+    #[serde(default, with = "optional_base64")]
+    pub body: Option<Vec<u8>>,
+    /// The multipart form, if any.
+    #[serde(default)]
+    pub multipart: Option<RecordedMultipart>,
+}
+
+impl RecordedRequest {
+    pub(crate) fn new(request: &Request<'_>) -> Result<Self> {
+        let (method, _, path) = request.route.deconstruct();
+
+        let multipart = match &request.multipart {
+            Some(multipart) => Some(RecordedMultipart {
+                filenames: multipart
+                    .files
+                    .iter()
+                    .map(AttachmentType::filename)
+                    .collect::<Result<_>>()?,
+                fields: multipart
+                    .fields
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect(),
+                payload_json: multipart.payload_json.clone(),
+            }),
+            None => None,
+        };
+
+        Ok(Self {
+            method: method.reqwest_method().to_string(),
+            path: path.into_owned(),
+            headers: request.headers.as_ref().map(header_map).unwrap_or_default(),
+            body: request.body.map(<[u8]>::to_vec),
+            multipart,
+        })
+    }
+}
+
+/// The metadata of a multipart form stored in a [`Cassette`]. The contents of
+/// the files are not recorded.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct RecordedMultipart {
+    /// The filenames of the attached files.
+    pub filenames: Vec<Option<String>>,
+    /// The text fields of the form.
+    pub fields: Vec<(String, String)>,
+    /// The JSON payload of the form.
+    pub payload_json: Option<Value>,
+}
+
+/// A response as stored in a [`Cassette`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct RecordedResponse {
+    /// The status code.
+    pub status: u16,
+    /// The headers, including the ratelimit headers.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// The body, which is usually JSON. It is stored base64-encoded, so that
+    /// bodies which are not valid UTF-8 are recorded as they are.
+    #[serde(default, with = "base64_bytes")]
+    pub body: Vec<u8>,
+}
+
+impl RecordedResponse {
+    fn to_response(&self, path: &str) -> Result<Response> {
+        let mut builder = Builder::new().status(self.status);
+
+        if let Ok(url) = Url::parse(path) {
+            builder = builder.url(url);
+        }
+
+        for (name, value) in &self.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+
+        let response = builder.body(self.body.clone()).map_err(|_| HttpError::InvalidCassette)?;
+
+        Ok(response.into())
+    }
+}
+
+/// (De)serializes bytes as a base64 string.
+mod base64_bytes {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base64::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        base64::decode(String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+/// (De)serializes optional bytes as a base64 string.
+mod optional_base64 {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        bytes: &Option<Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match bytes {
+            Some(bytes) => serializer.serialize_some(&base64::encode(bytes)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<u8>>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|body| base64::decode(body).map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+fn header_map(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use http_crate::response::Builder;
+    use reqwest::{Request as ReqwestRequest, Response};
+
+    use super::{Cassette, CassetteMode, RecordedResponse};
+    use crate::http::{HttpBuilder, HttpError, Transport};
+    use crate::Error;
+
+    /// Answers every request with a channel.
+    #[derive(Default)]
+    struct Channels {
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for Arc<Channels> {
+        async fn execute(&self, _: ReqwestRequest) -> crate::Result<Response> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+
+            let body =
+                r#"{"id": "1", "type": 0, "guild_id": "2", "name": "general", "position": 0}"#;
+
+            Ok(Builder::new().status(200).header("x-test", "1").body(body).unwrap().into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let path =
+            std::env::temp_dir().join(format!("serenity-cassette-{}.jsonl", std::process::id()));
+
+        let transport = Arc::new(Channels::default());
+        let http = HttpBuilder::new("token")
+            .transport(Arc::clone(&transport))
+            .cassette(Cassette::record(&path))
+            .build();
+
+        let channel = http.get_channel(1).await.unwrap();
+        assert_eq!(channel.id(), 1);
+
+        // Recordings are appended one per line.
+        http.get_channel(2).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+
+        let http = HttpBuilder::new("token")
+            .transport(Arc::clone(&transport))
+            .cassette(Cassette::replay(&path).unwrap())
+            .build();
+
+        let replayed = http.get_channel(1).await.unwrap();
+        assert_eq!(replayed.id(), 1);
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 2);
+
+        // Every recording is only replayed once.
+        match http.get_channel(1).await {
+            Err(Error::Http(error)) => {
+                assert!(matches!(*error, HttpError::UnrecordedRequest { .. }));
+            },
+            _ => panic!("expected an unrecorded request"),
+        }
+
+        let cassette = Cassette::replay(&path).unwrap();
+        let recordings = cassette.recordings().await;
+
+        assert_eq!(cassette.mode(), CassetteMode::Replay);
+        assert_eq!(recordings[0].request.method, "GET");
+        assert_eq!(recordings[0].request.path, "https://discord.com/api/v10/channels/1");
+        assert_eq!(recordings[0].response.headers["x-test"], "1");
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_body_round_trip() {
+        let response = RecordedResponse {
+            status: 200,
+            headers: BTreeMap::new(),
+            body: vec![0, 159, 146, 150],
+        };
+
+        let json = serde_json::to_string(&response).unwrap();
+        let response: RecordedResponse = serde_json::from_str(&json).unwrap();
+
+        assert!(json.contains(r#""body":"AJ+Slg==""#));
+        assert_eq!(response.body, [0, 159, 146, 150]);
+    }
+}
@@ -11,19 +11,27 @@ use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 use reqwest::header::{HeaderMap as Headers, HeaderValue, CONTENT_TYPE};
 use reqwest::{Client, ClientBuilder, Response as ReqwestResponse, StatusCode, Url};
 use serde::de::DeserializeOwned;
+use tokio::time::{sleep, Instant};
 use tracing::{debug, instrument, trace};
 
+#[cfg(feature = "http_cassettes")]
+use super::cassette::{Cassette, CassetteMode, RecordedRequest};
+use super::metrics::{HttpMetrics, MetricsSnapshot};
+use super::middleware::Middleware;
 use super::multipart::Multipart;
 use super::ratelimiting::{RatelimitedRequest, Ratelimiter};
 use super::request::Request;
+use super::retry::RetryPolicy;
 use super::routing::RouteInfo;
 use super::typing::Typing;
-use super::{AttachmentType, GuildPagination, HttpError, UserPagination};
+use super::{AttachmentType, GuildPagination, HttpError, Transport, UserPagination};
 use crate::internal::prelude::*;
 use crate::json::prelude::*;
 use crate::model::application::command::{Command, CommandPermission};
+use crate::model::application::role_connection::{RoleConnection, RoleConnectionMetadata};
 use crate::model::guild::automod::Rule;
 use crate::model::prelude::*;
+use crate::model::Timestamp;
 use crate::{constants, utils};
 
 /// A builder for the underlying [`Http`] client that performs requests
@@ -49,6 +57,11 @@ pub struct HttpBuilder {
     client: Option<Client>,
     ratelimiter: Option<Ratelimiter>,
     ratelimiter_disabled: bool,
+    transport: Option<Arc<dyn Transport>>,
+    retry_policy: Option<RetryPolicy>,
+    middlewares: Vec<Arc<dyn Middleware>>,
+    #[cfg(feature = "http_cassettes")]
+    cassette: Option<Arc<Cassette>>,
     token: String,
     proxy: Option<Url>,
     application_id: Option<u64>,
@@ -57,11 +70,20 @@ pub struct HttpBuilder {
 impl HttpBuilder {
     /// Construct a new builder to call methods on for the HTTP construction.
     /// The `token` will automatically be prefixed "Bot " if not already.
+    ///
+    /// An OAuth2 access token prefixed "Bearer " is used as is, and performs
+    /// requests on behalf of the user who authorized it. An empty token sends
+    /// no `Authorization` header at all.
     pub fn new(token: impl AsRef<str>) -> Self {
         Self {
             client: None,
             ratelimiter: None,
             ratelimiter_disabled: false,
+            transport: None,
+            retry_policy: None,
+            middlewares: Vec::new(),
+            #[cfg(feature = "http_cassettes")]
+            cassette: None,
             token: parse_token(token),
             proxy: None,
             application_id: None,
@@ -118,6 +140,49 @@ impl HttpBuilder {
         self
     }
 
+    /// Sets the [`Transport`] that built requests are executed with. If one
+    /// isn't provided, the [`reqwest::Client`] is used.
+    ///
+    /// This replaces the transport of the ratelimiter as well, including one
+    /// given via [`Self::ratelimiter`]. It is mainly intended for testing,
+    /// where a fake transport can return canned responses without touching the
+    /// network.
+    #[must_use]
+    pub fn transport<T: Transport + 'static>(mut self, transport: T) -> Self {
+        self.transport = Some(Arc::new(transport));
+
+        self
+    }
+
+    /// Sets the [`RetryPolicy`] for requests which failed for transient
+    /// reasons, such as a connection reset or a 503 response. If one isn't
+    /// provided, failed requests are not retried.
+    #[must_use]
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+
+        self
+    }
+
+    /// Adds a [`Middleware`] which sees every request and response. Middleware
+    /// is called in the order it was added in.
+    #[must_use]
+    pub fn middleware<M: Middleware + 'static>(mut self, middleware: M) -> Self {
+        self.middlewares.push(Arc::new(middleware));
+
+        self
+    }
+
+    /// Sets a [`Cassette`] which either records every request along with its
+    /// response, or replays recorded responses without sending the requests.
+    #[cfg(feature = "http_cassettes")]
+    #[must_use]
+    pub fn cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(Arc::new(cassette));
+
+        self
+    }
+
     /// Sets the proxy that Discord HTTP API requests will be passed to. This is
     /// mainly intended for something like [`twilight-http-proxy`] where
     /// multiple processes can make API requests while sharing a single
@@ -153,17 +218,34 @@ impl HttpBuilder {
             builder.build().expect("Cannot build reqwest::Client")
         });
 
-        let ratelimiter = self.ratelimiter.unwrap_or_else(|| {
+        let mut ratelimiter = self.ratelimiter.unwrap_or_else(|| {
             let client = client.clone();
             Ratelimiter::new(client, token.to_string())
         });
 
+        let transport: Arc<dyn Transport> = match self.transport {
+            Some(transport) => {
+                ratelimiter.set_transport(Arc::clone(&transport));
+                transport
+            },
+            None => Arc::new(client.clone()),
+        };
+
+        let metrics = Arc::new(HttpMetrics::default());
+        ratelimiter.set_metrics(Arc::clone(&metrics));
+
         let ratelimiter_disabled = self.ratelimiter_disabled;
 
         Http {
             client,
+            transport,
             ratelimiter,
             ratelimiter_disabled,
+            retry_policy: self.retry_policy,
+            middlewares: self.middlewares,
+            metrics,
+            #[cfg(feature = "http_cassettes")]
+            cassette: self.cassette,
             proxy: self.proxy,
             token,
             application_id,
@@ -174,7 +256,7 @@ impl HttpBuilder {
 fn parse_token(token: impl AsRef<str>) -> String {
     let token = token.as_ref().trim();
 
-    if token.starts_with("Bot ") || token.starts_with("Bearer ") {
+    if token.is_empty() || token.starts_with("Bot ") || token.starts_with("Bearer ") {
         token.to_string()
     } else {
         format!("Bot {}", token)
@@ -198,8 +280,14 @@ fn reason_into_header(reason: &str) -> Headers {
 /// Error kind will be either [`Error::Http`] or [`Error::Json`].
 pub struct Http {
     pub(crate) client: Client,
+    transport: Arc<dyn Transport>,
     pub ratelimiter: Ratelimiter,
     pub ratelimiter_disabled: bool,
+    pub retry_policy: Option<RetryPolicy>,
+    middlewares: Vec<Arc<dyn Middleware>>,
+    metrics: Arc<HttpMetrics>,
+    #[cfg(feature = "http_cassettes")]
+    cassette: Option<Arc<Cassette>>,
     pub proxy: Option<Url>,
     pub token: String,
     application_id: AtomicU64,
@@ -211,6 +299,7 @@ impl fmt::Debug for Http {
             .field("client", &self.client)
             .field("ratelimiter", &self.ratelimiter)
             .field("ratelimiter_disabled", &self.ratelimiter_disabled)
+            .field("retry_policy", &self.retry_policy)
             .field("proxy", &self.proxy)
             .finish()
     }
@@ -226,16 +315,38 @@ impl Http {
 
         let token = parse_token(token);
 
+        let metrics = Arc::new(HttpMetrics::default());
+        let mut ratelimiter = Ratelimiter::new(client2, token.to_string());
+        ratelimiter.set_metrics(Arc::clone(&metrics));
+
         Http {
+            transport: Arc::new(client.clone()),
             client,
-            ratelimiter: Ratelimiter::new(client2, token.to_string()),
+            ratelimiter,
             ratelimiter_disabled: false,
+            retry_policy: None,
+            middlewares: Vec::new(),
+            metrics,
+            #[cfg(feature = "http_cassettes")]
+            cassette: None,
             proxy: None,
             token,
             application_id: AtomicU64::new(0),
         }
     }
 
+    /// Creates a client which performs requests on behalf of the user who
+    /// authorized the given OAuth2 access token, such as [`Self::get_guilds`]
+    /// or [`Self::get_user_connections`].
+    ///
+    /// An access token can be acquired via the [`OAuth2Client`].
+    ///
+    /// [`OAuth2Client`]: super::OAuth2Client
+    #[must_use]
+    pub fn new_bearer(access_token: &str) -> Self {
+        Self::new(&format!("Bearer {}", access_token.trim()))
+    }
+
     #[must_use]
     pub fn new_with_application_id(token: &str, application_id: u64) -> Self {
         let http = Self::new(token);
@@ -255,6 +366,15 @@ impl Http {
         }
     }
 
+    /// Returns the request counts, status codes, latencies and ratelimit waits
+    /// collected so far, per route and method.
+    ///
+    /// See [`MetricsSnapshot`] for details.
+    #[must_use]
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
     fn try_application_id(&self) -> Result<u64> {
         self.application_id().ok_or_else(|| HttpError::ApplicationIdMissing.into())
     }
@@ -2369,6 +2489,9 @@ impl Http {
     }
 
     /// Gets all the users that are banned in specific guild.
+    ///
+    /// At most 1000 bans are returned, see [`Self::get_bans_paginated`] to
+    /// page through larger ban lists.
     pub async fn get_bans(&self, guild_id: u64) -> Result<Vec<Ban>> {
         self.fire(Request {
             body: None,
@@ -2381,6 +2504,38 @@ impl Http {
         .await
     }
 
+    /// Gets the users that are banned in specific guild.
+    ///
+    /// If `limit` is left unset, at most 1000 bans are returned. Pass a
+    /// `target` to page through larger ban lists.
+    pub async fn get_bans_paginated(
+        &self,
+        guild_id: u64,
+        target: Option<UserPagination>,
+        limit: Option<u64>,
+    ) -> Result<Vec<Ban>> {
+        let (after, before) = match target {
+            None => (None, None),
+            Some(p) => match p {
+                UserPagination::After(id) => (Some(id.0), None),
+                UserPagination::Before(id) => (None, Some(id.0)),
+            },
+        };
+
+        self.fire(Request {
+            body: None,
+            multipart: None,
+            headers: None,
+            route: RouteInfo::GetBansPaginated {
+                guild_id,
+                after,
+                before,
+                limit,
+            },
+        })
+        .await
+    }
+
     /// Gets all audit logs in a specific guild.
     pub async fn get_audit_logs(
         &self,
@@ -2543,6 +2698,9 @@ impl Http {
     }
 
     /// Gets all archived public threads from a channel.
+    ///
+    /// Discord expects `before` to be a timestamp, see
+    /// [`Self::get_channel_archived_public_threads_before`].
     pub async fn get_channel_archived_public_threads(
         &self,
         channel_id: u64,
@@ -2562,7 +2720,33 @@ impl Http {
         .await
     }
 
+    /// Gets archived public threads from a channel, most recently archived
+    /// first.
+    ///
+    /// Only threads archived before the `before` timestamp are returned.
+    pub async fn get_channel_archived_public_threads_before(
+        &self,
+        channel_id: u64,
+        before: Option<Timestamp>,
+        limit: Option<u64>,
+    ) -> Result<ThreadsData> {
+        self.fire(Request {
+            body: None,
+            multipart: None,
+            headers: None,
+            route: RouteInfo::GetChannelArchivedPublicThreadsBefore {
+                channel_id,
+                before,
+                limit,
+            },
+        })
+        .await
+    }
+
     /// Gets all archived private threads from a channel.
+    ///
+    /// Discord expects `before` to be a timestamp, see
+    /// [`Self::get_channel_archived_private_threads_before`].
     pub async fn get_channel_archived_private_threads(
         &self,
         channel_id: u64,
@@ -2582,7 +2766,33 @@ impl Http {
         .await
     }
 
-    /// Gets all archived private threads joined from a channel.
+    /// Gets archived private threads from a channel, most recently archived
+    /// first.
+    ///
+    /// Only threads archived before the `before` timestamp are returned.
+    pub async fn get_channel_archived_private_threads_before(
+        &self,
+        channel_id: u64,
+        before: Option<Timestamp>,
+        limit: Option<u64>,
+    ) -> Result<ThreadsData> {
+        self.fire(Request {
+            body: None,
+            multipart: None,
+            headers: None,
+            route: RouteInfo::GetChannelArchivedPrivateThreadsBefore {
+                channel_id,
+                before,
+                limit,
+            },
+        })
+        .await
+    }
+
+    /// Gets archived private threads joined from a channel, sorted by their Id
+    /// in descending order.
+    ///
+    /// Only threads with an Id lower than `before` are returned.
     pub async fn get_channel_joined_archived_private_threads(
         &self,
         channel_id: u64,
@@ -3598,6 +3808,114 @@ impl Http {
         .await
     }
 
+    /// Gets the current user's member information in a guild.
+    ///
+    /// This method only works for user tokens with the
+    /// [`GuildsMembersRead`] OAuth2 scope.
+    ///
+    /// [`GuildsMembersRead`]: crate::model::application::oauth::Scope::GuildsMembersRead
+    pub async fn get_current_user_guild_member(&self, guild_id: u64) -> Result<Member> {
+        let mut value = self
+            .request(Request {
+                body: None,
+                multipart: None,
+                headers: None,
+                route: RouteInfo::GetCurrentUserGuildMember {
+                    guild_id,
+                },
+            })
+            .await?
+            .json::<Value>()
+            .await?;
+
+        if let Some(map) = value.as_object_mut() {
+            map.insert("guild_id".to_string(), from_number(guild_id));
+        }
+
+        from_value::<Member>(value).map_err(From::from)
+    }
+
+    /// Gets the role connection the application has attached to the current
+    /// user.
+    ///
+    /// This method only works for user tokens with the
+    /// [`RoleConnectionsWrite`] OAuth2 scope.
+    ///
+    /// [`RoleConnectionsWrite`]: crate::model::application::oauth::Scope::RoleConnectionsWrite
+    pub async fn get_user_application_role_connection(
+        &self,
+        application_id: u64,
+    ) -> Result<RoleConnection> {
+        self.fire(Request {
+            body: None,
+            multipart: None,
+            headers: None,
+            route: RouteInfo::GetUserApplicationRoleConnection {
+                application_id,
+            },
+        })
+        .await
+    }
+
+    /// Updates the role connection the application has attached to the
+    /// current user, returning the updated connection.
+    ///
+    /// This method only works for user tokens with the
+    /// [`RoleConnectionsWrite`] OAuth2 scope.
+    ///
+    /// [`RoleConnectionsWrite`]: crate::model::application::oauth::Scope::RoleConnectionsWrite
+    pub async fn edit_user_application_role_connection(
+        &self,
+        application_id: u64,
+        connection: &RoleConnection,
+    ) -> Result<RoleConnection> {
+        let body = to_vec(connection)?;
+
+        self.fire(Request {
+            body: Some(&body),
+            multipart: None,
+            headers: None,
+            route: RouteInfo::EditUserApplicationRoleConnection {
+                application_id,
+            },
+        })
+        .await
+    }
+
+    /// Gets the role connection metadata fields of the current application.
+    pub async fn get_application_role_connection_metadata(
+        &self,
+    ) -> Result<Vec<RoleConnectionMetadata>> {
+        self.fire(Request {
+            body: None,
+            multipart: None,
+            headers: None,
+            route: RouteInfo::GetApplicationRoleConnectionMetadata {
+                application_id: self.try_application_id()?,
+            },
+        })
+        .await
+    }
+
+    /// Replaces the role connection metadata fields of the current
+    /// application. An application can have at most 5 fields.
+    pub async fn edit_application_role_connection_metadata(
+        &self,
+        metadata: &[RoleConnectionMetadata],
+    ) -> Result<Vec<RoleConnectionMetadata>> {
+        let body = to_vec(metadata)?;
+
+        self.fire(Request {
+            body: Some(&body),
+            multipart: None,
+            headers: None,
+            route: RouteInfo::EditApplicationRoleConnectionMetadata {
+                application_id: self.try_application_id()?,
+            },
+        })
+        .await
+    }
+
     /// Gets our DM channels.
     pub async fn get_user_dm_channels(&self) -> Result<Vec<PrivateChannel>> {
         self.fire(Request {
@@ -4044,6 +4362,9 @@ impl Http {
     /// Returns the raw reqwest Response. Use [`Self::fire`] to deserialize the response
     /// into some type.
     ///
+    /// If the request fails for a transient reason, it is retried according
+    /// to the [`Self::retry_policy`], if there is one.
+    ///
     /// # Examples
     ///
     /// Send a body of bytes over the [`RouteInfo::CreateMessage`] endpoint:
@@ -4075,16 +4396,70 @@ impl Http {
     /// # }
     /// ```
     #[instrument]
-    pub async fn request(&self, mut req: Request<'_>) -> Result<ReqwestResponse> {
-        let response = if self.ratelimiter_disabled {
-            let request =
-                req.build(&self.client, &self.token, self.proxy.as_ref()).await?.build()?;
-            self.client.execute(request).await?
-        } else {
-            let ratelimiting_req = RatelimitedRequest::from(req);
-            self.ratelimiter.perform(ratelimiting_req).await?
+    pub async fn request(&self, req: Request<'_>) -> Result<ReqwestResponse> {
+        let policy = match &self.retry_policy {
+            Some(policy) => policy,
+            None => return self.request_once(req).await,
         };
 
+        let method = req.route.deconstruct().0;
+        let replayable = req.is_replayable();
+        let mut attempt = 1;
+
+        loop {
+            // Only keep a copy of the request around if it may be sent again.
+            if !replayable || !policy.may_retry(method, attempt) {
+                return self.request_once(req).await;
+            }
+
+            let result = self.request_once(req.clone()).await;
+
+            if !policy.should_retry(method, attempt, &result) {
+                return result;
+            }
+
+            let delay = policy.delay(attempt);
+            debug!("Retrying failed request in {:?}, attempt {}", delay, attempt);
+
+            sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    async fn request_once(&self, mut req: Request<'_>) -> Result<ReqwestResponse> {
+        let start = Instant::now();
+        let mut short_circuit = None;
+
+        for middleware in &self.middlewares {
+            short_circuit = middleware.before_request(&mut req).await?;
+
+            if short_circuit.is_some() {
+                break;
+            }
+        }
+
+        let route = req.route.clone();
+
+        let mut result = match short_circuit {
+            Some(response) => Ok(response),
+            None => self.send(req).await,
+        };
+
+        let elapsed = start.elapsed();
+
+        for middleware in self.middlewares.iter().rev() {
+            match &mut result {
+                Ok(response) => {
+                    if let Err(why) = middleware.after_response(&route, response, elapsed).await {
+                        result = Err(why);
+                    }
+                },
+                Err(why) => middleware.on_error(&route, why, elapsed).await,
+            }
+        }
+
+        let response = result?;
+
         if response.status().is_success() {
             Ok(response)
         } else {
@@ -4092,6 +4467,49 @@ impl Http {
         }
     }
 
+    async fn send(&self, req: Request<'_>) -> Result<ReqwestResponse> {
+        #[cfg(feature = "http_cassettes")]
+        {
+            if let Some(cassette) = &self.cassette {
+                let recorded = RecordedRequest::new(&req)?;
+
+                return match cassette.mode() {
+                    CassetteMode::Record => {
+                        let response = self.execute(req).await?;
+
+                        cassette.record_response(recorded, response).await
+                    },
+                    CassetteMode::Replay => cassette.replay_request(&recorded).await,
+                };
+            }
+        }
+
+        self.execute(req).await
+    }
+
+    async fn execute(&self, mut req: Request<'_>) -> Result<ReqwestResponse> {
+        if self.ratelimiter_disabled {
+            let (method, route, _) = req.route.deconstruct();
+            let request =
+                req.build(&self.client, &self.token, self.proxy.as_ref()).await?.build()?;
+
+            let start = Instant::now();
+            let result = self.transport.execute(request).await;
+
+            match &result {
+                Ok(response) => {
+                    self.metrics.record_response(method, route, response.status(), start.elapsed());
+                },
+                Err(_) => self.metrics.record_error(method, route, start.elapsed()),
+            }
+
+            result
+        } else {
+            let ratelimiting_req = RatelimitedRequest::from(req);
+            self.ratelimiter.perform(ratelimiting_req).await
+        }
+    }
+
     /// Performs a request and then verifies that the response status code is equal
     /// to the expected value.
     ///
@@ -20,6 +20,14 @@ pub struct DiscordJsonError {
     pub errors: Vec<DiscordJsonSingleError>,
 }
 
+impl DiscordJsonError {
+    /// Returns the typed [`Self::code`].
+    #[must_use]
+    pub fn error_code(&self) -> JsonErrorCode {
+        JsonErrorCode::from_code(self.code)
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 pub struct DiscordJsonSingleError {
     /// The error code.
@@ -30,6 +38,450 @@ pub struct DiscordJsonSingleError {
     pub path: String,
 }
 
+/// Defines [`JsonErrorCode`] from a list of variants and their codes.
+macro_rules! json_error_codes {
+    ($($(#[doc = $doc:literal])* $variant:ident = $code:literal,)*) => {
+        /// The code of an error returned by Discord in a [`DiscordJsonError`].
+        ///
+        /// [Discord docs](https://discord.com/developers/docs/topics/opcodes-and-status-codes#json-json-error-codes).
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+        #[non_exhaustive]
+        pub enum JsonErrorCode {
+            $(
+                $(#[doc = $doc])*
+                $variant,
+            )*
+            /// An error code unknown to the library.
+            Unknown(isize),
+        }
+
+        impl JsonErrorCode {
+            /// Returns the variant of the given code.
+            #[must_use]
+            pub fn from_code(code: isize) -> Self {
+                match code {
+                    $($code => Self::$variant,)*
+                    _ => Self::Unknown(code),
+                }
+            }
+
+            /// Returns the numeric code.
+            #[must_use]
+            pub fn code(self) -> isize {
+                match self {
+                    $(Self::$variant => $code,)*
+                    Self::Unknown(code) => code,
+                }
+            }
+        }
+    };
+}
+
+json_error_codes! {
+    /// General error.
+    GeneralError = 0,
+    /// Unknown account.
+    UnknownAccount = 10001,
+    /// Unknown application.
+    UnknownApplication = 10002,
+    /// Unknown channel.
+    UnknownChannel = 10003,
+    /// Unknown guild.
+    UnknownGuild = 10004,
+    /// Unknown integration.
+    UnknownIntegration = 10005,
+    /// Unknown invite.
+    UnknownInvite = 10006,
+    /// Unknown member.
+    UnknownMember = 10007,
+    /// Unknown message.
+    UnknownMessage = 10008,
+    /// Unknown permission overwrite.
+    UnknownPermissionOverwrite = 10009,
+    /// Unknown provider.
+    UnknownProvider = 10010,
+    /// Unknown role.
+    UnknownRole = 10011,
+    /// Unknown token.
+    UnknownToken = 10012,
+    /// Unknown user.
+    UnknownUser = 10013,
+    /// Unknown emoji.
+    UnknownEmoji = 10014,
+    /// Unknown webhook.
+    UnknownWebhook = 10015,
+    /// Unknown webhook service.
+    UnknownWebhookService = 10016,
+    /// Unknown session.
+    UnknownSession = 10020,
+    /// Unknown ban.
+    UnknownBan = 10026,
+    /// Unknown SKU.
+    UnknownSku = 10027,
+    /// Unknown store listing.
+    UnknownStoreListing = 10028,
+    /// Unknown entitlement.
+    UnknownEntitlement = 10029,
+    /// Unknown build.
+    UnknownBuild = 10030,
+    /// Unknown lobby.
+    UnknownLobby = 10031,
+    /// Unknown branch.
+    UnknownBranch = 10032,
+    /// Unknown store directory layout.
+    UnknownStoreDirectoryLayout = 10033,
+    /// Unknown redistributable.
+    UnknownRedistributable = 10036,
+    /// Unknown gift code.
+    UnknownGiftCode = 10038,
+    /// Unknown stream.
+    UnknownStream = 10049,
+    /// Unknown premium server subscribe cooldown.
+    UnknownPremiumServerSubscribeCooldown = 10050,
+    /// Unknown guild template.
+    UnknownGuildTemplate = 10057,
+    /// Unknown discoverable server category.
+    UnknownDiscoverableServerCategory = 10059,
+    /// Unknown sticker.
+    UnknownSticker = 10060,
+    /// Unknown interaction.
+    UnknownInteraction = 10062,
+    /// Unknown application command.
+    UnknownApplicationCommand = 10063,
+    /// Unknown voice state.
+    UnknownVoiceState = 10065,
+    /// Unknown application command permissions.
+    UnknownApplicationCommandPermissions = 10066,
+    /// Unknown stage instance.
+    UnknownStageInstance = 10067,
+    /// Unknown guild member verification form.
+    UnknownGuildMemberVerificationForm = 10068,
+    /// Unknown guild welcome screen.
+    UnknownGuildWelcomeScreen = 10069,
+    /// Unknown guild scheduled event.
+    UnknownGuildScheduledEvent = 10070,
+    /// Unknown guild scheduled event user.
+    UnknownGuildScheduledEventUser = 10071,
+    /// Unknown tag.
+    UnknownTag = 10087,
+    /// Bots cannot use this endpoint.
+    BotsCannotUseEndpoint = 20001,
+    /// Only bots can use this endpoint.
+    OnlyBotsCanUseEndpoint = 20002,
+    /// Explicit content cannot be sent to the desired recipient(s).
+    ExplicitContentCannotBeSent = 20009,
+    /// You are not authorized to perform this action on this application.
+    NotAuthorizedForApplication = 20012,
+    /// This action cannot be performed due to slowmode rate limit.
+    SlowmodeRateLimit = 20016,
+    /// Only the owner of this account can perform this action.
+    OnlyAccountOwner = 20018,
+    /// This message cannot be edited due to announcement rate limits.
+    AnnouncementRateLimit = 20022,
+    /// Under minimum age.
+    UnderMinimumAge = 20024,
+    /// The channel you are writing has hit the write rate limit.
+    ChannelWriteRateLimit = 20028,
+    /// The write action you are performing on the server has hit the write rate limit.
+    ServerWriteRateLimit = 20029,
+    /// Your stage topic, server name, server description, or channel names contain words that are not allowed.
+    DisallowedWords = 20031,
+    /// Guild premium subscription level too low.
+    GuildPremiumLevelTooLow = 20035,
+    /// Maximum number of guilds reached.
+    MaximumGuilds = 30001,
+    /// Maximum number of friends reached.
+    MaximumFriends = 30002,
+    /// Maximum number of pins reached for the channel.
+    MaximumPins = 30003,
+    /// Maximum number of recipients reached.
+    MaximumRecipients = 30004,
+    /// Maximum number of guild roles reached.
+    MaximumGuildRoles = 30005,
+    /// Maximum number of webhooks reached.
+    MaximumWebhooks = 30007,
+    /// Maximum number of emojis reached.
+    MaximumEmojis = 30008,
+    /// Maximum number of reactions reached.
+    MaximumReactions = 30010,
+    /// Maximum number of group DMs reached.
+    MaximumGroupDms = 30011,
+    /// Maximum number of guild channels reached.
+    MaximumGuildChannels = 30013,
+    /// Maximum number of attachments in a message reached.
+    MaximumAttachments = 30015,
+    /// Maximum number of invites reached.
+    MaximumInvites = 30016,
+    /// Maximum number of animated emojis reached.
+    MaximumAnimatedEmojis = 30018,
+    /// Maximum number of server members reached.
+    MaximumServerMembers = 30019,
+    /// Maximum number of server categories has been reached.
+    MaximumServerCategories = 30030,
+    /// Guild already has a template.
+    GuildAlreadyHasTemplate = 30031,
+    /// Maximum number of application commands reached.
+    MaximumApplicationCommands = 30032,
+    /// Maximum number of thread participants has been reached.
+    MaximumThreadParticipants = 30033,
+    /// Maximum number of daily application command creates has been reached.
+    MaximumDailyApplicationCommandCreates = 30034,
+    /// Maximum number of bans for non-guild members have been exceeded.
+    MaximumNonMemberBans = 30035,
+    /// Maximum number of bans fetches has been reached.
+    MaximumBanFetches = 30037,
+    /// Maximum number of uncompleted guild scheduled events reached.
+    MaximumUncompletedScheduledEvents = 30038,
+    /// Maximum number of stickers reached.
+    MaximumStickers = 30039,
+    /// Maximum number of prune requests has been reached.
+    MaximumPruneRequests = 30040,
+    /// Maximum number of guild widget settings updates has been reached.
+    MaximumWidgetSettingsUpdates = 30042,
+    /// Maximum number of edits to messages older than 1 hour reached.
+    MaximumOldMessageEdits = 30046,
+    /// Maximum number of pinned threads in a forum channel has been reached.
+    MaximumPinnedForumThreads = 30047,
+    /// Maximum number of tags in a forum channel has been reached.
+    MaximumForumTags = 30048,
+    /// Bitrate is too high for channel of this type.
+    BitrateTooHigh = 30052,
+    /// Maximum number of premium emojis reached.
+    MaximumPremiumEmojis = 30056,
+    /// Maximum number of webhooks per guild reached.
+    MaximumGuildWebhooks = 30058,
+    /// Maximum number of channel permission overwrites reached.
+    MaximumPermissionOverwrites = 30060,
+    /// The channels for this guild are too large.
+    GuildChannelsTooLarge = 30061,
+    /// Unauthorized.
+    Unauthorized = 40001,
+    /// You need to verify your account in order to perform this action.
+    AccountVerificationRequired = 40002,
+    /// You are opening direct messages too fast.
+    OpeningDirectMessagesTooFast = 40003,
+    /// Send messages has been temporarily disabled.
+    SendMessagesTemporarilyDisabled = 40004,
+    /// Request entity too large.
+    RequestEntityTooLarge = 40005,
+    /// This feature has been temporarily disabled server-side.
+    FeatureTemporarilyDisabled = 40006,
+    /// The user is banned from this guild.
+    UserBannedFromGuild = 40007,
+    /// Connection has been revoked.
+    ConnectionRevoked = 40012,
+    /// Target user is not connected to voice.
+    TargetUserNotConnectedToVoice = 40032,
+    /// This message has already been crossposted.
+    MessageAlreadyCrossposted = 40033,
+    /// An application command with that name already exists.
+    ApplicationCommandNameExists = 40041,
+    /// Application interaction failed to send.
+    InteractionFailedToSend = 40043,
+    /// Cannot send a message in a forum channel.
+    CannotSendMessageInForumChannel = 40058,
+    /// Interaction has already been acknowledged.
+    InteractionAlreadyAcknowledged = 40060,
+    /// Tag names must be unique.
+    TagNamesMustBeUnique = 40061,
+    /// Service resource is being rate limited.
+    ServiceResourceRateLimited = 40062,
+    /// There are no tags available that can be set by non-moderators.
+    NoTagsAvailableToNonModerators = 40066,
+    /// A tag is required to create a forum post in this channel.
+    TagRequiredForForumPost = 40067,
+    /// Missing access.
+    MissingAccess = 50001,
+    /// Invalid account type.
+    InvalidAccountType = 50002,
+    /// Cannot execute action on a DM channel.
+    CannotExecuteOnDmChannel = 50003,
+    /// Guild widget disabled.
+    GuildWidgetDisabled = 50004,
+    /// Cannot edit a message authored by another user.
+    CannotEditMessageByOtherUser = 50005,
+    /// Cannot send an empty message.
+    CannotSendEmptyMessage = 50006,
+    /// Cannot send messages to this user.
+    CannotSendMessagesToUser = 50007,
+    /// Cannot send messages in a non-text channel.
+    CannotSendMessagesInNonTextChannel = 50008,
+    /// Channel verification level is too high for you to gain access.
+    ChannelVerificationLevelTooHigh = 50009,
+    /// OAuth2 application does not have a bot.
+    OAuth2ApplicationWithoutBot = 50010,
+    /// OAuth2 application limit reached.
+    OAuth2ApplicationLimitReached = 50011,
+    /// Invalid OAuth2 state.
+    InvalidOAuth2State = 50012,
+    /// You lack permissions to perform that action.
+    MissingPermissions = 50013,
+    /// Invalid authentication token provided.
+    InvalidAuthenticationToken = 50014,
+    /// Note was too long.
+    NoteTooLong = 50015,
+    /// Provided too few or too many messages to delete.
+    InvalidBulkDeleteAmount = 50016,
+    /// Invalid MFA level.
+    InvalidMfaLevel = 50017,
+    /// A message can only be pinned to the channel it was sent in.
+    CannotPinInDifferentChannel = 50019,
+    /// Invite code was either invalid or taken.
+    InvalidInviteCode = 50020,
+    /// Cannot execute action on a system message.
+    CannotExecuteOnSystemMessage = 50021,
+    /// Cannot execute action on this channel type.
+    CannotExecuteOnChannelType = 50024,
+    /// Invalid OAuth2 access token provided.
+    InvalidOAuth2AccessToken = 50025,
+    /// Missing required OAuth2 scope.
+    MissingOAuth2Scope = 50026,
+    /// Invalid webhook token provided.
+    InvalidWebhookToken = 50027,
+    /// Invalid role.
+    InvalidRole = 50028,
+    /// Invalid recipient(s).
+    InvalidRecipients = 50033,
+    /// A message provided was too old to bulk delete.
+    MessageTooOldToBulkDelete = 50034,
+    /// Invalid form body, or invalid Content-Type provided.
+    InvalidFormBody = 50035,
+    /// An invite was accepted to a guild the application's bot is not in.
+    InviteAcceptedToGuildWithoutBot = 50036,
+    /// Invalid activity action.
+    InvalidActivityAction = 50039,
+    /// Invalid API version provided.
+    InvalidApiVersion = 50041,
+    /// File uploaded exceeds the maximum size.
+    FileTooLarge = 50045,
+    /// Invalid file uploaded.
+    InvalidFile = 50046,
+    /// Cannot self-redeem this gift.
+    CannotSelfRedeemGift = 50054,
+    /// Invalid guild.
+    InvalidGuild = 50055,
+    /// Invalid message type.
+    InvalidMessageType = 50068,
+    /// Payment source required to redeem gift.
+    PaymentSourceRequired = 50070,
+    /// Cannot modify a system webhook.
+    CannotModifySystemWebhook = 50073,
+    /// Cannot delete a channel required for community guilds.
+    CannotDeleteCommunityChannel = 50074,
+    /// Cannot edit stickers within a message.
+    CannotEditMessageStickers = 50080,
+    /// Invalid sticker sent.
+    InvalidSticker = 50081,
+    /// Tried to perform an operation on an archived thread.
+    ThreadArchived = 50083,
+    /// Invalid thread notification settings.
+    InvalidThreadNotificationSettings = 50084,
+    /// The before value is earlier than the thread creation date.
+    BeforeEarlierThanThreadCreation = 50085,
+    /// Community server channels must be text channels.
+    CommunityChannelsMustBeText = 50086,
+    /// The entity type of the event is different from the entity you are trying to start the event for.
+    EventEntityTypeMismatch = 50091,
+    /// This server is not available in your location.
+    ServerNotAvailableInLocation = 50095,
+    /// This server needs monetization enabled in order to perform this action.
+    MonetizationRequired = 50097,
+    /// This server needs more boosts to perform this action.
+    MoreBoostsRequired = 50101,
+    /// The request body contains invalid JSON.
+    InvalidJson = 50109,
+    /// Ownership cannot be transferred to a bot user.
+    OwnershipCannotBeTransferredToBot = 50132,
+    /// Failed to resize asset below the maximum size.
+    AssetResizeFailed = 50138,
+    /// Uploaded file not found.
+    UploadedFileNotFound = 50146,
+    /// You do not have permission to send this sticker.
+    NoPermissionToSendSticker = 50600,
+    /// Two factor is required for this operation.
+    TwoFactorRequired = 60003,
+    /// No users with DiscordTag exist.
+    NoUsersWithTag = 80004,
+    /// Reaction was blocked.
+    ReactionBlocked = 90001,
+    /// Application not yet available.
+    ApplicationNotYetAvailable = 110001,
+    /// API resource is currently overloaded.
+    ApiResourceOverloaded = 130000,
+    /// The stage is already open.
+    StageAlreadyOpen = 150006,
+    /// Cannot reply without permission to read message history.
+    CannotReplyWithoutReadMessageHistory = 160002,
+    /// A thread has already been created for this message.
+    ThreadAlreadyCreated = 160004,
+    /// Thread is locked.
+    ThreadLocked = 160005,
+    /// Maximum number of active threads reached.
+    MaximumActiveThreads = 160006,
+    /// Maximum number of active announcement threads reached.
+    MaximumActiveAnnouncementThreads = 160007,
+    /// Invalid JSON for uploaded Lottie file.
+    InvalidLottieJson = 170001,
+    /// Uploaded Lotties cannot contain rasterized images such as PNG or JPEG.
+    LottieContainsRasterizedImages = 170002,
+    /// Sticker maximum framerate exceeded.
+    StickerMaximumFramerateExceeded = 170003,
+    /// Sticker frame count exceeds maximum of 1000 frames.
+    StickerMaximumFrameCountExceeded = 170004,
+    /// Lottie animation maximum dimensions exceeded.
+    LottieMaximumDimensionsExceeded = 170005,
+    /// Sticker frame rate is either too small or too large.
+    StickerFramerateOutOfRange = 170006,
+    /// Sticker animation duration exceeds maximum of 5 seconds.
+    StickerAnimationTooLong = 170007,
+    /// Cannot update a finished event.
+    CannotUpdateFinishedEvent = 180000,
+    /// Failed to create stage needed for stage event.
+    FailedToCreateStageForEvent = 180002,
+    /// Message was blocked by automatic moderation.
+    MessageBlockedByAutomod = 200000,
+    /// Title was blocked by automatic moderation.
+    TitleBlockedByAutomod = 200001,
+    /// Webhooks posted to forum channels must have a thread_name or thread_id.
+    ForumWebhookRequiresThread = 220001,
+    /// Webhooks posted to forum channels cannot have both a thread_name and thread_id.
+    ForumWebhookThreadNameAndId = 220002,
+    /// Webhooks can only create threads in forum channels.
+    WebhookThreadsOnlyInForums = 220003,
+    /// Webhook services cannot be used in forum channels.
+    WebhookServicesInForum = 220004,
+    /// Message blocked by harmful links filter.
+    MessageBlockedByHarmfulLinks = 240000,
+}
+
+impl JsonErrorCode {
+    /// Whether the error indicates that a resource, such as a channel or a
+    /// message, does not exist.
+    #[must_use]
+    pub fn is_unknown_resource(self) -> bool {
+        (10001..20000).contains(&self.code())
+    }
+
+    /// Whether the error indicates that the current user lacks the
+    /// permissions or access required for the action.
+    #[must_use]
+    pub fn is_missing_permissions(self) -> bool {
+        matches!(self, Self::MissingPermissions | Self::MissingAccess)
+    }
+}
+
+impl From<isize> for JsonErrorCode {
+    fn from(code: isize) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<JsonErrorCode> for isize {
+    fn from(code: JsonErrorCode) -> Self {
+        code.code()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ErrorResponse {
     pub status_code: StatusCode,
@@ -78,6 +530,19 @@ pub enum Error {
     InvalidPort,
     /// When an application id was expected but missing.
     ApplicationIdMissing,
+    /// When a streamed attachment was already uploaded, and can not be read
+    /// again.
+    AttachmentStreamConsumed,
+    /// When a replaying [`Cassette`] has no recording left for a request.
+    ///
+    /// [`Cassette`]: super::Cassette
+    #[cfg(feature = "http_cassettes")]
+    UnrecordedRequest { method: String, path: String },
+    /// When a recording of a [`Cassette`] can not be turned into a response.
+    ///
+    /// [`Cassette`]: super::Cassette
+    #[cfg(feature = "http_cassettes")]
+    InvalidCassette,
 }
 
 impl Error {
@@ -113,6 +578,62 @@ impl Error {
             _ => None,
         }
     }
+
+    /// Returns the JSON error code if the error is an unsuccessful request
+    /// and Discord responded with one.
+    #[must_use]
+    pub fn json_error_code(&self) -> Option<JsonErrorCode> {
+        match self {
+            // Responses without a JSON error are decoded with a code of -1.
+            Self::UnsuccessfulRequest(res) if res.error.code >= 0 => Some(res.error.error_code()),
+            _ => None,
+        }
+    }
+
+    /// Returns true when the current user lacks the permissions or access
+    /// required for the request.
+    #[must_use]
+    pub fn is_missing_permissions(&self) -> bool {
+        self.json_error_code().map_or(false, JsonErrorCode::is_missing_permissions)
+    }
+
+    /// Returns true when the requested resource does not exist.
+    #[must_use]
+    pub fn is_unknown_resource(&self) -> bool {
+        self.json_error_code().map_or(false, JsonErrorCode::is_unknown_resource)
+            || self.status_code() == Some(StatusCode::NOT_FOUND)
+    }
+
+    /// Returns true when the request failed for a transient reason, and may
+    /// succeed if it is sent again later.
+    ///
+    /// The status codes considered transient are the ones the default
+    /// [`RetryPolicy`] retries on, as well as 429, which is retried by the
+    /// [`Ratelimiter`] instead. A 500 usually means that Discord failed to
+    /// handle this specific request, so it is not considered transient.
+    ///
+    /// [`RetryPolicy`]: super::RetryPolicy
+    /// [`Ratelimiter`]: super::ratelimiting::Ratelimiter
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::UnsuccessfulRequest(res) => {
+                matches!(
+                    res.status_code,
+                    StatusCode::TOO_MANY_REQUESTS
+                        | StatusCode::BAD_GATEWAY
+                        | StatusCode::SERVICE_UNAVAILABLE
+                        | StatusCode::GATEWAY_TIMEOUT
+                ) || matches!(
+                    res.error.error_code(),
+                    JsonErrorCode::ApiResourceOverloaded
+                        | JsonErrorCode::ServiceResourceRateLimited
+                )
+            },
+            Self::Request(error) => error.is_connect() || error.is_timeout(),
+            _ => false,
+        }
+    }
 }
 
 impl From<ErrorResponse> for Error {
@@ -172,6 +693,16 @@ impl fmt::Display for Error {
             Self::InvalidScheme => f.write_str("Invalid Url scheme."),
             Self::InvalidPort => f.write_str("Invalid port."),
             Self::ApplicationIdMissing => f.write_str("Application id was expected but missing."),
+            Self::AttachmentStreamConsumed => {
+                f.write_str("Streamed attachment was already uploaded.")
+            },
+            #[cfg(feature = "http_cassettes")]
+            Self::UnrecordedRequest {
+                method,
+                path,
+            } => write!(f, "No recorded response left for {} {}.", method, path),
+            #[cfg(feature = "http_cassettes")]
+            Self::InvalidCassette => f.write_str("Cassette contains an invalid recording."),
         }
     }
 }
@@ -219,4 +750,37 @@ mod test {
 
         assert_eq!(error_response, known);
     }
+
+    #[test]
+    fn test_json_error_code() {
+        assert_eq!(JsonErrorCode::from_code(50013), JsonErrorCode::MissingPermissions);
+        assert_eq!(JsonErrorCode::from_code(1), JsonErrorCode::Unknown(1));
+        assert_eq!(JsonErrorCode::UnknownMessage.code(), 10008);
+        assert!(JsonErrorCode::UnknownChannel.is_unknown_resource());
+        assert!(!JsonErrorCode::MissingAccess.is_unknown_resource());
+    }
+
+    #[test]
+    fn test_classification() {
+        let error = |status, code| {
+            Error::UnsuccessfulRequest(ErrorResponse {
+                status_code: StatusCode::from_u16(status).unwrap(),
+                url: String::from("https://ferris.crab").parse().unwrap(),
+                error: DiscordJsonError {
+                    code,
+                    message: String::new(),
+                    errors: vec![],
+                },
+            })
+        };
+
+        assert!(error(403, 50013).is_missing_permissions());
+        assert!(error(404, 10008).is_unknown_resource());
+        assert!(error(404, -1).is_unknown_resource());
+        assert!(error(503, -1).is_retryable());
+        assert!(!error(500, -1).is_retryable());
+        assert!(!error(403, 50013).is_retryable());
+        assert_eq!(error(400, 50035).json_error_code(), Some(JsonErrorCode::InvalidFormBody));
+        assert_eq!(error(502, -1).json_error_code(), None);
+    }
 }
@@ -0,0 +1,306 @@
+//! Collecting metrics about the requests performed by [`Http`].
+//!
+//! [`Http`]: super::Http
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use reqwest::StatusCode;
+use tokio::time::Duration;
+
+use super::routing::Route;
+use super::LightMethod;
+
+/// The upper bounds of the [`LatencyHistogram`] buckets, matching the default
+/// buckets of Prometheus histograms.
+pub const LATENCY_BUCKETS: [Duration; 11] = [
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(25),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_millis(2500),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+];
+
+/// Collects request counts, status codes, latencies and ratelimit waits per
+/// route and method.
+///
+/// Routes are grouped by their template, the name of the [`Route`] without
+/// its Ids - such as `ChannelsIdMessages` - so that the number of collected
+/// metrics stays bounded no matter how many channels or guilds are requested.
+///
+/// Every [`Http`] client collects metrics, which are read via
+/// [`Http::metrics`]. Only requests which were actually sent are counted;
+/// responses produced by a [`Middleware`] are not.
+///
+/// [`Http`]: super::Http
+/// [`Http::metrics`]: super::Http::metrics
+/// [`Middleware`]: super::Middleware
+#[derive(Debug, Default)]
+pub struct HttpMetrics {
+    routes: Mutex<HashMap<(LightMethod, String), RouteMetrics>>,
+}
+
+impl HttpMetrics {
+    /// Returns the metrics collected so far.
+    ///
+    /// All values are cumulative since the client was created, so they map
+    /// directly to Prometheus counters and histograms.
+    #[must_use]
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            routes: self.routes.lock().expect("metrics lock poisoned").clone(),
+        }
+    }
+
+    /// Records a response to a request on the route, received after the
+    /// given latency.
+    pub(crate) fn record_response(
+        &self,
+        method: LightMethod,
+        route: Route,
+        status: StatusCode,
+        latency: Duration,
+    ) {
+        self.update(method, route, |metrics| {
+            metrics.requests += 1;
+            *metrics.status_codes.entry(status.as_u16()).or_default() += 1;
+
+            if status == StatusCode::TOO_MANY_REQUESTS {
+                metrics.ratelimited += 1;
+            }
+
+            metrics.latency.observe(latency);
+        });
+    }
+
+    /// Records a request on the route for which no response was received.
+    pub(crate) fn record_error(&self, method: LightMethod, route: Route, latency: Duration) {
+        self.update(method, route, |metrics| {
+            metrics.requests += 1;
+            metrics.errors += 1;
+            metrics.latency.observe(latency);
+        });
+    }
+
+    /// Records time a request on the route spent waiting on a ratelimit.
+    pub(crate) fn record_ratelimit_wait(&self, method: LightMethod, route: Route, wait: Duration) {
+        self.update(method, route, |metrics| metrics.ratelimit_wait += wait);
+    }
+
+    fn update(&self, method: LightMethod, route: Route, f: impl FnOnce(&mut RouteMetrics)) {
+        let mut routes = self.routes.lock().expect("metrics lock poisoned");
+
+        f(routes.entry((method, template(route))).or_default());
+    }
+}
+
+/// Returns the name of the route without its Ids.
+fn template(route: Route) -> String {
+    let mut template = format!("{:?}", route);
+
+    if let Some(index) = template.find('(') {
+        template.truncate(index);
+    }
+
+    template
+}
+
+/// The metrics collected by [`HttpMetrics`] at a point in time.
+///
+/// # Examples
+///
+/// Print the number of requests and the average latency of every route
+/// template:
+///
+/// ```rust,no_run
+/// # use serenity::http::Http;
+/// #
+/// # fn run(http: &Http) {
+/// let snapshot = http.metrics();
+///
+/// for ((method, route), metrics) in &snapshot.routes {
+///     let average = metrics.latency.sum.checked_div(metrics.latency.count as u32);
+///
+///     println!("{:?} {}: {} requests, {:?} on average", method, route, metrics.requests, average);
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct MetricsSnapshot {
+    /// The metrics of every route template and method a request was
+    /// performed on.
+    pub routes: HashMap<(LightMethod, String), RouteMetrics>,
+}
+
+impl MetricsSnapshot {
+    /// Returns the total number of requests across all routes.
+    #[must_use]
+    pub fn total_requests(&self) -> u64 {
+        self.routes.values().map(|metrics| metrics.requests).sum()
+    }
+
+    /// Returns the total number of 429 responses across all routes.
+    #[must_use]
+    pub fn total_ratelimited(&self) -> u64 {
+        self.routes.values().map(|metrics| metrics.ratelimited).sum()
+    }
+}
+
+/// The metrics of a single route template and method.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct RouteMetrics {
+    /// The number of requests sent, including ones retried by the
+    /// [`Ratelimiter`] or according to the [`RetryPolicy`].
+    ///
+    /// [`Ratelimiter`]: super::ratelimiting::Ratelimiter
+    /// [`RetryPolicy`]: super::RetryPolicy
+    pub requests: u64,
+    /// The number of requests for which no response was received, for
+    /// example because the connection failed.
+    pub errors: u64,
+    /// The number of responses per status code.
+    pub status_codes: BTreeMap<u16, u64>,
+    /// The number of responses with status code 429.
+    pub ratelimited: u64,
+    /// The time between sending requests and receiving their responses. This
+    /// does not include time spent waiting on ratelimits.
+    pub latency: LatencyHistogram,
+    /// The total time requests spent waiting on ratelimits, including the
+    /// global ratelimit, before being sent.
+    pub ratelimit_wait: Duration,
+}
+
+impl RouteMetrics {
+    /// Returns the number of responses with a 2xx status code.
+    #[must_use]
+    pub fn successes(&self) -> u64 {
+        self.status_codes.range(200..300).map(|(_, count)| count).sum()
+    }
+}
+
+/// A histogram of request latencies, with the buckets given by
+/// [`LATENCY_BUCKETS`].
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct LatencyHistogram {
+    /// The number of observations per bucket. The count at each index is the
+    /// number of requests which took at most the duration at the same index of
+    /// [`LATENCY_BUCKETS`], and is thus cumulative like Prometheus' `le`
+    /// buckets.
+    pub buckets: [u64; LATENCY_BUCKETS.len()],
+    /// The sum of all observed latencies.
+    pub sum: Duration,
+    /// The number of observed latencies.
+    pub count: u64,
+}
+
+impl LatencyHistogram {
+    fn observe(&mut self, latency: Duration) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS.iter()) {
+            if latency <= *bound {
+                *bucket += 1;
+            }
+        }
+
+        self.sum += latency;
+        self.count += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use http_crate::response::Builder;
+    use reqwest::{Request as ReqwestRequest, Response};
+    use tokio::sync::Mutex;
+
+    use super::{template, LatencyHistogram};
+    use crate::http::request::RequestBuilder;
+    use crate::http::routing::{Route, RouteInfo};
+    use crate::http::{HttpBuilder, LightMethod, Transport};
+
+    /// Answers with the queued responses, in order.
+    struct Queued {
+        responses: Mutex<Vec<Response>>,
+    }
+
+    #[async_trait]
+    impl Transport for Arc<Queued> {
+        async fn execute(&self, _: ReqwestRequest) -> crate::Result<Response> {
+            Ok(self.responses.lock().await.remove(0))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_records_responses() {
+        let ratelimited = Builder::new()
+            .status(429)
+            .header("retry-after", "0.001")
+            .header("x-ratelimit-bucket", "abc")
+            .body("{}")
+            .unwrap()
+            .into();
+        let ok = Builder::new().status(200).body("{}").unwrap().into();
+        let other_channel = Builder::new().status(200).body("{}").unwrap().into();
+        let not_found = Builder::new().status(404).body("{}").unwrap().into();
+
+        let transport = Arc::new(Queued {
+            responses: Mutex::new(vec![ratelimited, ok, other_channel, not_found]),
+        });
+        let http = HttpBuilder::new("token").transport(Arc::clone(&transport)).build();
+
+        let request = |channel_id| {
+            RequestBuilder::new(RouteInfo::GetChannel {
+                channel_id,
+            })
+            .build()
+        };
+
+        assert!(http.request(request(1)).await.is_ok());
+        assert!(http.request(request(2)).await.is_ok());
+        assert!(http.request(request(3)).await.is_err());
+
+        // Requests to different channels are grouped together.
+        let snapshot = http.metrics();
+        let metrics = &snapshot.routes[&(LightMethod::Get, "ChannelsId".to_string())];
+
+        assert_eq!(snapshot.routes.len(), 1);
+        assert_eq!(snapshot.total_requests(), 4);
+        assert_eq!(metrics.ratelimited, 1);
+        assert_eq!(metrics.successes(), 2);
+        assert_eq!(metrics.status_codes[&404], 1);
+        assert_eq!(metrics.latency.count, 4);
+        assert!(metrics.ratelimit_wait >= tokio::time::Duration::from_millis(1));
+    }
+
+    #[test]
+    fn test_template() {
+        assert_eq!(template(Route::ChannelsId(1)), "ChannelsId");
+        assert_eq!(
+            template(Route::ChannelsIdMessagesId(LightMethod::Delete, 1)),
+            "ChannelsIdMessagesId"
+        );
+        assert_eq!(template(Route::GatewayBot), "GatewayBot");
+    }
+
+    #[test]
+    fn test_histogram() {
+        let mut histogram = LatencyHistogram::default();
+        histogram.observe(tokio::time::Duration::from_millis(30));
+        histogram.observe(tokio::time::Duration::from_secs(20));
+
+        assert_eq!(histogram.buckets[..4], [0, 0, 0, 1]);
+        assert_eq!(histogram.buckets[10], 1);
+        assert_eq!(histogram.count, 2);
+    }
+}
@@ -0,0 +1,204 @@
+//! Hooks into every request performed by [`Http`].
+//!
+//! [`Http`]: super::Http
+
+use async_trait::async_trait;
+use reqwest::Response;
+use tokio::time::Duration;
+
+use super::request::Request;
+use super::routing::RouteInfo;
+use crate::internal::prelude::*;
+
+/// Middleware sees every request [`Http`] performs, and every response or
+/// error it results in.
+///
+/// Middleware is registered via [`HttpBuilder::middleware`]. The
+/// [`Self::before_request`] hooks run in the order of registration, right
+/// before the request is handed to the ratelimiter, while the
+/// [`Self::after_response`] and [`Self::on_error`] hooks run in reverse order.
+/// A request which is retried according to the [`RetryPolicy`] passes through
+/// the hooks once per attempt.
+///
+/// All methods do nothing by default.
+///
+/// # Examples
+///
+/// Log how long each request took:
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use serenity::http::routing::RouteInfo;
+/// use serenity::http::{HttpBuilder, Middleware};
+/// use serenity::{async_trait, Result};
+///
+/// struct Timing;
+///
+/// #[async_trait]
+/// impl Middleware for Timing {
+///     async fn after_response(
+///         &self,
+///         route: &RouteInfo<'_>,
+///         response: &mut reqwest::Response,
+///         elapsed: Duration,
+///     ) -> Result<()> {
+///         println!("{} {} took {:?}", response.status(), route.deconstruct().2, elapsed);
+///
+///         Ok(())
+///     }
+/// }
+///
+/// let http = HttpBuilder::new("token").middleware(Timing).build();
+/// ```
+///
+/// [`Http`]: super::Http
+/// [`HttpBuilder::middleware`]: super::HttpBuilder::middleware
+/// [`RetryPolicy`]: super::RetryPolicy
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    /// Called before the request is performed, allowing to modify it, e.g.
+    /// its headers via [`Request::headers_mut`].
+    ///
+    /// Returning a response short-circuits the request: it is not sent, and
+    /// the returned response is used instead. The `before_request` hooks of
+    /// later middleware are skipped, but all [`Self::after_response`] hooks
+    /// are still called.
+    ///
+    /// # Errors
+    ///
+    /// Returning an error aborts the request with that error.
+    async fn before_request(&self, _request: &mut Request<'_>) -> Result<Option<Response>> {
+        Ok(None)
+    }
+
+    /// Called with the response to a request, regardless of its status code,
+    /// and the time elapsed since the request was handed to the ratelimiter.
+    /// This includes time spent waiting on ratelimits.
+    ///
+    /// # Errors
+    ///
+    /// Returning an error discards the response, returning the error instead.
+    async fn after_response(
+        &self,
+        _route: &RouteInfo<'_>,
+        _response: &mut Response,
+        _elapsed: Duration,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called when no response could be received for a request, for example
+    /// because the connection failed.
+    async fn on_error(&self, _route: &RouteInfo<'_>, _error: &Error, _elapsed: Duration) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use http_crate::response::Builder;
+    use reqwest::header::HeaderValue;
+    use reqwest::{Request as ReqwestRequest, Response};
+    use tokio::time::Duration;
+
+    use super::Middleware;
+    use crate::http::request::{Request, RequestBuilder};
+    use crate::http::routing::RouteInfo;
+    use crate::http::{HttpBuilder, Transport};
+
+    /// Answers with a 200 if the request has the `x-test` header, and with a
+    /// 400 otherwise.
+    #[derive(Default)]
+    struct RequiresHeader {
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for Arc<RequiresHeader> {
+        async fn execute(&self, request: ReqwestRequest) -> crate::Result<Response> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+
+            let status = if request.headers().contains_key("x-test") { 200 } else { 400 };
+
+            Ok(Builder::new().status(status).body("{}").unwrap().into())
+        }
+    }
+
+    #[derive(Default)]
+    struct AddHeader {
+        responses: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Middleware for Arc<AddHeader> {
+        async fn before_request(
+            &self,
+            request: &mut Request<'_>,
+        ) -> crate::Result<Option<Response>> {
+            let headers = request.headers_mut().get_or_insert_with(Default::default);
+            headers.insert("x-test", HeaderValue::from_static("1"));
+
+            Ok(None)
+        }
+
+        async fn after_response(
+            &self,
+            _: &RouteInfo<'_>,
+            _: &mut Response,
+            _: Duration,
+        ) -> crate::Result<()> {
+            self.responses.fetch_add(1, Ordering::SeqCst);
+
+            Ok(())
+        }
+    }
+
+    struct ShortCircuit;
+
+    #[async_trait]
+    impl Middleware for ShortCircuit {
+        async fn before_request(&self, _: &mut Request<'_>) -> crate::Result<Option<Response>> {
+            Ok(Some(Builder::new().status(204).body("").unwrap().into()))
+        }
+    }
+
+    fn request() -> Request<'static> {
+        RequestBuilder::new(RouteInfo::GetChannel {
+            channel_id: 1,
+        })
+        .build()
+    }
+
+    #[tokio::test]
+    async fn test_modifies_request() {
+        let transport = Arc::new(RequiresHeader::default());
+        let middleware = Arc::new(AddHeader::default());
+        let http = HttpBuilder::new("token")
+            .transport(Arc::clone(&transport))
+            .middleware(Arc::clone(&middleware))
+            .build();
+
+        assert!(http.request(request()).await.is_ok());
+        assert_eq!(middleware.responses.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_short_circuits() {
+        let transport = Arc::new(RequiresHeader::default());
+        let middleware = Arc::new(AddHeader::default());
+        let http = HttpBuilder::new("token")
+            .transport(Arc::clone(&transport))
+            .middleware(ShortCircuit)
+            .middleware(Arc::clone(&middleware))
+            .build();
+
+        let response = http.request(request()).await.unwrap();
+
+        assert_eq!(response.status(), 204);
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 0);
+        assert_eq!(middleware.responses.load(Ordering::SeqCst), 1);
+    }
+}
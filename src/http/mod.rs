@@ -14,7 +14,18 @@
 //! The former require a [`Client`] to have logged in, while the latter may be
 //! made regardless of any other usage of the library.
 //!
-//! If a request spuriously fails, it will be retried once.
+//! If a request spuriously fails, it can be retried according to a
+//! [`RetryPolicy`].
+//!
+//! Requests on behalf of a user are performed with an OAuth2 access token,
+//! acquired via the [`OAuth2Client`].
+//!
+//! Request counts, latencies and ratelimit waits are collected per route, and
+//! can be read via [`Http::metrics`].
+//!
+//! With the `http_cassettes` feature, requests and their responses can be
+//! recorded to a file and replayed later via a `Cassette`, for testing without
+//! network access.
 //!
 //! Note that you may want to perform requests through a [model]s'
 //! instance methods where possible, as they each offer different
@@ -23,12 +34,19 @@
 //! [`Client`]: crate::Client
 //! [model]: crate::model
 
+#[cfg(feature = "http_cassettes")]
+pub mod cassette;
 pub mod client;
 pub mod error;
+pub mod metrics;
+pub mod middleware;
 pub mod multipart;
+pub mod oauth;
 pub mod ratelimiting;
 pub mod request;
+pub mod retry;
 pub mod routing;
+pub mod transport;
 pub mod typing;
 mod utils;
 
@@ -37,9 +55,16 @@ use std::sync::Arc;
 use reqwest::Method;
 pub use reqwest::StatusCode;
 
+#[cfg(feature = "http_cassettes")]
+pub use self::cassette::{Cassette, CassetteMode};
 pub use self::client::*;
-pub use self::error::Error as HttpError;
+pub use self::error::{Error as HttpError, JsonErrorCode};
+pub use self::metrics::{HttpMetrics, MetricsSnapshot};
+pub use self::middleware::Middleware;
+pub use self::oauth::OAuth2Client;
 use self::request::Request;
+pub use self::retry::RetryPolicy;
+pub use self::transport::Transport;
 pub use self::typing::*;
 #[cfg(feature = "cache")]
 use crate::cache::Cache;
@@ -32,19 +32,30 @@ impl<'a> Multipart<'a> {
             let part_name =
                 if file_num == 0 { "file".to_string() } else { format!("file{}", file_num) };
 
-            let data = file.data(client).await?;
             let filename = file.filename()?;
 
-            // Modify current AttachmentType to Bytes variant to prevent the
-            // need for another disk read or network request when retrying
-            if let AttachmentType::Path(_) | AttachmentType::Image(_) = file {
-                *file = AttachmentType::Bytes {
-                    data: data.clone().into(),
-                    filename: filename.clone().unwrap_or_default(),
-                };
-            }
+            let mut part = if let AttachmentType::Stream {
+                stream, ..
+            } = file
+            {
+                // Streamed attachments are sent as they are read, and are
+                // never held in memory.
+                Part::stream_with_length(stream.body()?, stream.length())
+            } else {
+                let data = file.data(client).await?;
+
+                // Modify current AttachmentType to Bytes variant to prevent the
+                // need for another disk read or network request when retrying
+                if let AttachmentType::Path(_) | AttachmentType::Image(_) = file {
+                    *file = AttachmentType::Bytes {
+                        data: data.clone().into(),
+                        filename: filename.clone().unwrap_or_default(),
+                    };
+                }
+
+                Part::bytes(data)
+            };
 
-            let mut part = Part::bytes(data);
             if let Some(filename) = filename {
                 part = guess_mime_str(part, &filename)?;
                 part = part.file_name(filename);
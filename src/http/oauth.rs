@@ -0,0 +1,265 @@
+//! Acquiring OAuth2 access tokens, which allow requests on behalf of a user.
+//!
+//! Once a user authorized the application at the [`OAuth2Client::authorization_url`],
+//! Discord redirects them back with a code, which is exchanged for an
+//! [`AccessToken`] via [`OAuth2Client::exchange_code`]. Its
+//! [`AccessToken::access_token`] is then used with [`Http::new_bearer`].
+
+use reqwest::header::{HeaderMap as Headers, HeaderValue, CONTENT_TYPE};
+use url::form_urlencoded::Serializer;
+use url::Url;
+
+use super::request::Request;
+use super::routing::RouteInfo;
+use super::Http;
+use crate::internal::prelude::*;
+use crate::model::application::oauth::{AccessToken, Scope};
+use crate::model::id::ApplicationId;
+
+/// A client for the OAuth2 token endpoints of an application.
+///
+/// Requests are performed through an [`Http`] client without a token, so
+/// they are subject to its ratelimiter and any configured [`RetryPolicy`] or
+/// [`Middleware`].
+///
+/// # Examples
+///
+/// Exchange the code a user was redirected back with, and get the guilds
+/// they are in:
+///
+/// ```rust,no_run
+/// # async fn run() -> serenity::Result<()> {
+/// use serenity::http::{Http, OAuth2Client};
+///
+/// let oauth = OAuth2Client::new(1234, "client secret");
+/// let token = oauth.exchange_code("code", "https://example.com/callback").await?;
+///
+/// let http = Http::new_bearer(&token.access_token);
+/// let guilds = http.get_guilds(None, None).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`RetryPolicy`]: super::RetryPolicy
+/// [`Middleware`]: super::Middleware
+#[derive(Debug)]
+pub struct OAuth2Client {
+    http: Http,
+    client_id: ApplicationId,
+    client_secret: String,
+}
+
+impl OAuth2Client {
+    /// Creates a client for the application with the given client Id and
+    /// secret.
+    #[must_use]
+    pub fn new(client_id: impl Into<ApplicationId>, client_secret: impl Into<String>) -> Self {
+        Self::with_http(Http::new(""), client_id, client_secret)
+    }
+
+    /// Creates a client which performs its requests through the given [`Http`]
+    /// client.
+    ///
+    /// **Note**: The `Http` client should have been created with an empty
+    /// token, so that it does not send the bot's token along.
+    #[must_use]
+    pub fn with_http(
+        http: Http,
+        client_id: impl Into<ApplicationId>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        Self {
+            http,
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+        }
+    }
+
+    /// Builds the URL a user has to visit to authorize the application with
+    /// the given scopes, using the authorization code grant.
+    ///
+    /// The `state` is passed back along with the code, and should be used to
+    /// prevent cross-site request forgery.
+    #[must_use]
+    pub fn authorization_url(
+        &self,
+        scopes: &[Scope],
+        redirect_uri: &str,
+        state: Option<&str>,
+    ) -> String {
+        let mut params = vec![
+            ("client_id", self.client_id.0.to_string()),
+            ("response_type", "code".to_string()),
+            ("redirect_uri", redirect_uri.to_string()),
+            ("scope", join_scopes(scopes)),
+        ];
+
+        if let Some(state) = state {
+            params.push(("state", state.to_string()));
+        }
+
+        let url = Url::parse_with_params("https://discord.com/api/oauth2/authorize", &params)
+            .expect("failed to construct URL");
+
+        url.to_string()
+    }
+
+    /// Exchanges the code a user was redirected back with for an access
+    /// token. The `redirect_uri` must be the one the code was issued for.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Http`] if the code is invalid or expired.
+    pub async fn exchange_code(&self, code: &str, redirect_uri: &str) -> Result<AccessToken> {
+        self.token(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", redirect_uri),
+        ])
+        .await
+    }
+
+    /// Exchanges a refresh token for a new access token, once the previous
+    /// one expired.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Http`] if the refresh token is invalid or was
+    /// revoked.
+    pub async fn refresh_token(&self, refresh_token: &str) -> Result<AccessToken> {
+        self.token(&[("grant_type", "refresh_token"), ("refresh_token", refresh_token)]).await
+    }
+
+    /// Gets an access token on behalf of the owner of the application, for
+    /// the given scopes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Http`] if a scope is not available to the
+    /// client credentials grant.
+    pub async fn client_credentials(&self, scopes: &[Scope]) -> Result<AccessToken> {
+        let scope = join_scopes(scopes);
+
+        self.token(&[("grant_type", "client_credentials"), ("scope", &scope)]).await
+    }
+
+    /// Revokes an access token or refresh token. Revoking either one revokes
+    /// both.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Http`] if the client credentials are invalid.
+    pub async fn revoke_token(&self, token: &str) -> Result<()> {
+        let body = self.form(&[("token", token)]);
+
+        self.http.request(form_request(&body, RouteInfo::RevokeOAuth2Token)).await?;
+
+        Ok(())
+    }
+
+    async fn token(&self, params: &[(&str, &str)]) -> Result<AccessToken> {
+        let body = self.form(params);
+
+        self.http.fire(form_request(&body, RouteInfo::ExchangeOAuth2Token)).await
+    }
+
+    /// Encodes the params along with the client credentials as a form.
+    fn form(&self, params: &[(&str, &str)]) -> Vec<u8> {
+        Serializer::new(String::new())
+            .append_pair("client_id", &self.client_id.0.to_string())
+            .append_pair("client_secret", &self.client_secret)
+            .extend_pairs(params)
+            .finish()
+            .into_bytes()
+    }
+}
+
+fn form_request<'a>(body: &'a [u8], route: RouteInfo<'a>) -> Request<'a> {
+    let mut headers = Headers::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/x-www-form-urlencoded"));
+
+    Request {
+        body: Some(body),
+        multipart: None,
+        headers: Some(headers),
+        route,
+    }
+}
+
+fn join_scopes(scopes: &[Scope]) -> String {
+    scopes.iter().map(ToString::to_string).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use http_crate::response::Builder;
+    use reqwest::{Request as ReqwestRequest, Response};
+    use tokio::sync::Mutex;
+
+    use super::OAuth2Client;
+    use crate::http::{HttpBuilder, Transport};
+    use crate::model::application::oauth::Scope;
+
+    /// Records the requests, and answers each with a token.
+    #[derive(Default)]
+    struct Recording {
+        requests: Mutex<Vec<ReqwestRequest>>,
+    }
+
+    #[async_trait]
+    impl Transport for Arc<Recording> {
+        async fn execute(&self, request: ReqwestRequest) -> crate::Result<Response> {
+            self.requests.lock().await.push(request);
+
+            let body = r#"{
+                "access_token": "access",
+                "token_type": "Bearer",
+                "expires_in": 604800,
+                "refresh_token": "refresh",
+                "scope": "identify guilds unknown.scope"
+            }"#;
+
+            Ok(Builder::new().status(200).body(body).unwrap().into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_exchange_code() {
+        let transport = Arc::new(Recording::default());
+        let http = HttpBuilder::new("").transport(Arc::clone(&transport)).build();
+        let oauth = OAuth2Client::with_http(http, 1, "secret");
+
+        let token = oauth.exchange_code("abc", "https://example.com/a b").await.unwrap();
+
+        assert_eq!(token.access_token, "access");
+        assert_eq!(token.refresh_token.as_deref(), Some("refresh"));
+        assert_eq!(token.scopes(), vec![Scope::Identify, Scope::Guilds]);
+
+        let requests = transport.requests.lock().await;
+        let request = &requests[0];
+        let body = request.body().and_then(reqwest::Body::as_bytes).unwrap();
+
+        assert_eq!(request.url().as_str(), "https://discord.com/api/v10/oauth2/token");
+        assert!(!request.headers().contains_key("authorization"));
+        assert_eq!(request.headers()["content-type"], "application/x-www-form-urlencoded");
+        assert_eq!(
+            body,
+            b"client_id=1&client_secret=secret&grant_type=authorization_code&code=abc&redirect_uri=https%3A%2F%2Fexample.com%2Fa+b"
+        );
+    }
+
+    #[test]
+    fn test_authorization_url() {
+        let oauth = OAuth2Client::new(1, "secret");
+        let url =
+            oauth.authorization_url(&[Scope::Identify, Scope::Guilds], "https://a.b", Some("xyz"));
+
+        assert_eq!(
+            url,
+            "https://discord.com/api/oauth2/authorize?client_id=1&response_type=code&redirect_uri=https%3A%2F%2Fa.b&scope=identify+guilds&state=xyz"
+        );
+    }
+}
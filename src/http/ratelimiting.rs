@@ -37,6 +37,18 @@
 //! variants) have an associated u64 as data. This is the Id of the parameter,
 //! differentiating between different ratelimits.
 //!
+//! Discord may also share a single bucket between several routes, which it
+//! tells through the `X-RateLimit-Bucket` header of its responses. Once a
+//! route's bucket is known, its ratelimit is tracked by the [`BucketKey`] - the
+//! bucket hash together with the major parameter - so that every route in the
+//! same bucket waits on the same [`Ratelimit`].
+//!
+//! All of this state is kept in a [`RatelimitStore`]. By default, it lives in
+//! the memory of a single [`Ratelimiter`], but a store can also be shared
+//! between several [`Http`] instances or processes using the same token.
+//!
+//! [`Http`]: super::Http
+//!
 //! [Taken from]: https://discord.com/developers/docs/topics/rate-limits#rate-limits
 
 use std::collections::HashMap;
@@ -45,15 +57,18 @@ use std::str::{self, FromStr};
 use std::sync::Arc;
 use std::time::SystemTime;
 
+use async_trait::async_trait;
 use reqwest::header::HeaderMap;
 use reqwest::{Client, Response, StatusCode};
 use tokio::sync::{Mutex, RwLock};
-use tokio::time::{sleep, Duration};
+use tokio::time::{sleep, Duration, Instant};
 use tracing::{debug, instrument};
 
+use super::metrics::HttpMetrics;
 pub use super::routing::Route;
 use super::routing::RouteInfo;
-use super::{HttpError, LightMethod, Request};
+use super::{HttpError, LightMethod, Request, Transport};
+use crate::constants;
 use crate::internal::prelude::*;
 
 /// Passed to the [`Ratelimiter::set_ratelimit_callback`] callback. If using Client, that callback
@@ -73,36 +88,42 @@ pub struct RatelimitInfo {
 /// This keeps track of ratelimit data for known routes through the
 /// [`Ratelimit`] implementation for each route: how many tickets are
 /// [`remaining`] until the user needs to wait for the known [`reset`] time, and
-/// the [`limit`] of requests that can be made within that time.
+/// the [`limit`] of requests that can be made within that time. Routes which
+/// Discord reports to be in the same bucket share their [`Ratelimit`].
 ///
 /// When no tickets are available for some time, then the thread sleeps until
 /// that time passes. The mechanism is known as "pre-emptive ratelimiting".
 ///
 /// Occasionally for very high traffic bots, a global ratelimit may be reached
 /// which blocks all future requests until the global ratelimit is over,
-/// regardless of route. The value of this global ratelimit is never given
-/// through the API, so it can't be pre-emptively ratelimited. This only affects
-/// the largest of bots.
+/// regardless of route. To avoid this, no more than [`GLOBAL_RATELIMIT`]
+/// requests are sent per second, except to interaction endpoints which are
+/// exempt from the global ratelimit. Bots which were granted a higher limit can
+/// change this via [`Self::set_global_limit`].
 ///
+/// The ratelimit state is kept in a [`RatelimitStore`], see
+/// [`Self::set_store`].
+///
+/// [`GLOBAL_RATELIMIT`]: constants::GLOBAL_RATELIMIT
 /// [`limit`]: Ratelimit::limit
 /// [`remaining`]: Ratelimit::remaining
 /// [`reset`]: Ratelimit::reset
 pub struct Ratelimiter {
     client: Client,
-    global: Arc<Mutex<()>>,
-    // When futures is implemented, make tasks clear out their respective entry
-    // when the 'reset' passes.
-    routes: Arc<RwLock<HashMap<Route, Arc<Mutex<Ratelimit>>>>>,
+    transport: Arc<dyn Transport>,
+    store: Arc<dyn RatelimitStore>,
+    in_memory: Arc<InMemoryRatelimitStore>,
+    global_limit: Option<u32>,
     token: String,
     ratelimit_callback: Box<dyn Fn(RatelimitInfo) + Send + Sync>,
+    metrics: Arc<HttpMetrics>,
 }
 
 impl fmt::Debug for Ratelimiter {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Ratelimiter")
             .field("client", &self.client)
-            .field("global", &self.global)
-            .field("routes", &self.routes)
+            .field("global_limit", &self.global_limit)
             .finish()
     }
 }
@@ -118,12 +139,17 @@ impl Ratelimiter {
     }
 
     fn _new(client: Client, token: String) -> Self {
+        let in_memory = Arc::new(InMemoryRatelimitStore::default());
+
         Self {
+            transport: Arc::new(client.clone()),
             client,
-            global: Arc::default(),
-            routes: Arc::default(),
+            store: Arc::clone(&in_memory) as Arc<dyn RatelimitStore>,
+            in_memory,
+            global_limit: Some(constants::GLOBAL_RATELIMIT),
             token,
             ratelimit_callback: Box::new(|_| {}),
+            metrics: Arc::default(),
         }
     }
 
@@ -135,12 +161,88 @@ impl Ratelimiter {
         self.ratelimit_callback = ratelimit_callback;
     }
 
+    /// Sets the [`HttpMetrics`] that responses and ratelimit waits are
+    /// recorded to.
+    pub(crate) fn set_metrics(&mut self, metrics: Arc<HttpMetrics>) {
+        self.metrics = metrics;
+    }
+
+    /// Sets the number of requests per second that may be sent across all
+    /// routes, defaulting to [`GLOBAL_RATELIMIT`]. Passing [`None`] disables
+    /// the client-side global ratelimit, leaving only the one enforced by
+    /// Discord.
+    ///
+    /// This should only be raised if Discord granted the bot a higher global
+    /// ratelimit.
+    ///
+    /// [`GLOBAL_RATELIMIT`]: constants::GLOBAL_RATELIMIT
+    pub fn set_global_limit(&mut self, limit: Option<u32>) {
+        self.global_limit = limit.map(|limit| limit.max(1));
+    }
+
+    /// Sets the [`Transport`] requests are executed with. By default, the
+    /// [`reqwest::Client`] the ratelimiter was created with is used.
+    ///
+    /// The client is still used to build requests and to download
+    /// attachments given by URL.
+    pub fn set_transport(&mut self, transport: Arc<dyn Transport>) {
+        self.transport = transport;
+    }
+
+    /// Sets the [`RatelimitStore`] the ratelimit state is kept in. By default,
+    /// an [`InMemoryRatelimitStore`] owned by this ratelimiter is used.
+    ///
+    /// Ratelimiters sharing a store respect each other's ratelimits, so that
+    /// several [`Http`] instances or processes using the same token don't
+    /// race each other into 429s.
+    ///
+    /// [`Http`]: super::Http
+    pub fn set_store(&mut self, store: Arc<dyn RatelimitStore>) {
+        self.store = store;
+    }
+
+    /// The store which holds the ratelimit information of every route and
+    /// bucket, as well as the global ratelimit.
+    ///
+    /// See the documentation for [`Ratelimit`] for more information on how the
+    /// library handles ratelimiting.
+    ///
+    /// # Examples
+    ///
+    /// View the `reset` time of the route for `ChannelsId(7)`:
+    ///
+    /// ```rust,no_run
+    /// use serenity::http::ratelimiting::Route;
+    /// use serenity::http::LightMethod;
+    /// # use serenity::http::Http;
+    ///
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// #     let http = Http::new("token");
+    /// let key = http.ratelimiter.key(LightMethod::Get, Route::ChannelsId(7)).await?;
+    ///
+    /// if let Some(ratelimit) = http.ratelimiter.store().get(&key).await? {
+    ///     if let Some(reset) = ratelimit.reset() {
+    ///         println!("Reset time at: {:?}", reset);
+    ///     }
+    /// }
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn store(&self) -> Arc<dyn RatelimitStore> {
+        Arc::clone(&self.store)
+    }
+
     /// The routes mutex is a HashMap of each [`Route`] and their respective
     /// ratelimit information.
     ///
     /// See the documentation for [`Ratelimit`] for more information on how the
     /// library handles ratelimiting.
     ///
+    /// This is the state of the default [`InMemoryRatelimitStore`], so it
+    /// stays empty once another store is set via [`Self::set_store`]. Use
+    /// [`Self::store`] to inspect any store.
+    ///
     /// # Examples
     ///
     /// View the `reset` time of the route for `ChannelsId(7)`:
@@ -162,24 +264,178 @@ impl Ratelimiter {
     /// #     Ok(())
     /// # }
     /// ```
+    ///
+    /// Once the bucket of a route is known, its entry points to the same
+    /// [`Ratelimit`] as the one in [`Self::buckets`].
     #[must_use]
     pub fn routes(&self) -> Arc<RwLock<HashMap<Route, Arc<Mutex<Ratelimit>>>>> {
-        Arc::clone(&self.routes)
+        self.in_memory.routes()
+    }
+
+    /// The buckets mutex is a HashMap of each [`BucketKey`] Discord has
+    /// reported and their respective ratelimit information.
+    ///
+    /// Every route in the same bucket and with the same major parameter shares
+    /// a single [`Ratelimit`].
+    ///
+    /// Like [`Self::routes`], this is the state of the default
+    /// [`InMemoryRatelimitStore`].
+    #[must_use]
+    pub fn buckets(&self) -> Arc<RwLock<HashMap<BucketKey, Arc<Mutex<Ratelimit>>>>> {
+        self.in_memory.buckets()
     }
 
+    /// Returns the key the ratelimit of the route is stored under, which is
+    /// the one of its bucket if Discord already reported it.
+    ///
     /// # Errors
     ///
-    /// Only error kind that may be returned is [`Error::Http`].
+    /// Returns any error of the [`RatelimitStore`].
+    pub async fn key(&self, method: LightMethod, route: Route) -> Result<RatelimitKey> {
+        Ok(match self.store.bucket_hash(method, route).await? {
+            Some(hash) => RatelimitKey::Bucket(BucketKey {
+                hash,
+                major_parameter: route.major_parameter(),
+            }),
+            None => RatelimitKey::Route(route),
+        })
+    }
+
+    /// Takes a ticket from the global ratelimit, sleeping until one is
+    /// available.
+    async fn global_pre_hook(&self, route: &RouteInfo<'_>) -> Result<()> {
+        loop {
+            let mut delay = None;
+            let mut limit = 0;
+            let global_limit = self.global_limit;
+
+            self.store
+                .update(&RatelimitKey::Global, &mut |ratelimit| {
+                    delay = ratelimit.take_global_ticket(global_limit, now());
+                    limit = ratelimit.limit;
+                })
+                .await?;
+
+            let delay = match delay {
+                Some(delay) => delay,
+                None => return Ok(()),
+            };
+
+            let (method, route, path) = route.deconstruct();
+
+            debug!("Pre-emptive global ratelimit on route {:?} for {}ms", route, delay.as_millis());
+            (self.ratelimit_callback)(RatelimitInfo {
+                timeout: delay,
+                limit,
+                method,
+                path: path.to_string(),
+                global: true,
+            });
+            self.metrics.record_ratelimit_wait(method, route, delay);
+
+            sleep(delay).await;
+        }
+    }
+
+    /// Takes a ticket from the ratelimit under the key, sleeping until one is
+    /// available.
+    async fn pre_hook(&self, key: &RatelimitKey, route: &RouteInfo<'_>) -> Result<()> {
+        loop {
+            let mut delay = None;
+            let mut limit = 0;
+
+            self.store
+                .update(key, &mut |ratelimit| {
+                    delay = ratelimit.take_ticket(now());
+                    limit = ratelimit.limit;
+                })
+                .await?;
+
+            let delay = match delay {
+                Some(delay) => delay,
+                None => return Ok(()),
+            };
+
+            let (method, route, path) = route.deconstruct();
+
+            debug!("Pre-emptive ratelimit on route {:?} for {}ms", route, delay.as_millis());
+            (self.ratelimit_callback)(RatelimitInfo {
+                timeout: delay,
+                limit,
+                method,
+                path: path.to_string(),
+                global: false,
+            });
+            self.metrics.record_ratelimit_wait(method, route, delay);
+
+            sleep(delay).await;
+        }
+    }
+
+    /// Updates the ratelimit under the key with the headers of the response,
+    /// returning whether the request has to be retried because it got
+    /// ratelimited.
+    async fn post_hook(
+        &self,
+        key: &RatelimitKey,
+        response: &Response,
+        route: &RouteInfo<'_>,
+    ) -> Result<bool> {
+        let headers = response.headers();
+
+        let limit = parse_header(headers, "x-ratelimit-limit")?;
+        let remaining = parse_header(headers, "x-ratelimit-remaining")?;
+        let reset = parse_header::<f64>(headers, "x-ratelimit-reset")?;
+        let reset_after = parse_header::<f64>(headers, "x-ratelimit-reset-after")?;
+        let mut current_limit = 0;
+
+        self.store
+            .update(key, &mut |ratelimit| {
+                ratelimit.update(limit, remaining, reset, reset_after, now());
+                current_limit = ratelimit.limit;
+            })
+            .await?;
+
+        Ok(if response.status() != StatusCode::TOO_MANY_REQUESTS {
+            false
+        } else if let Some(retry_after) = parse_header::<f64>(headers, "retry-after")? {
+            let (method, route, path) = route.deconstruct();
+
+            let timeout = Duration::from_secs_f64(retry_after);
+
+            debug!("Ratelimited on route {:?} for {:?}s", route, retry_after);
+            (self.ratelimit_callback)(RatelimitInfo {
+                timeout,
+                limit: current_limit,
+                method,
+                path: path.to_string(),
+                global: false,
+            });
+            self.metrics.record_ratelimit_wait(method, route, timeout);
+
+            sleep(timeout).await;
+
+            true
+        } else {
+            false
+        })
+    }
+
+    /// # Errors
+    ///
+    /// Only error kind that may be returned is [`Error::Http`], unless the
+    /// [`RatelimitStore`] returns another.
     #[instrument]
     pub async fn perform(&self, req: RatelimitedRequest<'_>) -> Result<Response> {
         let RatelimitedRequest {
             mut req,
         } = req;
 
-        loop {
-            // This will block if another thread hit the global ratelimit.
-            drop(self.global.lock().await);
+        // A request uploading a streamed attachment can not be sent again, so
+        // the 429 response is returned once the ratelimit is over instead.
+        let replayable = req.is_replayable();
 
+        loop {
             // Destructure the tuple instead of retrieving the third value to
             // take advantage of the type system. If `RouteInfo::deconstruct`
             // returns a different number of tuple elements in the future,
@@ -194,19 +450,42 @@ impl Ratelimiter {
 
             // Perform pre-checking here:
             //
-            // - get the route's relevant rate
+            // - take a ticket from the global rate, sleeping if there are none
+            //   left - interaction and webhook endpoints, which interaction
+            //   followups are routed through, are not bound to it;
+            // - get the route's relevant rate, which is shared by its bucket
+            //   if it's known;
             // - sleep if that route's already rate-limited until the end of the
             //   'reset' time;
-            // - get the global rate;
-            // - sleep if there is 0 remaining
             // - then, perform the request
-            let bucket = Arc::clone(self.routes.write().await.entry(route).or_default());
+            if !matches!(
+                route,
+                Route::InteractionsId(_) | Route::WebhooksApplicationId(_) | Route::WebhooksId(_)
+            ) {
+                self.global_pre_hook(&req.route).await?;
+            }
+
+            let key = self.key(method, route).await?;
 
-            bucket.lock().await.pre_hook(&req.route, &self.ratelimit_callback).await;
+            if route != Route::None {
+                self.pre_hook(&key, &req.route).await?;
+            }
 
             let request = req.build(&self.client, &self.token, None).await?.build()?;
 
-            let response = self.client.execute(request).await?;
+            let start = Instant::now();
+            let response = match self.transport.execute(request).await {
+                Ok(response) => {
+                    self.metrics.record_response(method, route, response.status(), start.elapsed());
+
+                    response
+                },
+                Err(why) => {
+                    self.metrics.record_error(method, route, start.elapsed());
+
+                    return Err(why);
+                },
+            };
 
             // Check if the request got ratelimited by checking for status 429,
             // and if so, sleep for the value of the header 'retry-after' -
@@ -225,22 +504,49 @@ impl Ratelimiter {
                 return Ok(response);
             }
 
-            let redo = if response.headers().get("x-ratelimit-global").is_some() {
-                drop(self.global.lock().await);
+            // Routes which Discord reports to share a bucket share their
+            // ratelimit from here on.
+            let key = match parse_header::<String>(response.headers(), "x-ratelimit-bucket")? {
+                Some(hash) => {
+                    self.store.set_bucket_hash(method, route, hash.clone()).await?;
+
+                    RatelimitKey::Bucket(BucketKey {
+                        hash,
+                        major_parameter: route.major_parameter(),
+                    })
+                },
+                None => key,
+            };
 
+            let redo = if response.headers().get("x-ratelimit-global").is_some() {
                 Ok(
                     if let Some(retry_after) =
                         parse_header::<f64>(response.headers(), "retry-after")?
                     {
+                        let timeout = Duration::from_secs_f64(retry_after);
+
+                        // Block every other request until the global ratelimit
+                        // is over.
+                        self.store
+                            .update(&RatelimitKey::Global, &mut |ratelimit| {
+                                ratelimit.remaining = 0;
+                                ratelimit.reset = Some(now() + timeout);
+                            })
+                            .await?;
+
                         debug!("Ratelimited on route {:?} for {:?}s", route, retry_after);
                         (self.ratelimit_callback)(RatelimitInfo {
-                            timeout: Duration::from_secs_f64(retry_after),
-                            limit: 50,
+                            timeout,
+                            limit: i64::from(
+                                self.global_limit.unwrap_or(constants::GLOBAL_RATELIMIT),
+                            ),
                             method,
                             path,
                             global: true,
                         });
-                        sleep(Duration::from_secs_f64(retry_after)).await;
+                        self.metrics.record_ratelimit_wait(method, route, timeout);
+
+                        sleep(timeout).await;
 
                         true
                     } else {
@@ -248,27 +554,172 @@ impl Ratelimiter {
                     },
                 )
             } else {
-                bucket.lock().await.post_hook(&response, &req.route, &self.ratelimit_callback).await
+                self.post_hook(&key, &response, &req.route).await
             };
 
-            if !redo.unwrap_or(true) {
+            if !redo.unwrap_or(true) || !replayable {
                 return Ok(response);
             }
         }
     }
 }
 
+/// The key a [`Ratelimit`] is stored under in a [`RatelimitStore`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum RatelimitKey {
+    /// The global ratelimit, which all routes except interaction endpoints
+    /// are bound to.
+    Global,
+    /// A route whose bucket Discord has not reported yet.
+    Route(Route),
+    /// A bucket reported by Discord, shared by every route in it.
+    Bucket(BucketKey),
+}
+
+/// The key identifying a ratelimit bucket shared between routes.
+///
+/// Routes in the same bucket are only ratelimited together when their major
+/// parameter is the same as well.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BucketKey {
+    /// The bucket hash, as given by the `X-RateLimit-Bucket` header.
+    pub hash: String,
+    /// The major parameter of the route, see [`Route::major_parameter`].
+    pub major_parameter: Option<u64>,
+}
+
+/// Storage for the ratelimit state of a [`Ratelimiter`].
+///
+/// The state consists of a [`Ratelimit`] per [`RatelimitKey`] and the bucket
+/// hash Discord reported for each route. By default, it is kept in an
+/// [`InMemoryRatelimitStore`]. Backing a store by something shared - such as a
+/// file, a socket, or a database - lets several processes using the same token
+/// respect each other's ratelimits.
+///
+/// # Examples
+///
+/// See [the shared ratelimits example] for a store backed by a file.
+///
+/// [the shared ratelimits example]: https://github.com/serenity-rs/serenity/tree/current/examples/e19_shared_ratelimits
+#[async_trait]
+pub trait RatelimitStore: Send + Sync {
+    /// Applies `f` to the ratelimit stored under the key and stores the
+    /// result. Keys without a stored ratelimit start from
+    /// [`Ratelimit::default`].
+    ///
+    /// This must be atomic: no other update of the same key may happen in
+    /// between reading and storing the ratelimit, as tickets are taken this
+    /// way.
+    async fn update(
+        &self,
+        key: &RatelimitKey,
+        f: &mut (dyn for<'a> FnMut(&'a mut Ratelimit) + Send),
+    ) -> Result<()>;
+
+    /// Returns the ratelimit stored under the key, if any.
+    async fn get(&self, key: &RatelimitKey) -> Result<Option<Ratelimit>>;
+
+    /// Returns the bucket hash Discord reported for the route, if any.
+    async fn bucket_hash(&self, method: LightMethod, route: Route) -> Result<Option<String>>;
+
+    /// Stores the bucket hash Discord reported for the route.
+    async fn set_bucket_hash(&self, method: LightMethod, route: Route, hash: String) -> Result<()>;
+}
+
+/// A [`RatelimitStore`] keeping the state in memory, which is only shared
+/// between the [`Ratelimiter`]s it is given to.
+#[derive(Debug, Default)]
+pub struct InMemoryRatelimitStore {
+    global: Arc<Mutex<Ratelimit>>,
+    // When futures is implemented, make tasks clear out their respective entry
+    // when the 'reset' passes.
+    routes: Arc<RwLock<HashMap<Route, Arc<Mutex<Ratelimit>>>>>,
+    buckets: Arc<RwLock<HashMap<BucketKey, Arc<Mutex<Ratelimit>>>>>,
+    bucket_hashes: RwLock<HashMap<(LightMethod, Route), String>>,
+}
+
+impl InMemoryRatelimitStore {
+    /// The ratelimit of each [`Route`], see [`Ratelimiter::routes`].
+    #[must_use]
+    pub fn routes(&self) -> Arc<RwLock<HashMap<Route, Arc<Mutex<Ratelimit>>>>> {
+        Arc::clone(&self.routes)
+    }
+
+    /// The ratelimit of each bucket, see [`Ratelimiter::buckets`].
+    #[must_use]
+    pub fn buckets(&self) -> Arc<RwLock<HashMap<BucketKey, Arc<Mutex<Ratelimit>>>>> {
+        Arc::clone(&self.buckets)
+    }
+
+    async fn ratelimit(&self, key: &RatelimitKey) -> Arc<Mutex<Ratelimit>> {
+        match key {
+            RatelimitKey::Global => Arc::clone(&self.global),
+            RatelimitKey::Route(route) => {
+                Arc::clone(self.routes.write().await.entry(*route).or_default())
+            },
+            RatelimitKey::Bucket(bucket) => {
+                Arc::clone(self.buckets.write().await.entry(bucket.clone()).or_default())
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl RatelimitStore for InMemoryRatelimitStore {
+    async fn update(
+        &self,
+        key: &RatelimitKey,
+        f: &mut (dyn for<'a> FnMut(&'a mut Ratelimit) + Send),
+    ) -> Result<()> {
+        f(&mut *self.ratelimit(key).await.lock().await);
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &RatelimitKey) -> Result<Option<Ratelimit>> {
+        let ratelimit = match key {
+            RatelimitKey::Global => Some(Arc::clone(&self.global)),
+            RatelimitKey::Route(route) => self.routes.read().await.get(route).cloned(),
+            RatelimitKey::Bucket(bucket) => self.buckets.read().await.get(bucket).cloned(),
+        };
+
+        Ok(match ratelimit {
+            Some(ratelimit) => Some(ratelimit.lock().await.clone()),
+            None => None,
+        })
+    }
+
+    async fn bucket_hash(&self, method: LightMethod, route: Route) -> Result<Option<String>> {
+        Ok(self.bucket_hashes.read().await.get(&(method, route)).cloned())
+    }
+
+    /// Also makes the route share the ratelimit of the bucket. If the bucket
+    /// is not known yet, the route's ratelimit becomes the shared one.
+    async fn set_bucket_hash(&self, method: LightMethod, route: Route, hash: String) -> Result<()> {
+        let key = BucketKey {
+            hash: hash.clone(),
+            major_parameter: route.major_parameter(),
+        };
+
+        self.bucket_hashes.write().await.insert((method, route), hash);
+
+        let mut routes = self.routes.write().await;
+        let current = Arc::clone(routes.entry(route).or_default());
+        let bucket = Arc::clone(self.buckets.write().await.entry(key).or_insert(current));
+        routes.insert(route, bucket);
+
+        Ok(())
+    }
+}
+
 /// A set of data containing information about the ratelimits for a particular
-/// [`Route`], which is stored in [`Http`].
+/// [`Route`] or bucket, which is stored in a [`RatelimitStore`].
 ///
 /// See the [Discord docs] on ratelimits for more information.
 ///
-/// **Note**: You should _not_ mutate any of the fields, as this can help cause
-/// 429s.
-///
-/// [`Http`]: super::Http
 /// [Discord docs]: https://discord.com/developers/docs/topics/rate-limits
-#[derive(Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Ratelimit {
     /// The total number of requests that can be made in a period of time.
     limit: i64,
@@ -281,39 +732,17 @@ pub struct Ratelimit {
 }
 
 impl Ratelimit {
+    /// Takes a ticket, sleeping until one is available.
     #[instrument(skip(ratelimit_callback))]
     pub async fn pre_hook(
         &mut self,
         route: &RouteInfo<'_>,
         ratelimit_callback: &(dyn Fn(RatelimitInfo) + Send + Sync),
     ) {
-        if self.limit() == 0 {
-            return;
-        }
-
-        let reset = if let Some(reset) = self.reset {
-            reset
-        } else {
-            // We're probably in the past.
-            self.remaining = self.limit;
-
-            return;
-        };
-
-        let delay = if let Ok(delay) = reset.duration_since(SystemTime::now()) {
-            delay
-        } else {
-            // if duration is negative (i.e. adequate time has passed since last call to this api)
-            if self.remaining() != 0 {
-                self.remaining -= 1;
-            }
-            return;
-        };
-
-        if self.remaining() == 0 {
+        while let Some(delay) = self.take_ticket(now()) {
             let (method, route, path) = route.deconstruct();
 
-            debug!("Pre-emptive ratelimit on route {:?} for {}ms", route, delay.as_millis(),);
+            debug!("Pre-emptive ratelimit on route {:?} for {}ms", route, delay.as_millis());
             ratelimit_callback(RatelimitInfo {
                 timeout: delay,
                 limit: self.limit,
@@ -323,13 +752,15 @@ impl Ratelimit {
             });
 
             sleep(delay).await;
-
-            return;
         }
-
-        self.remaining -= 1;
     }
 
+    /// Updates the ratelimit with the headers of the response, returning
+    /// whether the request has to be retried because it got ratelimited.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Http`] if a ratelimit header is malformed.
     #[instrument(skip(ratelimit_callback))]
     pub async fn post_hook(
         &mut self,
@@ -337,33 +768,19 @@ impl Ratelimit {
         route: &RouteInfo<'_>,
         ratelimit_callback: &(dyn Fn(RatelimitInfo) + Send + Sync),
     ) -> Result<bool> {
-        if let Some(limit) = parse_header(response.headers(), "x-ratelimit-limit")? {
-            self.limit = limit;
-        }
+        let headers = response.headers();
 
-        if let Some(remaining) = parse_header(response.headers(), "x-ratelimit-remaining")? {
-            self.remaining = remaining;
-        }
-
-        #[cfg(feature = "absolute_ratelimits")]
-        if let Some(reset) = parse_header::<f64>(response.headers(), "x-ratelimit-reset")? {
-            self.reset = Some(std::time::UNIX_EPOCH + Duration::from_secs_f64(reset));
-        }
-
-        if let Some(reset_after) =
-            parse_header::<f64>(response.headers(), "x-ratelimit-reset-after")?
-        {
-            #[cfg(not(feature = "absolute_ratelimits"))]
-            {
-                self.reset = Some(SystemTime::now() + Duration::from_secs_f64(reset_after));
-            }
-
-            self.reset_after = Some(Duration::from_secs_f64(reset_after));
-        }
+        self.update(
+            parse_header(headers, "x-ratelimit-limit")?,
+            parse_header(headers, "x-ratelimit-remaining")?,
+            parse_header(headers, "x-ratelimit-reset")?,
+            parse_header(headers, "x-ratelimit-reset-after")?,
+            now(),
+        );
 
         Ok(if response.status() != StatusCode::TOO_MANY_REQUESTS {
             false
-        } else if let Some(retry_after) = parse_header::<f64>(response.headers(), "retry-after")? {
+        } else if let Some(retry_after) = parse_header::<f64>(headers, "retry-after")? {
             let (method, route, path) = route.deconstruct();
 
             debug!("Ratelimited on route {:?} for {:?}s", route, retry_after);
@@ -383,6 +800,99 @@ impl Ratelimit {
         })
     }
 
+    /// Takes a ticket, returning how long to wait for the reset if there are
+    /// none left.
+    fn take_ticket(&mut self, now: SystemTime) -> Option<Duration> {
+        if self.limit() == 0 {
+            return None;
+        }
+
+        let reset = if let Some(reset) = self.reset {
+            reset
+        } else {
+            // We're probably in the past.
+            self.remaining = self.limit;
+
+            return None;
+        };
+
+        match reset.duration_since(now) {
+            Ok(delay) if !delay.is_zero() => {
+                if self.remaining() <= 0 {
+                    return Some(delay);
+                }
+            },
+            _ => {
+                // The interval is over, so assume it started over with every
+                // ticket until a response tells otherwise. Waiters which wake up
+                // at the reset keep taking tickets from this one.
+                self.remaining = self.limit;
+                self.reset = self.reset_after.map(|reset_after| now + reset_after);
+            },
+        }
+
+        self.remaining -= 1;
+
+        None
+    }
+
+    /// Takes a ticket from the global ratelimit, which allows `limit` requests
+    /// per second, returning how long to wait if there are none left.
+    fn take_global_ticket(&mut self, limit: Option<u32>, now: SystemTime) -> Option<Duration> {
+        let delay = self.reset.and_then(|reset| reset.duration_since(now).ok());
+
+        if let Some(delay) = delay.filter(|delay| !delay.is_zero()) {
+            if self.remaining <= 0 {
+                return Some(delay);
+            }
+
+            self.remaining -= 1;
+
+            return None;
+        }
+
+        // The previous second is over, start a new one.
+        self.limit = limit.map_or(i64::MAX, i64::from);
+        self.remaining = self.limit - 1;
+        self.reset = Some(now + Duration::from_secs(1));
+        self.reset_after = Some(Duration::from_secs(1));
+
+        None
+    }
+
+    /// Updates the ratelimit with the values of the headers of a response.
+    #[cfg_attr(not(feature = "absolute_ratelimits"), allow(unused_variables))]
+    fn update(
+        &mut self,
+        limit: Option<i64>,
+        remaining: Option<i64>,
+        reset: Option<f64>,
+        reset_after: Option<f64>,
+        now: SystemTime,
+    ) {
+        if let Some(limit) = limit {
+            self.limit = limit;
+        }
+
+        if let Some(remaining) = remaining {
+            self.remaining = remaining;
+        }
+
+        #[cfg(feature = "absolute_ratelimits")]
+        if let Some(reset) = reset {
+            self.reset = Some(std::time::UNIX_EPOCH + Duration::from_secs_f64(reset));
+        }
+
+        if let Some(reset_after) = reset_after {
+            #[cfg(not(feature = "absolute_ratelimits"))]
+            {
+                self.reset = Some(now + Duration::from_secs_f64(reset_after));
+            }
+
+            self.reset_after = Some(Duration::from_secs_f64(reset_after));
+        }
+    }
+
     /// The total number of requests that can be made in a period of time.
     #[inline]
     #[must_use]
@@ -442,6 +952,22 @@ impl<'a> From<Request<'a>> for RatelimitedRequest<'a> {
     }
 }
 
+/// The current time. Tests follow the clock of tokio instead, so that they can
+/// pause time.
+#[cfg(not(test))]
+fn now() -> SystemTime {
+    SystemTime::now()
+}
+
+#[cfg(test)]
+fn now() -> SystemTime {
+    thread_local! {
+        static START: (SystemTime, Instant) = (SystemTime::now(), Instant::now());
+    }
+
+    START.with(|(system, instant)| *system + instant.elapsed())
+}
+
 fn parse_header<T: FromStr>(headers: &HeaderMap, header: &str) -> Result<Option<T>> {
     let header = match headers.get(header) {
         Some(v) => v,
@@ -460,12 +986,30 @@ fn parse_header<T: FromStr>(headers: &HeaderMap, header: &str) -> Result<Option<
 mod tests {
     use std::error::Error as StdError;
     use std::result::Result as StdResult;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
 
+    use async_trait::async_trait;
+    use futures::future::join_all;
+    use http_crate::response::Builder;
     use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
-
-    use super::parse_header;
+    use reqwest::{Client, Request as ReqwestRequest, Response};
+    use tokio::time::{Duration, Instant};
+
+    use super::{
+        parse_header,
+        BucketKey,
+        InMemoryRatelimitStore,
+        RatelimitKey,
+        RatelimitStore,
+        Ratelimiter,
+        Route,
+    };
     use crate::error::Error;
-    use crate::http::HttpError;
+    use crate::http::multipart::Multipart;
+    use crate::http::request::RequestBuilder;
+    use crate::http::routing::RouteInfo;
+    use crate::http::{AttachmentType, HttpError, LightMethod, Transport};
 
     type Result<T> = StdResult<T, Box<dyn StdError>>;
 
@@ -523,4 +1067,287 @@ mod tests {
         assert!(is_err!("x-bad-num", HttpError::RateLimitI64F64));
         assert!(is_err!("x-bad-unicode", HttpError::RateLimitUtf8));
     }
+
+    /// Answers the first request with a 429 and every later one with a 200.
+    #[derive(Default)]
+    struct RatelimitOnce {
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for RatelimitOnce {
+        async fn execute(&self, _: ReqwestRequest) -> crate::Result<Response> {
+            let response = if self.requests.fetch_add(1, Ordering::SeqCst) == 0 {
+                Builder::new()
+                    .status(429)
+                    .header("x-ratelimit-limit", "1")
+                    .header("x-ratelimit-remaining", "0")
+                    .header("retry-after", "0")
+                    .body("{}")
+            } else {
+                Builder::new().status(200).body("{}")
+            };
+
+            Ok(response.unwrap().into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_perform_retries_through_transport() -> Result<()> {
+        let transport = Arc::new(RatelimitOnce::default());
+
+        let mut ratelimiter = Ratelimiter::new(Client::new(), "Bot token");
+        ratelimiter.set_transport(Arc::clone(&transport) as Arc<dyn Transport>);
+
+        let request = RequestBuilder::new(RouteInfo::GetChannel {
+            channel_id: 1,
+        })
+        .build();
+        let response = ratelimiter.perform(request.into()).await?;
+
+        assert_eq!(response.status(), 200);
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_perform_returns_ratelimit_of_streamed_attachment() -> Result<()> {
+        let transport = Arc::new(RatelimitOnce::default());
+
+        let mut ratelimiter = Ratelimiter::new(Client::new(), "Bot token");
+        ratelimiter.set_transport(Arc::clone(&transport) as Arc<dyn Transport>);
+
+        let chunks = futures::stream::iter(vec![Ok::<_, std::io::Error>(vec![1, 2, 3])]);
+        let mut request = RequestBuilder::new(RouteInfo::CreateMessage {
+            channel_id: 1,
+        });
+        request.multipart(Some(Multipart {
+            files: vec![AttachmentType::from_stream(chunks, 3, "a.bin")],
+            fields: vec![],
+            payload_json: None,
+        }));
+
+        // The stream can't be sent again, so the 429 is returned.
+        let response = ratelimiter.perform(request.build().into()).await?;
+
+        assert_eq!(response.status(), 429);
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 1);
+
+        Ok(())
+    }
+
+    /// Answers every request as part of the same bucket.
+    struct SharedBucket;
+
+    #[async_trait]
+    impl Transport for SharedBucket {
+        async fn execute(&self, _: ReqwestRequest) -> crate::Result<Response> {
+            let response = Builder::new()
+                .status(200)
+                .header("x-ratelimit-bucket", "abcd1234")
+                .header("x-ratelimit-limit", "5")
+                .header("x-ratelimit-remaining", "4")
+                .header("x-ratelimit-reset-after", "1")
+                .body("{}");
+
+            Ok(response.unwrap().into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routes_share_bucket_per_major_parameter() -> Result<()> {
+        let mut ratelimiter = Ratelimiter::new(Client::new(), "Bot token");
+        ratelimiter.set_transport(Arc::new(SharedBucket));
+
+        for route in [
+            RouteInfo::GetChannel {
+                channel_id: 1,
+            },
+            RouteInfo::GetMessage {
+                channel_id: 1,
+                message_id: 2,
+            },
+            RouteInfo::GetChannel {
+                channel_id: 3,
+            },
+        ] {
+            ratelimiter.perform(RequestBuilder::new(route).build().into()).await?;
+        }
+
+        let bucket = |major_parameter| {
+            RatelimitKey::Bucket(BucketKey {
+                hash: "abcd1234".to_string(),
+                major_parameter: Some(major_parameter),
+            })
+        };
+
+        let channel = ratelimiter.key(LightMethod::Get, Route::ChannelsId(1)).await?;
+        let message = Route::ChannelsIdMessagesId(LightMethod::Get, 1);
+        let message = ratelimiter.key(LightMethod::Get, message).await?;
+        let other_channel = ratelimiter.key(LightMethod::Get, Route::ChannelsId(3)).await?;
+
+        assert_eq!(channel, bucket(1));
+        assert_eq!(message, bucket(1));
+        assert_eq!(other_channel, bucket(3));
+        assert_eq!(ratelimiter.store().get(&bucket(1)).await?.unwrap().remaining(), 4);
+
+        let routes = ratelimiter.routes();
+        let routes = routes.read().await;
+        let buckets = ratelimiter.buckets();
+        let buckets = buckets.read().await;
+
+        assert_eq!(buckets.len(), 2);
+        assert!(Arc::ptr_eq(
+            &routes[&Route::ChannelsId(1)],
+            &buckets[&BucketKey {
+                hash: "abcd1234".to_string(),
+                major_parameter: Some(1),
+            }]
+        ));
+
+        Ok(())
+    }
+
+    /// Answers every request with a single ticket per second.
+    #[derive(Default)]
+    struct OnePerSecond {
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for OnePerSecond {
+        async fn execute(&self, _: ReqwestRequest) -> crate::Result<Response> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+
+            let response = Builder::new()
+                .status(200)
+                .header("x-ratelimit-limit", "1")
+                .header("x-ratelimit-remaining", "0")
+                .header("x-ratelimit-reset-after", "1")
+                .body("{}");
+
+            Ok(response.unwrap().into())
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_waiters_take_turns_after_reset() -> Result<()> {
+        let transport = Arc::new(OnePerSecond::default());
+
+        let mut ratelimiter = Ratelimiter::new(Client::new(), "Bot token");
+        ratelimiter.set_transport(Arc::clone(&transport) as Arc<dyn Transport>);
+        ratelimiter.set_global_limit(None);
+
+        let request = || {
+            RequestBuilder::new(RouteInfo::GetChannel {
+                channel_id: 1,
+            })
+            .build()
+        };
+
+        ratelimiter.perform(request().into()).await?;
+
+        let start = Instant::now();
+        let waiters = (0..3).map(|_| ratelimiter.perform(request().into()));
+
+        for response in join_all(waiters).await {
+            response?;
+        }
+
+        // Each waiter takes the single ticket of a later interval instead of
+        // all of them being sent at the first reset.
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 4);
+        assert!(start.elapsed() >= Duration::from_secs(3));
+
+        Ok(())
+    }
+
+    /// Answers every request successfully, counting them.
+    #[derive(Default)]
+    struct Counting {
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for Counting {
+        async fn execute(&self, _: ReqwestRequest) -> crate::Result<Response> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+
+            Ok(Builder::new().status(200).body("{}").unwrap().into())
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_global_limit() -> Result<()> {
+        let transport = Arc::new(Counting::default());
+
+        let mut ratelimiter = Ratelimiter::new(Client::new(), "Bot token");
+        ratelimiter.set_transport(Arc::clone(&transport) as Arc<dyn Transport>);
+        ratelimiter.set_global_limit(Some(2));
+
+        let start = Instant::now();
+
+        for channel_id in 0..3 {
+            let request = RequestBuilder::new(RouteInfo::GetChannel {
+                channel_id,
+            });
+            ratelimiter.perform(request.build().into()).await?;
+        }
+
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 3);
+        assert!(start.elapsed() >= Duration::from_secs(1));
+
+        // Interaction endpoints, followups included, are exempt.
+        let start = Instant::now();
+
+        for interaction_id in 0..3 {
+            let request = RequestBuilder::new(RouteInfo::CreateInteractionResponse {
+                interaction_id,
+                interaction_token: "token",
+            });
+            ratelimiter.perform(request.build().into()).await?;
+
+            let request = RequestBuilder::new(RouteInfo::CreateFollowupMessage {
+                application_id: 1,
+                interaction_token: "token",
+            });
+            ratelimiter.perform(request.build().into()).await?;
+        }
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_shared_store() -> Result<()> {
+        let store = Arc::new(InMemoryRatelimitStore::default());
+        let transport = Arc::new(Counting::default());
+
+        let mut first = Ratelimiter::new(Client::new(), "Bot token");
+        first.set_transport(Arc::clone(&transport) as Arc<dyn Transport>);
+        first.set_store(Arc::clone(&store) as Arc<dyn RatelimitStore>);
+        first.set_global_limit(Some(2));
+
+        let mut second = Ratelimiter::new(Client::new(), "Bot token");
+        second.set_transport(Arc::clone(&transport) as Arc<dyn Transport>);
+        second.set_store(Arc::clone(&store) as Arc<dyn RatelimitStore>);
+        second.set_global_limit(Some(2));
+
+        let start = Instant::now();
+
+        for ratelimiter in [&first, &second, &first] {
+            let request = RequestBuilder::new(RouteInfo::GetChannel {
+                channel_id: 1,
+            });
+            ratelimiter.perform(request.build().into()).await?;
+        }
+
+        // The third request waits on the global ratelimit shared by both.
+        assert!(start.elapsed() >= Duration::from_secs(1));
+        assert!(store.get(&RatelimitKey::Global).await?.is_some());
+
+        Ok(())
+    }
 }
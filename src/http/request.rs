@@ -13,7 +13,7 @@ use tracing::instrument;
 
 use super::multipart::Multipart;
 use super::routing::RouteInfo;
-use super::HttpError;
+use super::{AttachmentType, HttpError};
 use crate::constants;
 use crate::internal::prelude::*;
 
@@ -120,8 +120,13 @@ impl<'a> Request<'a> {
 
         let mut headers = Headers::with_capacity(4);
         headers.insert(USER_AGENT, HeaderValue::from_static(constants::USER_AGENT));
-        headers
-            .insert(AUTHORIZATION, HeaderValue::from_str(token).map_err(HttpError::InvalidHeader)?);
+
+        if !token.is_empty() {
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(token).map_err(HttpError::InvalidHeader)?,
+            );
+        }
 
         // Discord will return a 400: Bad Request response if we set the content type header,
         // but don't give a body.
@@ -147,6 +152,14 @@ impl<'a> Request<'a> {
         Ok(builder.headers(headers))
     }
 
+    /// Whether the request can be sent again, which it can't if it uploads a
+    /// streamed attachment, as the stream can only be read once.
+    pub(crate) fn is_replayable(&self) -> bool {
+        self.multipart.as_ref().map_or(true, |multipart| {
+            !multipart.files.iter().any(|file| matches!(file, AttachmentType::Stream { .. }))
+        })
+    }
+
     #[must_use]
     pub fn body_ref(&self) -> &Option<&'a [u8]> {
         &self.body
@@ -0,0 +1,300 @@
+//! Retrying requests which failed for transient reasons.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use reqwest::{Response, StatusCode};
+use tokio::time::Duration;
+
+use super::{HttpError, LightMethod};
+use crate::internal::prelude::*;
+
+/// A policy deciding which failed requests are retried, and how long to wait
+/// in between attempts.
+///
+/// Requests are retried when the connection failed or timed out, or when
+/// Discord responded with one of the [`Self::status_codes`]. Ratelimited
+/// requests are already retried by the [`Ratelimiter`], and are not subject to
+/// this policy.
+///
+/// The wait before each retry grows exponentially from the
+/// [`Self::base_delay`], up to the [`Self::max_delay`].
+///
+/// By default, only requests with an idempotent method - `GET`, `PUT` and
+/// `DELETE` - are retried. Retrying a `POST` or `PATCH` request may repeat its
+/// effects, such as sending a message twice, and has to be opted into via
+/// [`Self::methods`].
+///
+/// # Examples
+///
+/// Retry up to 5 times, including when sending messages:
+///
+/// ```rust
+/// use serenity::http::{HttpBuilder, LightMethod, RetryPolicy};
+///
+/// let policy = RetryPolicy::new().max_attempts(5).methods(vec![
+///     LightMethod::Get,
+///     LightMethod::Put,
+///     LightMethod::Delete,
+///     LightMethod::Post,
+/// ]);
+///
+/// let http = HttpBuilder::new("token").retry_policy(policy).build();
+/// ```
+///
+/// [`Ratelimiter`]: super::ratelimiting::Ratelimiter
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    methods: Vec<LightMethod>,
+    status_codes: Vec<StatusCode>,
+}
+
+impl RetryPolicy {
+    /// Creates a policy which makes up to 3 attempts, waiting 500ms before the
+    /// first retry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+            methods: vec![LightMethod::Get, LightMethod::Put, LightMethod::Delete],
+            status_codes: vec![
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+        }
+    }
+
+    /// Sets the maximum number of attempts, including the first one.
+    #[must_use]
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+
+        self
+    }
+
+    /// Sets the wait before the first retry, which doubles with every further
+    /// retry.
+    #[must_use]
+    pub fn base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+
+        self
+    }
+
+    /// Sets the longest wait in between two attempts.
+    #[must_use]
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+
+        self
+    }
+
+    /// Sets whether the waits are randomised, to avoid many failed requests
+    /// being retried all at once. If enabled, each wait lies between half of
+    /// and the full exponential backoff.
+    #[must_use]
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+
+        self
+    }
+
+    /// Sets the methods of requests which may be retried.
+    #[must_use]
+    pub fn methods(mut self, methods: Vec<LightMethod>) -> Self {
+        self.methods = methods;
+
+        self
+    }
+
+    /// Sets the response status codes on which requests are retried.
+    #[must_use]
+    pub fn status_codes(mut self, status_codes: Vec<StatusCode>) -> Self {
+        self.status_codes = status_codes;
+
+        self
+    }
+
+    /// Whether a request with the method may be retried after its
+    /// `attempt`th attempt, depending on the result.
+    pub(crate) fn may_retry(&self, method: LightMethod, attempt: u32) -> bool {
+        attempt < self.max_attempts && self.methods.contains(&method)
+    }
+
+    /// Whether a request with the method should be retried after its
+    /// `attempt`th attempt resulted in `result`.
+    pub(crate) fn should_retry(
+        &self,
+        method: LightMethod,
+        attempt: u32,
+        result: &Result<Response>,
+    ) -> bool {
+        if !self.may_retry(method, attempt) {
+            return false;
+        }
+
+        match result {
+            Err(Error::Http(error)) => match &**error {
+                HttpError::UnsuccessfulRequest(response) => {
+                    self.status_codes.contains(&response.status_code)
+                },
+                HttpError::Request(error) => {
+                    error.is_connect() || error.is_timeout() || error.is_request()
+                },
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// The wait before the retry following the `attempt`th attempt.
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2_u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+
+        if self.jitter {
+            // Only used for spreading out retries, so the randomly seeded
+            // std hasher is good enough.
+            let random = RandomState::new().build_hasher().finish();
+            let fraction = (random % 1000) as u32;
+
+            delay / 2 + delay / 2 * fraction / 1000
+        } else {
+            delay
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use http_crate::response::Builder;
+    use reqwest::{Request as ReqwestRequest, Response};
+    use tokio::time::Duration;
+
+    use super::RetryPolicy;
+    use crate::http::multipart::Multipart;
+    use crate::http::request::RequestBuilder;
+    use crate::http::routing::RouteInfo;
+    use crate::http::{AttachmentType, HttpBuilder, HttpError, LightMethod, Transport};
+    use crate::Error;
+
+    /// Answers the first request with a 503 and every later one with a 200.
+    #[derive(Default)]
+    struct UnavailableOnce {
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for Arc<UnavailableOnce> {
+        async fn execute(&self, _: ReqwestRequest) -> crate::Result<Response> {
+            let status = if self.requests.fetch_add(1, Ordering::SeqCst) == 0 { 503 } else { 200 };
+
+            Ok(Builder::new().status(status).body("{}").unwrap().into())
+        }
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::new().base_delay(Duration::from_millis(1)).jitter(false)
+    }
+
+    #[tokio::test]
+    async fn test_retries_idempotent_request() {
+        let transport = Arc::new(UnavailableOnce::default());
+        let http = HttpBuilder::new("token")
+            .transport(Arc::clone(&transport))
+            .retry_policy(policy())
+            .build();
+
+        let request = RequestBuilder::new(RouteInfo::GetChannel {
+            channel_id: 1,
+        });
+
+        assert!(http.request(request.build()).await.is_ok());
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_post_is_opt_in() {
+        let transport = Arc::new(UnavailableOnce::default());
+        let http = HttpBuilder::new("token")
+            .transport(Arc::clone(&transport))
+            .retry_policy(policy())
+            .build();
+
+        let request = RequestBuilder::new(RouteInfo::CreateMessage {
+            channel_id: 1,
+        });
+
+        assert!(http.request(request.build()).await.is_err());
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_streamed_attachment_is_not_retried() {
+        let transport = Arc::new(UnavailableOnce::default());
+        let http = HttpBuilder::new("token")
+            .transport(Arc::clone(&transport))
+            .retry_policy(policy().methods(vec![LightMethod::Post]))
+            .build();
+
+        let chunks = futures::stream::iter(vec![Ok::<_, std::io::Error>(vec![1, 2, 3])]);
+        let mut request = RequestBuilder::new(RouteInfo::CreateMessage {
+            channel_id: 1,
+        });
+        request.multipart(Some(Multipart {
+            files: vec![AttachmentType::from_stream(chunks, 3, "a.bin")],
+            fields: vec![],
+            payload_json: None,
+        }));
+
+        // The original error is returned, instead of the stream having been
+        // consumed by the first attempt.
+        match http.request(request.build()).await {
+            Err(Error::Http(error)) => {
+                assert!(matches!(*error, HttpError::UnsuccessfulRequest(_)));
+            },
+            other => panic!("expected the request to fail, got {:?}", other),
+        }
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy::new().max_delay(Duration::from_secs(1)).jitter(false);
+
+        assert_eq!(policy.delay(1), Duration::from_millis(500));
+        assert_eq!(policy.delay(2), Duration::from_secs(1));
+        assert_eq!(policy.delay(10), Duration::from_secs(1));
+
+        let delay = policy.jitter(true).delay(1);
+        assert!(delay >= Duration::from_millis(250) && delay <= Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_may_retry() {
+        let policy = RetryPolicy::new();
+
+        assert!(policy.may_retry(LightMethod::Get, 1));
+        assert!(policy.may_retry(LightMethod::Get, 2));
+        assert!(!policy.may_retry(LightMethod::Get, 3));
+        assert!(!policy.may_retry(LightMethod::Post, 1));
+    }
+}
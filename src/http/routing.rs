@@ -3,6 +3,7 @@ use std::fmt::{Display, Write};
 
 use super::LightMethod;
 use crate::constants;
+use crate::model::Timestamp;
 
 /// A representation of all routes registered within the library. These are safe
 /// and memory-efficient representations of each path that functions exist for
@@ -391,6 +392,11 @@ pub enum Route {
     UsersMeGuilds,
     /// Route for the `/users/@me/guilds/:guild_id` path.
     UsersMeGuildsId,
+    /// Route for the `/users/@me/guilds/:guild_id/member` path.
+    UsersMeGuildsIdMember,
+    /// Route for the `/users/@me/applications/:application_id/role-connection`
+    /// path.
+    UsersMeApplicationsIdRoleConnection,
     /// Route for the `/voice/regions` path.
     VoiceRegions,
     /// Route for the `/webhooks/:webhook_id` path.
@@ -449,6 +455,17 @@ pub enum Route {
     ///
     /// [`ApplicationId`]: crate::model::id::ApplicationId
     ApplicationsIdGuildsIdCommandsId(u64),
+    /// Route for the `/applications/:application_id/role-connections/metadata`
+    /// path.
+    ///
+    /// The data is the relevant [`ApplicationId`].
+    ///
+    /// [`ApplicationId`]: crate::model::id::ApplicationId
+    ApplicationsIdRoleConnectionsMetadata(u64),
+    /// Route for the `/oauth2/token` path.
+    OAuth2Token,
+    /// Route for the `/oauth2/token/revoke` path.
+    OAuth2TokenRevoke,
     /// Route for the `/stage-instances` path.
     ///
     /// The data is the relevant [`ChannelId`].
@@ -470,6 +487,79 @@ pub enum Route {
 }
 
 impl Route {
+    /// The major parameter of the route, if it has one.
+    ///
+    /// Discord tracks ratelimits per major parameter, so two routes sharing a
+    /// bucket only share their ratelimit if this is the same for both. The
+    /// major parameters are channel, guild, and webhook Ids.
+    #[must_use]
+    pub fn major_parameter(&self) -> Option<u64> {
+        match *self {
+            Self::ChannelsId(id)
+            | Self::ChannelsIdInvites(id)
+            | Self::ChannelsIdMessages(id)
+            | Self::ChannelsIdMessagesBulkDelete(id)
+            | Self::ChannelsIdMessagesId(_, id)
+            | Self::ChannelsIdMessagesIdAck(id)
+            | Self::ChannelsIdMessagesIdReactions(id)
+            | Self::ChannelsIdMessagesIdReactionsUserIdType(id)
+            | Self::ChannelsIdPermissionsOverwriteId(id)
+            | Self::ChannelsIdPins(id)
+            | Self::ChannelsIdPinsMessageId(id)
+            | Self::ChannelsIdCrosspostsMessageId(id)
+            | Self::ChannelsIdTyping(id)
+            | Self::ChannelsIdWebhooks(id)
+            | Self::ChannelsIdMessagesIdThreads(id)
+            | Self::ChannelsIdThreads(id)
+            | Self::ChannelsIdThreadMembersMe(id)
+            | Self::ChannelsIdThreadMembersUserId(id)
+            | Self::ChannelsIdThreadMembers(id)
+            | Self::ChannelsIdArchivedPublicThreads(id)
+            | Self::ChannelsIdArchivedPrivateThreads(id)
+            | Self::ChannelsIdMeJoindedArchivedPrivateThreads(id)
+            | Self::FollowNewsChannel(id)
+            | Self::GuildsId(id)
+            | Self::GuildsIdAutoModRules(id)
+            | Self::GuildsIdAutoModRulesId(id)
+            | Self::GuildsIdBans(id)
+            | Self::GuildsIdAuditLogs(id)
+            | Self::GuildsIdBansUserId(id)
+            | Self::GuildsIdChannels(id)
+            | Self::GuildsIdWidget(id)
+            | Self::GuildsIdPreview(id)
+            | Self::GuildsIdEmojis(id)
+            | Self::GuildsIdEmojisId(id)
+            | Self::GuildsIdIntegrations(id)
+            | Self::GuildsIdIntegrationsId(id)
+            | Self::GuildsIdIntegrationsIdSync(id)
+            | Self::GuildsIdInvites(id)
+            | Self::GuildsIdMembers(id)
+            | Self::GuildsIdMembersId(id)
+            | Self::GuildsIdMembersIdRolesId(id)
+            | Self::GuildsIdMembersMe(id)
+            | Self::GuildsIdMembersMeNick(id)
+            | Self::GuildsIdMembersSearch(id)
+            | Self::GuildsIdPrune(id)
+            | Self::GuildsIdRegions(id)
+            | Self::GuildsIdRoles(id)
+            | Self::GuildsIdRolesId(id)
+            | Self::GuildsIdScheduledEvents(id)
+            | Self::GuildsIdScheduledEventsId(id)
+            | Self::GuildsIdScheduledEventsIdUsers(id)
+            | Self::GuildsIdStickers(id)
+            | Self::GuildsIdStickersId(id)
+            | Self::GuildsIdVanityUrl(id)
+            | Self::GuildsIdVoiceStates(id)
+            | Self::GuildsIdVoiceStatesMe(id)
+            | Self::GuildsIdWebhooks(id)
+            | Self::GuildsIdWelcomeScreen(id)
+            | Self::WebhooksId(id)
+            | Self::WebhooksIdMessagesId(id)
+            | Self::WebhooksApplicationId(id) => Some(id),
+            _ => None,
+        }
+    }
+
     #[must_use]
     pub fn channel(channel_id: u64) -> String {
         api!("/channels/{}", channel_id)
@@ -621,7 +711,7 @@ impl Route {
         before: Option<u64>,
         limit: Option<u64>,
     ) -> String {
-        let mut s = api!("/channels/{}/threads/archived/public", channel_id);
+        let mut s = api!("/channels/{}/threads/archived/public?", channel_id);
 
         if let Some(id) = before {
             write!(s, "&before={}", id).unwrap();
@@ -634,13 +724,32 @@ impl Route {
         s
     }
 
+    #[must_use]
+    pub fn channel_archived_public_threads_before(
+        channel_id: u64,
+        before: Option<Timestamp>,
+        limit: Option<u64>,
+    ) -> String {
+        let mut s = api!("/channels/{}/threads/archived/public?", channel_id);
+
+        if let Some(timestamp) = before {
+            write!(s, "&before={}", timestamp).unwrap();
+        }
+
+        if let Some(limit) = limit {
+            write!(s, "&limit={}", limit).unwrap();
+        }
+
+        s
+    }
+
     #[must_use]
     pub fn channel_archived_private_threads(
         channel_id: u64,
         before: Option<u64>,
         limit: Option<u64>,
     ) -> String {
-        let mut s = api!("/channels/{}/threads/archived/private", channel_id);
+        let mut s = api!("/channels/{}/threads/archived/private?", channel_id);
 
         if let Some(id) = before {
             write!(s, "&before={}", id).unwrap();
@@ -653,13 +762,32 @@ impl Route {
         s
     }
 
+    #[must_use]
+    pub fn channel_archived_private_threads_before(
+        channel_id: u64,
+        before: Option<Timestamp>,
+        limit: Option<u64>,
+    ) -> String {
+        let mut s = api!("/channels/{}/threads/archived/private?", channel_id);
+
+        if let Some(timestamp) = before {
+            write!(s, "&before={}", timestamp).unwrap();
+        }
+
+        if let Some(limit) = limit {
+            write!(s, "&limit={}", limit).unwrap();
+        }
+
+        s
+    }
+
     #[must_use]
     pub fn channel_joined_private_threads(
         channel_id: u64,
         before: Option<u64>,
         limit: Option<u64>,
     ) -> String {
-        let mut s = api!("/channels/{}/users/@me/threads/archived/private", channel_id);
+        let mut s = api!("/channels/{}/users/@me/threads/archived/private?", channel_id);
 
         if let Some(id) = before {
             write!(s, "&before={}", id).unwrap();
@@ -751,6 +879,30 @@ impl Route {
         api!("/guilds/{}/bans", guild_id)
     }
 
+    #[must_use]
+    pub fn guild_bans_optioned(
+        guild_id: u64,
+        after: Option<u64>,
+        before: Option<u64>,
+        limit: Option<u64>,
+    ) -> String {
+        let mut s = api!("/guilds/{}/bans?", guild_id);
+
+        if let Some(after) = after {
+            write!(s, "&after={}", after).unwrap();
+        }
+
+        if let Some(before) = before {
+            write!(s, "&before={}", before).unwrap();
+        }
+
+        if let Some(limit) = limit {
+            write!(s, "&limit={}", limit).unwrap();
+        }
+
+        s
+    }
+
     #[must_use]
     pub fn guild_channels(guild_id: u64) -> String {
         api!("/guilds/{}/channels", guild_id)
@@ -1035,6 +1187,16 @@ impl Route {
         api!("/users/{}/guilds/{}", target, guild_id)
     }
 
+    #[must_use]
+    pub fn user_guild_member<D: Display>(target: D, guild_id: u64) -> String {
+        api!("/users/{}/guilds/{}/member", target, guild_id)
+    }
+
+    #[must_use]
+    pub fn user_application_role_connection<D: Display>(target: D, application_id: u64) -> String {
+        api!("/users/{}/applications/{}/role-connection", target, application_id)
+    }
+
     #[must_use]
     pub fn user_guilds<D: Display>(target: D) -> String {
         api!("/users/{}/guilds", target)
@@ -1190,6 +1352,21 @@ impl Route {
         api!("/applications/{}/guilds/{}/commands/permissions", application_id, guild_id)
     }
 
+    #[must_use]
+    pub fn application_role_connections_metadata(application_id: u64) -> String {
+        api!("/applications/{}/role-connections/metadata", application_id)
+    }
+
+    #[must_use]
+    pub fn oauth2_token() -> &'static str {
+        api!("/oauth2/token")
+    }
+
+    #[must_use]
+    pub fn oauth2_token_revoke() -> &'static str {
+        api!("/oauth2/token/revoke")
+    }
+
     #[must_use]
     pub fn stage_instances() -> &'static str {
         api!("/stage-instances")
@@ -1538,6 +1715,12 @@ pub enum RouteInfo<'a> {
     GetBans {
         guild_id: u64,
     },
+    GetBansPaginated {
+        guild_id: u64,
+        after: Option<u64>,
+        before: Option<u64>,
+        limit: Option<u64>,
+    },
     GetBotGateway,
     GetChannel {
         channel_id: u64,
@@ -1562,11 +1745,21 @@ pub enum RouteInfo<'a> {
         before: Option<u64>,
         limit: Option<u64>,
     },
+    GetChannelArchivedPublicThreadsBefore {
+        channel_id: u64,
+        before: Option<Timestamp>,
+        limit: Option<u64>,
+    },
     GetChannelArchivedPrivateThreads {
         channel_id: u64,
         before: Option<u64>,
         limit: Option<u64>,
     },
+    GetChannelArchivedPrivateThreadsBefore {
+        channel_id: u64,
+        before: Option<Timestamp>,
+        limit: Option<u64>,
+    },
     GetChannelJoinedPrivateArchivedThreads {
         channel_id: u64,
         before: Option<u64>,
@@ -1727,6 +1920,23 @@ pub enum RouteInfo<'a> {
     },
     GetUserConnections,
     GetUserDmChannels,
+    GetCurrentUserGuildMember {
+        guild_id: u64,
+    },
+    GetApplicationRoleConnectionMetadata {
+        application_id: u64,
+    },
+    EditApplicationRoleConnectionMetadata {
+        application_id: u64,
+    },
+    GetUserApplicationRoleConnection {
+        application_id: u64,
+    },
+    EditUserApplicationRoleConnection {
+        application_id: u64,
+    },
+    ExchangeOAuth2Token,
+    RevokeOAuth2Token,
     GetVoiceRegions,
     GetWebhook {
         webhook_id: u64,
@@ -2471,6 +2681,16 @@ impl<'a> RouteInfo<'a> {
                 Route::GuildsIdBans(guild_id),
                 Cow::from(Route::guild_bans(guild_id)),
             ),
+            RouteInfo::GetBansPaginated {
+                guild_id,
+                after,
+                before,
+                limit,
+            } => (
+                LightMethod::Get,
+                Route::GuildsIdBans(guild_id),
+                Cow::from(Route::guild_bans_optioned(guild_id, after, before, limit)),
+            ),
             RouteInfo::GetBotGateway => {
                 (LightMethod::Get, Route::GatewayBot, Cow::from(Route::gateway_bot()))
             },
@@ -2525,6 +2745,15 @@ impl<'a> RouteInfo<'a> {
                 Route::ChannelsIdArchivedPublicThreads(channel_id),
                 Cow::from(Route::channel_archived_public_threads(channel_id, before, limit)),
             ),
+            RouteInfo::GetChannelArchivedPublicThreadsBefore {
+                channel_id,
+                before,
+                limit,
+            } => (
+                LightMethod::Get,
+                Route::ChannelsIdArchivedPublicThreads(channel_id),
+                Cow::from(Route::channel_archived_public_threads_before(channel_id, before, limit)),
+            ),
             RouteInfo::GetChannelArchivedPrivateThreads {
                 channel_id,
                 before,
@@ -2534,6 +2763,17 @@ impl<'a> RouteInfo<'a> {
                 Route::ChannelsIdArchivedPrivateThreads(channel_id),
                 Cow::from(Route::channel_archived_private_threads(channel_id, before, limit)),
             ),
+            RouteInfo::GetChannelArchivedPrivateThreadsBefore {
+                channel_id,
+                before,
+                limit,
+            } => (
+                LightMethod::Get,
+                Route::ChannelsIdArchivedPrivateThreads(channel_id),
+                Cow::from(Route::channel_archived_private_threads_before(
+                    channel_id, before, limit,
+                )),
+            ),
             RouteInfo::GetChannelJoinedPrivateArchivedThreads {
                 channel_id,
                 before,
@@ -2899,6 +3139,49 @@ impl<'a> RouteInfo<'a> {
                 Route::UsersMeChannels,
                 Cow::from(Route::user_dm_channels("@me")),
             ),
+            RouteInfo::GetCurrentUserGuildMember {
+                guild_id,
+            } => (
+                LightMethod::Get,
+                Route::UsersMeGuildsIdMember,
+                Cow::from(Route::user_guild_member("@me", guild_id)),
+            ),
+            RouteInfo::GetApplicationRoleConnectionMetadata {
+                application_id,
+            } => (
+                LightMethod::Get,
+                Route::ApplicationsIdRoleConnectionsMetadata(application_id),
+                Cow::from(Route::application_role_connections_metadata(application_id)),
+            ),
+            RouteInfo::EditApplicationRoleConnectionMetadata {
+                application_id,
+            } => (
+                LightMethod::Put,
+                Route::ApplicationsIdRoleConnectionsMetadata(application_id),
+                Cow::from(Route::application_role_connections_metadata(application_id)),
+            ),
+            RouteInfo::GetUserApplicationRoleConnection {
+                application_id,
+            } => (
+                LightMethod::Get,
+                Route::UsersMeApplicationsIdRoleConnection,
+                Cow::from(Route::user_application_role_connection("@me", application_id)),
+            ),
+            RouteInfo::EditUserApplicationRoleConnection {
+                application_id,
+            } => (
+                LightMethod::Put,
+                Route::UsersMeApplicationsIdRoleConnection,
+                Cow::from(Route::user_application_role_connection("@me", application_id)),
+            ),
+            RouteInfo::ExchangeOAuth2Token => {
+                (LightMethod::Post, Route::OAuth2Token, Cow::from(Route::oauth2_token()))
+            },
+            RouteInfo::RevokeOAuth2Token => (
+                LightMethod::Post,
+                Route::OAuth2TokenRevoke,
+                Cow::from(Route::oauth2_token_revoke()),
+            ),
             RouteInfo::GetVoiceRegions => {
                 (LightMethod::Get, Route::VoiceRegions, Cow::from(Route::voice_regions()))
             },
@@ -0,0 +1,62 @@
+//! The transport that built requests are sent over.
+//!
+//! By default, [`Http`] and [`Ratelimiter`] execute their requests with the
+//! [`reqwest::Client`] they were created with. Providing a custom [`Transport`]
+//! through [`HttpBuilder::transport`] replaces the part that touches the
+//! network, while request building and ratelimiting stay the same. This is
+//! mostly useful for testing, where an in-memory fake can return canned Discord
+//! responses, including 429s and ratelimit headers.
+//!
+//! [`Http`]: super::Http
+//! [`HttpBuilder::transport`]: super::HttpBuilder::transport
+//! [`Ratelimiter`]: super::ratelimiting::Ratelimiter
+
+use async_trait::async_trait;
+use reqwest::{Client, Request, Response};
+
+use crate::internal::prelude::*;
+
+/// Executes a fully built HTTP request and returns the raw response.
+///
+/// This is implemented for [`reqwest::Client`], which is the transport used
+/// unless another is provided.
+///
+/// # Examples
+///
+/// A fake transport which answers every request with an empty JSON object:
+///
+/// ```rust,no_run
+/// use serenity::http::{HttpBuilder, Transport};
+/// use serenity::{async_trait, Result};
+/// # use http_crate as http;
+///
+/// struct Fake;
+///
+/// #[async_trait]
+/// impl Transport for Fake {
+///     async fn execute(&self, _request: reqwest::Request) -> Result<reqwest::Response> {
+///         let response = http::Response::builder().status(200).body("{}").unwrap();
+///
+///         Ok(response.into())
+///     }
+/// }
+///
+/// let http = HttpBuilder::new("token").transport(Fake).build();
+/// ```
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// Sends the request, returning the response regardless of its status
+    /// code.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Http`] if the request could not be sent.
+    async fn execute(&self, request: Request) -> Result<Response>;
+}
+
+#[async_trait]
+impl Transport for Client {
+    async fn execute(&self, request: Request) -> Result<Response> {
+        Client::execute(self, request).await.map_err(From::from)
+    }
+}
@@ -1,30 +1,83 @@
-use std::io::Read;
+use std::io::{Error as IoError, ErrorKind as IoErrorKind};
 
 use async_trait::async_trait;
 use async_tungstenite::tungstenite::Message;
-use flate2::read::ZlibDecoder;
 use futures::{SinkExt, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
 use tokio::time::timeout;
 use tracing::{instrument, warn};
 use url::Url;
 
-use crate::gateway::{GatewayError, WsStream};
+use crate::gateway::{etf, Decompressor, GatewayError, TransportEncoding, WsStream};
 use crate::internal::prelude::*;
-use crate::json::{from_str, to_string};
+use crate::json::{from_str, from_value, to_string};
+
+/// A payload received from the gateway.
+#[derive(Debug)]
+pub(crate) enum Payload {
+    /// A JSON payload, parsed into a [`Value`].
+    Json(Value),
+    /// An ETF payload, which is deserialized straight from its bytes.
+    Etf(Vec<u8>),
+}
+
+impl Payload {
+    /// Deserializes the payload, such as into a [`GatewayEvent`].
+    ///
+    /// [`GatewayEvent`]: crate::model::event::GatewayEvent
+    pub(crate) fn deserialize<T: DeserializeOwned>(self) -> Result<T> {
+        match self {
+            Self::Json(value) => from_value(value),
+            Self::Etf(bytes) => Ok(etf::from_slice(&bytes)?),
+        }
+    }
+
+    /// Reads the type and sequence number of a dispatch, without
+    /// deserializing its data.
+    pub(crate) fn dispatch_header(&self) -> DispatchHeader {
+        match self {
+            Self::Json(value) => DispatchHeader {
+                t: value.get("t").and_then(Value::as_str).map(String::from),
+                s: value.get("s").and_then(Value::as_u64),
+            },
+            // A malformed payload fails to deserialize later on.
+            Self::Etf(bytes) => etf::from_slice(bytes).unwrap_or_default(),
+        }
+    }
+}
+
+/// The type and sequence number of a payload, which are only present if it is
+/// a dispatch.
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct DispatchHeader {
+    pub t: Option<String>,
+    pub s: Option<u64>,
+}
 
 #[async_trait]
 pub trait ReceiverExt {
-    async fn recv_json(&mut self) -> Result<Option<Value>>;
+    async fn recv_json(
+        &mut self,
+        decompressor: &mut Decompressor,
+        encoding: TransportEncoding,
+    ) -> Result<Option<Payload>>;
 }
 
 #[async_trait]
 pub trait SenderExt {
     async fn send_json(&mut self, value: &Value) -> Result<()>;
+
+    async fn send_etf(&mut self, value: &Value) -> Result<()>;
 }
 
 #[async_trait]
 impl ReceiverExt for WsStream {
-    async fn recv_json(&mut self) -> Result<Option<Value>> {
+    async fn recv_json(
+        &mut self,
+        decompressor: &mut Decompressor,
+        encoding: TransportEncoding,
+    ) -> Result<Option<Payload>> {
         const TIMEOUT: tokio::time::Duration = tokio::time::Duration::from_millis(500);
 
         let ws_message = match timeout(TIMEOUT, self.next()).await {
@@ -33,7 +86,7 @@ impl ReceiverExt for WsStream {
             Ok(None) | Err(_) => None,
         };
 
-        convert_ws_message(ws_message)
+        convert_ws_message(ws_message, decompressor, encoding)
     }
 }
 
@@ -42,33 +95,47 @@ impl SenderExt for WsStream {
     async fn send_json(&mut self, value: &Value) -> Result<()> {
         Ok(to_string(value).map(Message::Text).map_err(Error::from).map(|m| self.send(m))?.await?)
     }
+
+    async fn send_etf(&mut self, value: &Value) -> Result<()> {
+        Ok(etf::to_vec(value)
+            .map(Message::Binary)
+            .map_err(Error::from)
+            .map(|m| self.send(m))?
+            .await?)
+    }
 }
 
 #[inline]
-pub(crate) fn convert_ws_message(message: Option<Message>) -> Result<Option<Value>> {
-    const DECOMPRESSION_MULTIPLIER: usize = 3;
-
+pub(crate) fn convert_ws_message(
+    message: Option<Message>,
+    decompressor: &mut Decompressor,
+    encoding: TransportEncoding,
+) -> Result<Option<Payload>> {
     Ok(match message {
         Some(Message::Binary(bytes)) => {
-            let mut decompressed = String::with_capacity(bytes.len() * DECOMPRESSION_MULTIPLIER);
+            let decompressed = match decompressor.decompress(&bytes) {
+                Ok(Some(decompressed)) => decompressed,
+                Ok(None) => return Ok(None),
+                Err(why) => {
+                    warn!("Err decompressing bytes: {:?}; bytes: {:?}", why, bytes);
 
-            ZlibDecoder::new(&bytes[..]).read_to_string(&mut decompressed).map_err(|why| {
-                warn!("Err decompressing bytes: {:?}; bytes: {:?}", why, bytes);
-
-                why
-            })?;
+                    return Err(why);
+                },
+            };
 
-            from_str(decompressed.as_mut_str()).map(Some).map_err(|why| {
+            decode(decompressed, encoding).map(Some).map_err(|why| {
                 warn!("Err deserializing bytes: {:?}; bytes: {:?}", why, bytes);
 
                 why
             })?
         },
-        Some(Message::Text(mut payload)) => from_str(&mut payload).map(Some).map_err(|why| {
-            warn!("Err deserializing text: {:?}; text: {}", why, payload,);
+        Some(Message::Text(mut payload)) => {
+            from_str(&mut payload).map(|value| Some(Payload::Json(value))).map_err(|why| {
+                warn!("Err deserializing text: {:?}; text: {}", why, payload,);
 
-            why
-        })?,
+                why
+            })?
+        },
         Some(Message::Close(Some(frame))) => {
             return Err(Error::Gateway(GatewayError::Closed(Some(frame))));
         },
@@ -77,6 +144,22 @@ pub(crate) fn convert_ws_message(message: Option<Message>) -> Result<Option<Valu
     })
 }
 
+/// Decodes a decompressed binary message.
+///
+/// ETF payloads are kept as bytes, to be deserialized without an intermediate
+/// [`Value`].
+fn decode(bytes: Vec<u8>, encoding: TransportEncoding) -> Result<Payload> {
+    match encoding {
+        TransportEncoding::Json => {
+            let mut payload = String::from_utf8(bytes)
+                .map_err(|why| IoError::new(IoErrorKind::InvalidData, why))?;
+
+            from_str(payload.as_mut_str()).map(Payload::Json)
+        },
+        TransportEncoding::Etf => Ok(Payload::Etf(bytes)),
+    }
+}
+
 #[instrument]
 pub(crate) async fn create_client(url: Url) -> Result<WsStream> {
     let config = async_tungstenite::tungstenite::protocol::WebSocketConfig {
@@ -90,3 +173,52 @@ pub(crate) async fn create_client(url: Url) -> Result<WsStream> {
 
     Ok(stream)
 }
+
+#[cfg(test)]
+mod tests {
+    use async_tungstenite::tungstenite::Message;
+
+    use super::{convert_ws_message, Payload};
+    use crate::gateway::{Decompressor, TransportCompression, TransportEncoding};
+    use crate::model::event::{Event, GatewayEvent};
+
+    const HELLO: &[u8] = include_bytes!("../gateway/fixtures/hello.etf");
+    const READY: &[u8] = include_bytes!("../gateway/fixtures/ready.etf");
+
+    fn convert(message: Message, encoding: TransportEncoding) -> Payload {
+        let mut decompressor = Decompressor::new(TransportCompression::None).unwrap();
+
+        convert_ws_message(Some(message), &mut decompressor, encoding).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_etf_payload() {
+        let payload = convert(Message::Binary(READY.to_vec()), TransportEncoding::Etf);
+        assert!(matches!(payload, Payload::Etf(_)));
+
+        let header = payload.dispatch_header();
+        assert_eq!(header.t.as_deref(), Some("READY"));
+        assert_eq!(header.s, Some(1));
+
+        assert!(matches!(
+            payload.deserialize().unwrap(),
+            GatewayEvent::Dispatch(1, Event::Ready(_))
+        ));
+
+        let header =
+            convert(Message::Binary(HELLO.to_vec()), TransportEncoding::Etf).dispatch_header();
+        assert_eq!(header.t, None);
+        assert_eq!(header.s, None);
+    }
+
+    #[test]
+    fn test_json_payload() {
+        let text = r#"{"op":0,"s":3,"t":"TYPING_START","d":{}}"#.to_string();
+        let payload = convert(Message::Text(text), TransportEncoding::Etf);
+        assert!(matches!(payload, Payload::Json(_)));
+
+        let header = payload.dispatch_header();
+        assert_eq!(header.t.as_deref(), Some("TYPING_START"));
+        assert_eq!(header.s, Some(3));
+    }
+}
@@ -5,7 +5,7 @@
 use std::collections::HashMap;
 use std::hash::{BuildHasher, Hash};
 
-#[cfg(feature = "gateway")]
+#[cfg(any(feature = "gateway", feature = "http_cassettes"))]
 use serde::de::Deserialize;
 use serde::de::DeserializeOwned;
 use serde::ser::Serialize;
@@ -61,7 +61,7 @@ where
     Ok(simd_json::to_string(v)?)
 }
 
-#[cfg(all(feature = "gateway", not(feature = "simd-json")))]
+#[cfg(all(any(feature = "gateway", feature = "http_cassettes"), not(feature = "simd-json")))]
 pub(crate) fn from_str<'a, T>(s: &'a mut str) -> Result<T>
 where
     T: Deserialize<'a>,
@@ -69,7 +69,7 @@ where
     Ok(serde_json::from_str(s)?)
 }
 
-#[cfg(all(feature = "gateway", feature = "simd-json"))]
+#[cfg(all(any(feature = "gateway", feature = "http_cassettes"), feature = "simd-json"))]
 pub(crate) fn from_str<'a, T>(s: &'a mut str) -> Result<T>
 where
     T: Deserialize<'a>,
@@ -4,6 +4,7 @@ pub mod command;
 pub mod component;
 pub mod interaction;
 pub mod oauth;
+pub mod role_connection;
 
 use self::oauth::Scope;
 use super::id::{snowflake, ApplicationId, GuildId, SkuId, UserId};
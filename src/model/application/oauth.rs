@@ -1,7 +1,11 @@
 use std::fmt;
 
+use serde::de::value::{Error as DeError, StrDeserializer};
+use serde::de::IntoDeserializer;
 use serde::{Deserialize, Serialize};
 
+use crate::model::webhook::Webhook;
+
 /// The available OAuth2 Scopes.
 ///
 /// [Discord docs](https://discord.com/developers/docs/topics/oauth2#shared-resources-oauth2-scopes).
@@ -89,6 +93,9 @@ pub enum Scope {
     /// Allows your app to connect to voice on user's behalf and see all the voice members - requires Discord approval.
     #[serde(rename = "voice")]
     Voice,
+    /// Allows your app to update a user's connection and metadata for the app.
+    #[serde(rename = "role_connections.write")]
+    RoleConnectionsWrite,
 }
 
 impl fmt::Display for Scope {
@@ -96,3 +103,40 @@ impl fmt::Display for Scope {
         self.serialize(f)
     }
 }
+
+/// The response to exchanging an authorization code or refreshing a token.
+///
+/// [Discord docs](https://discord.com/developers/docs/topics/oauth2#authorization-code-grant-access-token-response).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct AccessToken {
+    /// The token to authorize requests on behalf of the user with.
+    pub access_token: String,
+    /// The type of the token, which is always `Bearer`.
+    pub token_type: String,
+    /// The number of seconds until the token expires.
+    pub expires_in: u64,
+    /// The token to get a new access token with once this one expired.
+    ///
+    /// This is [`None`] for tokens of the client credentials grant.
+    pub refresh_token: Option<String>,
+    /// The space separated scopes the token was granted.
+    pub scope: String,
+    /// The webhook created via the [`Scope::WebhookIncomming`] scope.
+    pub webhook: Option<Webhook>,
+}
+
+impl AccessToken {
+    /// The scopes the token was granted. Scopes unknown to the library are
+    /// skipped.
+    #[must_use]
+    pub fn scopes(&self) -> Vec<Scope> {
+        self.scope
+            .split_whitespace()
+            .filter_map(|scope| {
+                let deserializer: StrDeserializer<'_, DeError> = scope.into_deserializer();
+                Scope::deserialize(deserializer).ok()
+            })
+            .collect()
+    }
+}
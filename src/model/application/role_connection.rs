@@ -0,0 +1,87 @@
+//! Models for linked roles, which are granted based on metadata an
+//! application attaches to a user's connection.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// A metadata field which linked roles of an application can require a
+/// minimum, maximum or matching value of.
+///
+/// [Discord docs](https://discord.com/developers/docs/resources/application-role-connection-metadata#application-role-connection-metadata-object-application-role-connection-metadata-structure).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct RoleConnectionMetadata {
+    /// The type of the metadata value.
+    #[serde(rename = "type")]
+    pub kind: RoleConnectionMetadataType,
+    /// The key of the metadata field, which may only contain `a-z`, `0-9` and
+    /// `_`.
+    pub key: String,
+    /// The name of the metadata field.
+    pub name: String,
+    /// Localizations of the name, with locale as the key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_localizations: Option<HashMap<String, String>>,
+    /// The description of the metadata field.
+    pub description: String,
+    /// Localizations of the description, with locale as the key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description_localizations: Option<HashMap<String, String>>,
+}
+
+/// The type of a [`RoleConnectionMetadata`] value, and how it is compared
+/// against the value a guild requires.
+///
+/// [Discord docs](https://discord.com/developers/docs/resources/application-role-connection-metadata#application-role-connection-metadata-object-application-role-connection-metadata-type).
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, PartialOrd, Ord)]
+#[non_exhaustive]
+#[repr(u8)]
+pub enum RoleConnectionMetadataType {
+    /// The user's integer is less than or equal to the guild's.
+    IntegerLessThanOrEqual = 1,
+    /// The user's integer is greater than or equal to the guild's.
+    IntegerGreaterThanOrEqual = 2,
+    /// The user's integer is equal to the guild's.
+    IntegerEqual = 3,
+    /// The user's integer is not equal to the guild's.
+    IntegerNotEqual = 4,
+    /// The user's ISO8601 date is less than or equal to the guild's number of
+    /// days before now.
+    DatetimeLessThanOrEqual = 5,
+    /// The user's ISO8601 date is greater than or equal to the guild's number
+    /// of days before now.
+    DatetimeGreaterThanOrEqual = 6,
+    /// The user's boolean is equal to the guild's.
+    BooleanEqual = 7,
+    /// The user's boolean is not equal to the guild's.
+    BooleanNotEqual = 8,
+    Unknown = !0,
+}
+
+enum_number!(RoleConnectionMetadataType {
+    IntegerLessThanOrEqual,
+    IntegerGreaterThanOrEqual,
+    IntegerEqual,
+    IntegerNotEqual,
+    DatetimeLessThanOrEqual,
+    DatetimeGreaterThanOrEqual,
+    BooleanEqual,
+    BooleanNotEqual
+});
+
+/// The connection an application has attached to a user, which decides the
+/// linked roles they are eligible for.
+///
+/// [Discord docs](https://discord.com/developers/docs/resources/user#application-role-connection-object-application-role-connection-structure).
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct RoleConnection {
+    /// The vanity name of the platform the application is for.
+    pub platform_name: Option<String>,
+    /// The user's username on the platform.
+    pub platform_username: Option<String>,
+    /// The user's metadata values, keyed by [`RoleConnectionMetadata::key`].
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
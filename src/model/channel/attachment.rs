@@ -1,10 +1,19 @@
 #[cfg(feature = "model")]
-use reqwest::Client as ReqwestClient;
+use std::path::Path;
+
+#[cfg(feature = "model")]
+use reqwest::{Client as ReqwestClient, Response, Url};
+#[cfg(feature = "model")]
+use tokio::fs::{self, File};
+#[cfg(feature = "model")]
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 
 #[cfg(feature = "model")]
 use crate::internal::prelude::*;
 use crate::model::id::AttachmentId;
 use crate::model::utils::is_false;
+#[cfg(feature = "model")]
+use crate::model::ModelError;
 
 /// A file uploaded with a message. Not to be confused with [`Embed`]s.
 ///
@@ -136,4 +145,320 @@ impl Attachment {
         let bytes = reqwest.get(&self.url).send().await?.bytes().await?;
         Ok(bytes.to_vec())
     }
+
+    /// Downloads the attachment into the writer as it is received, without
+    /// holding it in memory, returning the number of bytes written.
+    ///
+    /// The download is checked against the [`DownloadOptions`] before and
+    /// while it is received.
+    ///
+    /// # Examples
+    ///
+    /// Archive image attachments of up to 100 MB:
+    ///
+    /// ```rust,no_run
+    /// # use serenity::model::channel::Attachment;
+    /// #
+    /// # async fn run(attachment: Attachment) -> Result<(), Box<dyn std::error::Error>> {
+    /// use serenity::model::channel::DownloadOptions;
+    /// use tokio::fs::File;
+    ///
+    /// let options = DownloadOptions::new().max_size(100 * 1024 * 1024).content_type("image/");
+    ///
+    /// let mut file = File::create(&attachment.filename).await?;
+    /// attachment.download_to(&mut file, &options).await?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ModelError::AttachmentTooLarge`] if the attachment exceeds
+    /// the [`DownloadOptions::max_size`], and a
+    /// [`ModelError::InvalidContentType`] if its content type is not allowed.
+    ///
+    /// Returns an [`Error::Http`] when there is a problem retrieving the
+    /// attachment, and an [`Error::Io`] when writing to the writer fails.
+    ///
+    /// **Note**: If the download is aborted, the data received up to that point
+    /// has already been written.
+    pub async fn download_to<W>(&self, writer: &mut W, options: &DownloadOptions) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        if options.width.is_none() && options.height.is_none() {
+            options.check_size(self.size)?;
+        }
+
+        if self.content_type.is_some() {
+            options.check_content_type(self.content_type.as_deref())?;
+        }
+
+        let response = ReqwestClient::new().get(options.url(self)?).send().await?;
+        let response = response.error_for_status()?;
+
+        if self.content_type.is_none() {
+            let content_type = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok());
+
+            options.check_content_type(content_type)?;
+        }
+
+        write_response(response, writer, options.max_size).await
+    }
+
+    /// Downloads the attachment into the file at the given path as it is
+    /// received, returning the number of bytes written. If the download
+    /// fails, the file is removed again.
+    ///
+    /// See [`Self::download_to`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Self::download_to`], as well as an
+    /// [`Error::Io`] if the file can not be created.
+    pub async fn download_to_file(
+        &self,
+        path: impl AsRef<Path>,
+        options: &DownloadOptions,
+    ) -> Result<u64> {
+        let path = path.as_ref();
+        let mut file = File::create(path).await?;
+
+        let result = self.download_to(&mut file, options).await;
+
+        if result.is_err() {
+            drop(file);
+            fs::remove_file(path).await.ok();
+        }
+
+        result
+    }
+}
+
+/// Writes the body of the response into the writer, aborting once more than
+/// `max_size` bytes were received.
+#[cfg(feature = "model")]
+async fn write_response<W>(
+    mut response: Response,
+    writer: &mut W,
+    max_size: Option<u64>,
+) -> Result<u64>
+where
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let max_size = max_size.unwrap_or(u64::MAX);
+
+    if let Some(length) = response.content_length() {
+        if length > max_size {
+            return Err(Error::Model(ModelError::AttachmentTooLarge(length)));
+        }
+    }
+
+    let mut written = 0;
+
+    while let Some(chunk) = response.chunk().await? {
+        written += chunk.len() as u64;
+
+        if written > max_size {
+            return Err(Error::Model(ModelError::AttachmentTooLarge(written)));
+        }
+
+        writer.write_all(&chunk).await?;
+    }
+
+    writer.flush().await?;
+
+    Ok(written)
+}
+
+/// Options for downloading an [`Attachment`] via [`Attachment::download_to`]
+/// or [`Attachment::download_to_file`].
+///
+/// By default, any attachment is downloaded from its [`Attachment::url`].
+#[cfg(feature = "model")]
+#[derive(Clone, Debug, Default)]
+#[must_use]
+pub struct DownloadOptions {
+    max_size: Option<u64>,
+    content_types: Vec<String>,
+    proxy: bool,
+    width: Option<u64>,
+    height: Option<u64>,
+}
+
+#[cfg(feature = "model")]
+impl DownloadOptions {
+    /// Creates options which allow any attachment.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of bytes to download. Attachments which are
+    /// known to be larger are not downloaded at all, and any other download
+    /// is aborted once it exceeds the size.
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+
+        self
+    }
+
+    /// Allows attachments of the given content type, such as `image/png`. A
+    /// content type ending in `/`, such as `image/`, allows all of its
+    /// subtypes.
+    ///
+    /// If no content type is allowed, attachments of any content type are
+    /// downloaded. Otherwise, attachments whose content type is unknown are
+    /// not downloaded.
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_types.push(content_type.into());
+
+        self
+    }
+
+    /// Sets whether to download via the [`Attachment::proxy_url`] instead of
+    /// the [`Attachment::url`].
+    pub fn proxy(mut self, proxy: bool) -> Self {
+        self.proxy = proxy;
+
+        self
+    }
+
+    /// Requests an image attachment resized to the given width and height,
+    /// each optional. Resized images are always downloaded via the
+    /// [`Attachment::proxy_url`].
+    ///
+    /// As the size of a resized image is not known in advance, only the
+    /// received bytes are checked against the [`Self::max_size`].
+    pub fn resize(mut self, width: Option<u64>, height: Option<u64>) -> Self {
+        self.width = width;
+        self.height = height;
+
+        self
+    }
+
+    fn check_size(&self, size: u64) -> Result<()> {
+        match self.max_size {
+            Some(max_size) if size > max_size => {
+                Err(Error::Model(ModelError::AttachmentTooLarge(size)))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn check_content_type(&self, content_type: Option<&str>) -> Result<()> {
+        if self.content_types.is_empty() {
+            return Ok(());
+        }
+
+        let allowed = content_type.map_or(false, |content_type| {
+            let essence = content_type.split(';').next().unwrap_or_default().trim();
+
+            self.content_types.iter().any(|allowed| {
+                if allowed.ends_with('/') {
+                    essence.len() > allowed.len()
+                        && essence
+                            .get(..allowed.len())
+                            .map_or(false, |prefix| prefix.eq_ignore_ascii_case(allowed))
+                } else {
+                    essence.eq_ignore_ascii_case(allowed)
+                }
+            })
+        });
+
+        if allowed {
+            Ok(())
+        } else {
+            Err(Error::Model(ModelError::InvalidContentType(content_type.map(ToString::to_string))))
+        }
+    }
+
+    fn url(&self, attachment: &Attachment) -> Result<Url> {
+        let resize = self.width.is_some() || self.height.is_some();
+        let url = if self.proxy || resize { &attachment.proxy_url } else { &attachment.url };
+
+        let mut url = Url::parse(url).map_err(|_| Error::Url(url.clone()))?;
+
+        if resize {
+            let mut query = url.query_pairs_mut();
+
+            if let Some(width) = self.width {
+                query.append_pair("width", &width.to_string());
+            }
+
+            if let Some(height) = self.height {
+                query.append_pair("height", &height.to_string());
+            }
+        }
+
+        Ok(url)
+    }
+}
+
+#[cfg(all(test, feature = "model"))]
+mod tests {
+    use http_crate::response::Builder;
+
+    use super::{write_response, Attachment, DownloadOptions};
+    use crate::model::ModelError;
+    use crate::Error;
+
+    fn attachment() -> Attachment {
+        Attachment {
+            id: 1.into(),
+            filename: "cat.png".to_string(),
+            height: Some(100),
+            proxy_url: "https://media.discordapp.net/attachments/1/2/cat.png".to_string(),
+            size: 2048,
+            url: "https://cdn.discordapp.com/attachments/1/2/cat.png".to_string(),
+            width: Some(100),
+            content_type: Some("image/png".to_string()),
+            ephemeral: false,
+        }
+    }
+
+    #[test]
+    fn test_url() {
+        let attachment = attachment();
+
+        assert_eq!(DownloadOptions::new().url(&attachment).unwrap().as_str(), attachment.url);
+        assert_eq!(
+            DownloadOptions::new().resize(Some(64), None).url(&attachment).unwrap().as_str(),
+            "https://media.discordapp.net/attachments/1/2/cat.png?width=64"
+        );
+    }
+
+    #[test]
+    fn test_checks() {
+        let options = DownloadOptions::new().max_size(1024).content_type("image/");
+
+        assert!(options.check_content_type(Some("image/png")).is_ok());
+        assert!(options.check_content_type(Some("IMAGE/gif; charset=x")).is_ok());
+        assert!(options.check_content_type(Some("video/mp4")).is_err());
+        assert!(options.check_content_type(Some("imag\u{20ac}/png")).is_err());
+        assert!(options.check_content_type(None).is_err());
+        assert!(DownloadOptions::new().check_content_type(None).is_ok());
+
+        assert!(matches!(
+            options.check_size(attachment().size),
+            Err(Error::Model(ModelError::AttachmentTooLarge(2048)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_write_response_is_capped() {
+        let response = || Builder::new().status(200).body(vec![1; 2048]).unwrap().into();
+
+        let mut buf = Vec::new();
+        assert_eq!(write_response(response(), &mut buf, Some(4096)).await.unwrap(), 2048);
+        assert_eq!(buf.len(), 2048);
+
+        let mut buf = Vec::new();
+        assert!(matches!(
+            write_response(response(), &mut buf, Some(1024)).await,
+            Err(Error::Model(ModelError::AttachmentTooLarge(_)))
+        ));
+    }
 }
@@ -1,16 +1,41 @@
 use std::borrow::Cow;
+#[cfg(feature = "http")]
+use std::fmt;
 #[cfg(not(feature = "http"))]
 use std::fs::File;
+#[cfg(feature = "http")]
+use std::io::Result as IoResult;
 use std::path::{Path, PathBuf};
+#[cfg(feature = "http")]
+use std::pin::Pin;
+#[cfg(feature = "http")]
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "http")]
+use std::task::{Context, Poll};
 
 #[cfg(feature = "http")]
-use reqwest::Client;
+use bytes::Bytes;
 #[cfg(feature = "http")]
-use tokio::{fs::File, io::AsyncReadExt};
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+#[cfg(feature = "http")]
+use reqwest::{Body, Client};
+#[cfg(feature = "http")]
+use tokio::fs::File;
+#[cfg(feature = "http")]
+use tokio::io::{AsyncRead, AsyncReadExt};
 use url::Url;
 
 #[cfg(feature = "http")]
 use crate::error::{Error, Result};
+#[cfg(feature = "http")]
+use crate::http::HttpError;
+
+/// The size of the chunks an [`AsyncRead`] is read in when streaming it.
+#[cfg(feature = "http")]
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+#[cfg(feature = "http")]
+type ByteStream = Pin<Box<dyn Stream<Item = IoResult<Bytes>> + Send>>;
 
 /// Enum that allows a user to pass a [`Path`] or a [`File`] type to [`send_files`]
 ///
@@ -26,6 +51,157 @@ pub enum AttachmentType<'a> {
     Path(&'a Path),
     /// Indicates that the [`AttachmentType`] is an image URL.
     Image(Url),
+    /// Indicates that the [`AttachmentType`] is streamed while uploading,
+    /// instead of being loaded into memory first. See
+    /// [`AttachmentType::from_reader`] and [`AttachmentType::from_stream`].
+    ///
+    /// **Note**: The stream can only be read once, so a request uploading it
+    /// is never retried. If Discord ratelimits the request, the 429 response
+    /// is returned as an error once the ratelimit is over, and if it fails,
+    /// the error is returned even if a [`RetryPolicy`] would retry it.
+    ///
+    /// [`RetryPolicy`]: crate::http::RetryPolicy
+    #[cfg(feature = "http")]
+    Stream { stream: AttachmentStream, filename: String },
+}
+
+/// A stream of bytes with a known length, which an attachment is uploaded from
+/// without buffering it in memory.
+///
+/// The stream can only be read once, and clones share it. A request uploading
+/// it is therefore not retried, neither by the [`Ratelimiter`] after a 429
+/// response nor according to a [`RetryPolicy`], and the response or error is
+/// returned instead. Uploading the stream again fails with
+/// [`HttpError::AttachmentStreamConsumed`].
+///
+/// [`Ratelimiter`]: crate::http::ratelimiting::Ratelimiter
+/// [`RetryPolicy`]: crate::http::RetryPolicy
+#[cfg(feature = "http")]
+#[derive(Clone)]
+pub struct AttachmentStream {
+    stream: Arc<Mutex<Option<ByteStream>>>,
+    length: u64,
+}
+
+#[cfg(feature = "http")]
+impl AttachmentStream {
+    fn new(stream: ByteStream, length: u64) -> Self {
+        Self {
+            stream: Arc::new(Mutex::new(Some(stream))),
+            length,
+        }
+    }
+
+    /// The number of bytes in the stream.
+    #[must_use]
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    /// Takes the stream out, failing if it was already taken.
+    fn take(&self) -> Result<ByteStream> {
+        let stream = self.stream.lock().expect("attachment stream lock poisoned").take();
+
+        stream.ok_or_else(|| Error::Http(Box::new(HttpError::AttachmentStreamConsumed)))
+    }
+
+    /// Takes the stream out as a request body.
+    pub(crate) fn body(&self) -> Result<Body> {
+        Ok(Body::wrap_stream(SyncStream(Mutex::new(self.take()?))))
+    }
+}
+
+/// Makes a stream `Sync`, as required for request bodies, without requiring
+/// it of the stream itself. The stream is only polled through a mutable
+/// reference, so the mutex is never locked.
+#[cfg(feature = "http")]
+struct SyncStream(Mutex<ByteStream>);
+
+#[cfg(feature = "http")]
+impl Stream for SyncStream {
+    type Item = IoResult<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let stream = self.get_mut().0.get_mut().expect("attachment stream lock poisoned");
+
+        stream.as_mut().poll_next(cx)
+    }
+}
+
+#[cfg(feature = "http")]
+impl fmt::Debug for AttachmentStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AttachmentStream").field("length", &self.length).finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "http")]
+impl AttachmentType<'static> {
+    /// Creates an attachment which is read from the reader while uploading,
+    /// so that it is never fully loaded into memory.
+    ///
+    /// The `length` must be the exact number of bytes the reader yields, as
+    /// Discord requires the size of every part of the upload up front.
+    ///
+    /// # Examples
+    ///
+    /// Upload a large file:
+    ///
+    /// ```rust,no_run
+    /// # use serenity::http::Http;
+    /// # use serenity::model::id::ChannelId;
+    /// #
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// #     let http = Http::new("token");
+    /// use serenity::model::channel::AttachmentType;
+    /// use tokio::fs::File;
+    ///
+    /// let file = File::open("video.mp4").await?;
+    /// let length = file.metadata().await?.len();
+    /// let attachment = AttachmentType::from_reader(file, length, "video.mp4");
+    ///
+    /// ChannelId(7).send_files(&http, vec![attachment], |m| m.content("a video")).await?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn from_reader<R>(reader: R, length: u64, filename: impl Into<String>) -> Self
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        let stream = stream::try_unfold(reader, |mut reader| async move {
+            let mut buf = vec![0; READ_CHUNK_SIZE];
+            let read = reader.read(&mut buf).await?;
+
+            if read == 0 {
+                return Ok(None);
+            }
+
+            buf.truncate(read);
+
+            Ok(Some((Bytes::from(buf), reader)))
+        });
+
+        Self::Stream {
+            stream: AttachmentStream::new(Box::pin(stream), length),
+            filename: filename.into(),
+        }
+    }
+
+    /// Creates an attachment which is read from the stream of bytes while
+    /// uploading, so that it is never fully loaded into memory.
+    ///
+    /// The `length` must be the exact number of bytes the stream yields, as
+    /// Discord requires the size of every part of the upload up front.
+    pub fn from_stream<S, B>(stream: S, length: u64, filename: impl Into<String>) -> Self
+    where
+        S: Stream<Item = IoResult<B>> + Send + 'static,
+        B: Into<Bytes> + 'static,
+    {
+        Self::Stream {
+            stream: AttachmentStream::new(Box::pin(stream.map_ok(Into::into)), length),
+            filename: filename.into(),
+        }
+    }
 }
 
 #[cfg(feature = "http")]
@@ -52,6 +228,18 @@ impl<'a> AttachmentType<'a> {
                 let response = client.get(url.clone()).send().await?;
                 response.bytes().await?.to_vec()
             },
+            Self::Stream {
+                stream, ..
+            } => {
+                let mut buf = Vec::new();
+                let mut stream = stream.take()?;
+
+                while let Some(chunk) = stream.next().await {
+                    buf.extend_from_slice(&chunk?);
+                }
+
+                buf
+            },
         };
         Ok(data)
     }
@@ -63,6 +251,9 @@ impl<'a> AttachmentType<'a> {
             }
             | Self::File {
                 filename, ..
+            }
+            | Self::Stream {
+                filename, ..
             } => Ok(Some(filename.to_string())),
             Self::Path(path) => {
                 Ok(path.file_name().map(|filename| filename.to_string_lossy().to_string()))
@@ -141,4 +332,36 @@ mod test {
             AttachmentType::Image(_)
         ));
     }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_stream_is_read_once() {
+        let data = vec![7; 200 * 1024];
+        let attachment =
+            AttachmentType::from_reader(std::io::Cursor::new(data.clone()), 200 * 1024, "a.bin");
+        let client = reqwest::Client::new();
+
+        assert_eq!(attachment.filename().unwrap().as_deref(), Some("a.bin"));
+        assert_eq!(attachment.clone().data(&client).await.unwrap(), data);
+        assert!(attachment.data(&client).await.is_err());
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_stream_need_not_be_sync() {
+        use futures::StreamExt;
+
+        // `Cell` is `Send`, but not `Sync`.
+        let cell = std::cell::Cell::new(0);
+        let chunks = vec![Ok::<_, std::io::Error>(vec![1, 2]), Ok(vec![3])];
+        let stream = futures::stream::iter(chunks).map(move |chunk| {
+            cell.set(cell.get() + 1);
+            chunk
+        });
+
+        let attachment = AttachmentType::from_stream(stream, 3, "a.bin");
+        let client = reqwest::Client::new();
+
+        assert_eq!(attachment.data(&client).await.unwrap(), [1, 2, 3]);
+    }
 }
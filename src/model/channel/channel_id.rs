@@ -37,6 +37,8 @@ use crate::json::{self, json};
 use crate::model::channel::AttachmentType;
 use crate::model::prelude::*;
 #[cfg(feature = "model")]
+use crate::model::Timestamp;
+#[cfg(feature = "model")]
 use crate::utils::encode_image;
 
 #[cfg(feature = "model")]
@@ -677,6 +679,25 @@ impl ChannelId {
             .await
     }
 
+    /// Streams over all [`User`]s who have reacted to a [`Message`] with a
+    /// certain [`Emoji`], sorted by their Id.
+    ///
+    /// This is accomplished and equivalent to repeated calls to
+    /// [`Self::reaction_users`]. A buffer of at most 100 users is used to
+    /// reduce the number of calls necessary.
+    ///
+    /// **Note**: Requires the [Read Message History] permission.
+    ///
+    /// [Read Message History]: Permissions::READ_MESSAGE_HISTORY
+    pub fn reaction_users_iter<H: AsRef<Http>>(
+        self,
+        http: H,
+        message_id: impl Into<MessageId>,
+        reaction_type: impl Into<ReactionType>,
+    ) -> impl Stream<Item = Result<User>> {
+        ReactionUsersIter::<H>::stream(http, self, message_id.into(), reaction_type.into())
+    }
+
     /// Sends a message with just the given message content in the channel.
     ///
     /// # Errors
@@ -1154,6 +1175,9 @@ impl ChannelId {
 
     /// Gets private archived threads of a channel.
     ///
+    /// Discord expects `before` to be a timestamp, see
+    /// [`Self::get_archived_private_threads_before`].
+    ///
     /// # Errors
     ///
     /// It may return an [`Error::Http`] if the bot doesn't have the
@@ -1167,8 +1191,28 @@ impl ChannelId {
         http.as_ref().get_channel_archived_private_threads(self.0, before, limit).await
     }
 
+    /// Gets private archived threads of a channel, most recently archived first.
+    ///
+    /// Only threads archived before the `before` timestamp are returned.
+    ///
+    /// # Errors
+    ///
+    /// It may return an [`Error::Http`] if the bot doesn't have the
+    /// permission to get it.
+    pub async fn get_archived_private_threads_before(
+        &self,
+        http: impl AsRef<Http>,
+        before: Option<Timestamp>,
+        limit: Option<u64>,
+    ) -> Result<ThreadsData> {
+        http.as_ref().get_channel_archived_private_threads_before(self.0, before, limit).await
+    }
+
     /// Gets public archived threads of a channel.
     ///
+    /// Discord expects `before` to be a timestamp, see
+    /// [`Self::get_archived_public_threads_before`].
+    ///
     /// # Errors
     ///
     /// It may return an [`Error::Http`] if the bot doesn't have the
@@ -1182,6 +1226,23 @@ impl ChannelId {
         http.as_ref().get_channel_archived_public_threads(self.0, before, limit).await
     }
 
+    /// Gets public archived threads of a channel, most recently archived first.
+    ///
+    /// Only threads archived before the `before` timestamp are returned.
+    ///
+    /// # Errors
+    ///
+    /// It may return an [`Error::Http`] if the bot doesn't have the
+    /// permission to get it.
+    pub async fn get_archived_public_threads_before(
+        &self,
+        http: impl AsRef<Http>,
+        before: Option<Timestamp>,
+        limit: Option<u64>,
+    ) -> Result<ThreadsData> {
+        http.as_ref().get_channel_archived_public_threads_before(self.0, before, limit).await
+    }
+
     /// Gets private archived threads joined by the current user of a channel.
     ///
     /// # Errors
@@ -1196,6 +1257,42 @@ impl ChannelId {
     ) -> Result<ThreadsData> {
         http.as_ref().get_channel_joined_archived_private_threads(self.0, before, limit).await
     }
+
+    /// Streams over all archived threads of a channel of the given kind.
+    ///
+    /// This is accomplished and equivalent to repeated calls to
+    /// [`Self::get_archived_public_threads_before`],
+    /// [`Self::get_archived_private_threads_before`] or
+    /// [`Self::get_joined_archived_private_threads`] respectively.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use serenity::model::id::ChannelId;
+    /// # use serenity::http::Http;
+    /// #
+    /// # async fn run() {
+    /// # let channel_id = ChannelId::default();
+    /// # let ctx = Http::new("token");
+    /// use serenity::futures::StreamExt;
+    /// use serenity::model::channel::ArchivedThreadsKind;
+    ///
+    /// let mut threads = channel_id.archived_threads_iter(&ctx, ArchivedThreadsKind::Public).boxed();
+    /// while let Some(thread_result) = threads.next().await {
+    ///     match thread_result {
+    ///         Ok(thread) => println!("{} is archived", thread.name),
+    ///         Err(error) => eprintln!("Uh oh! Error: {}", error),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn archived_threads_iter<H: AsRef<Http>>(
+        self,
+        http: H,
+        kind: ArchivedThreadsKind,
+    ) -> impl Stream<Item = Result<GuildChannel>> {
+        ArchivedThreadsIter::<H>::stream(http, self, kind)
+    }
 }
 
 #[cfg(feature = "model")]
@@ -1354,3 +1451,414 @@ impl<H: AsRef<Http>> MessagesIter<H> {
         })
     }
 }
+
+/// A helper class returned by [`ChannelId::reaction_users_iter`]
+#[derive(Clone, Debug)]
+#[cfg(feature = "model")]
+pub struct ReactionUsersIter<H: AsRef<Http>> {
+    http: H,
+    channel_id: ChannelId,
+    message_id: MessageId,
+    reaction_type: ReactionType,
+    buffer: Vec<User>,
+    after: Option<UserId>,
+    tried_fetch: bool,
+}
+
+#[cfg(feature = "model")]
+impl<H: AsRef<Http>> ReactionUsersIter<H> {
+    fn new(
+        http: H,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        reaction_type: ReactionType,
+    ) -> ReactionUsersIter<H> {
+        ReactionUsersIter {
+            http,
+            channel_id,
+            message_id,
+            reaction_type,
+            buffer: Vec::new(),
+            after: None,
+            tried_fetch: false,
+        }
+    }
+
+    /// Fills the `self.buffer` cache of [`User`]s.
+    ///
+    /// This drops any users that were currently in the buffer, so it should
+    /// only be called when `self.buffer` is empty. Additionally, this updates
+    /// `self.after` so that the next call does not return duplicate items. If
+    /// there are no more users to be fetched, then this marks `self.after` as
+    /// None, indicating that no more calls ought to be made.
+    async fn refresh(&mut self) -> Result<()> {
+        // Number of users to fetch.
+        let grab_size = 100;
+
+        self.buffer = self
+            .channel_id
+            .reaction_users(
+                &self.http,
+                self.message_id,
+                self.reaction_type.clone(),
+                Some(grab_size),
+                self.after,
+            )
+            .await?;
+
+        // Get the last user. If shorter than 100, there are no more results anyway.
+        self.after = self.buffer.get(grab_size as usize - 1).map(|user| user.id);
+
+        // Reverse to optimize pop().
+        self.buffer.reverse();
+
+        self.tried_fetch = true;
+
+        Ok(())
+    }
+
+    /// Streams over all the users who reacted to a message with a certain
+    /// emoji.
+    ///
+    /// This is accomplished and equivalent to repeated calls to
+    /// [`ChannelId::reaction_users`]. A buffer of at most 100 users is used to
+    /// reduce the number of calls necessary.
+    pub fn stream(
+        http: impl AsRef<Http>,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        reaction_type: ReactionType,
+    ) -> impl Stream<Item = Result<User>> {
+        let init_state = ReactionUsersIter::new(http, channel_id, message_id, reaction_type);
+
+        futures::stream::unfold(init_state, |mut state| async {
+            if state.buffer.is_empty() && state.after.is_some() || !state.tried_fetch {
+                if let Err(error) = state.refresh().await {
+                    return Some((Err(error), state));
+                }
+            }
+
+            state.buffer.pop().map(|entry| (Ok(entry), state))
+        })
+    }
+}
+
+/// The archived threads of a channel which [`ChannelId::archived_threads_iter`]
+/// streams over.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg(feature = "model")]
+#[non_exhaustive]
+pub enum ArchivedThreadsKind {
+    /// Public threads, most recently archived first.
+    Public,
+    /// Private threads, most recently archived first.
+    ///
+    /// **Note**: Requires the [Manage Threads] permission.
+    ///
+    /// [Manage Threads]: Permissions::MANAGE_THREADS
+    Private,
+    /// Private threads the current user has joined, with the highest Id first.
+    JoinedPrivate,
+}
+
+/// A helper class returned by [`ChannelId::archived_threads_iter`]
+#[derive(Clone, Debug)]
+#[cfg(feature = "model")]
+pub struct ArchivedThreadsIter<H: AsRef<Http>> {
+    http: H,
+    channel_id: ChannelId,
+    kind: ArchivedThreadsKind,
+    buffer: Vec<GuildChannel>,
+    before_timestamp: Option<Timestamp>,
+    before_id: Option<ChannelId>,
+    has_more: bool,
+    tried_fetch: bool,
+}
+
+#[cfg(feature = "model")]
+impl<H: AsRef<Http>> ArchivedThreadsIter<H> {
+    fn new(http: H, channel_id: ChannelId, kind: ArchivedThreadsKind) -> ArchivedThreadsIter<H> {
+        ArchivedThreadsIter {
+            http,
+            channel_id,
+            kind,
+            buffer: Vec::new(),
+            before_timestamp: None,
+            before_id: None,
+            has_more: false,
+            tried_fetch: false,
+        }
+    }
+
+    /// Fills the `self.buffer` cache of threads.
+    ///
+    /// This drops any threads that were currently in the buffer, so it should
+    /// only be called when `self.buffer` is empty. Additionally, this updates
+    /// the cursors so that the next call does not return duplicate items, and
+    /// `self.has_more` according to whether Discord has any more threads.
+    async fn refresh(&mut self) -> Result<()> {
+        // Number of threads to fetch.
+        let grab_size = Some(100);
+
+        let http = self.http.as_ref();
+        let channel_id = self.channel_id.0;
+        let data = match self.kind {
+            ArchivedThreadsKind::Public => {
+                http.get_channel_archived_public_threads_before(
+                    channel_id,
+                    self.before_timestamp,
+                    grab_size,
+                )
+                .await?
+            },
+            ArchivedThreadsKind::Private => {
+                http.get_channel_archived_private_threads_before(
+                    channel_id,
+                    self.before_timestamp,
+                    grab_size,
+                )
+                .await?
+            },
+            ArchivedThreadsKind::JoinedPrivate => {
+                http.get_channel_joined_archived_private_threads(
+                    channel_id,
+                    self.before_id.map(|id| id.0),
+                    grab_size,
+                )
+                .await?
+            },
+        };
+
+        self.has_more = data.has_more && !data.threads.is_empty();
+
+        if let Some(last) = data.threads.last() {
+            self.before_id = Some(last.id);
+
+            match last.thread_metadata.and_then(|m| m.archive_timestamp) {
+                Some(timestamp) => self.before_timestamp = Some(timestamp),
+                // Without the archive timestamp, the threads paginated by it
+                // can't be paginated further, and the cursor is kept so as not
+                // to start over from the newest threads.
+                None if self.kind != ArchivedThreadsKind::JoinedPrivate => {
+                    self.has_more = false;
+                },
+                None => {},
+            }
+        }
+        self.buffer = data.threads;
+
+        // Reverse to optimize pop().
+        self.buffer.reverse();
+
+        self.tried_fetch = true;
+
+        Ok(())
+    }
+
+    /// Streams over all the archived threads of a channel of the given kind.
+    ///
+    /// Only the threads themselves are returned, use
+    /// [`ChannelId::get_archived_public_threads_before`] and its siblings to
+    /// also get the current user's thread members.
+    pub fn stream(
+        http: impl AsRef<Http>,
+        channel_id: ChannelId,
+        kind: ArchivedThreadsKind,
+    ) -> impl Stream<Item = Result<GuildChannel>> {
+        let init_state = ArchivedThreadsIter::new(http, channel_id, kind);
+
+        futures::stream::unfold(init_state, |mut state| async {
+            if state.buffer.is_empty() && state.has_more || !state.tried_fetch {
+                if let Err(error) = state.refresh().await {
+                    return Some((Err(error), state));
+                }
+            }
+
+            state.buffer.pop().map(|entry| (Ok(entry), state))
+        })
+    }
+}
+
+#[cfg(all(test, feature = "model"))]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use futures::StreamExt;
+    use http_crate::response::Builder;
+    use reqwest::{Request, Response};
+    use serde_json::json;
+
+    use super::ArchivedThreadsKind;
+    use crate::http::{HttpBuilder, Transport};
+    use crate::model::channel::ReactionType;
+    use crate::model::id::ChannelId;
+    use crate::model::Timestamp;
+
+    fn query(request: &Request, name: &str) -> Option<String> {
+        request.url().query_pairs().find(|(key, _)| key == name).map(|(_, value)| value.into())
+    }
+
+    fn user(id: u64) -> serde_json::Value {
+        json!({
+            "id": id.to_string(),
+            "username": "user",
+            "discriminator": "0001",
+            "avatar": null,
+        })
+    }
+
+    /// Serves 250 reaction users in pages of at most the requested limit.
+    #[derive(Default)]
+    struct ReactionUsers {
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for Arc<ReactionUsers> {
+        async fn execute(&self, request: Request) -> crate::Result<Response> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+
+            let after = query(&request, "after").map_or(0, |after| after.parse().unwrap());
+            let limit = query(&request, "limit").map_or(25, |limit| limit.parse().unwrap());
+
+            let users = (after + 1..=250).take(limit).map(user).collect::<Vec<_>>();
+            let body = serde_json::to_vec(&users).unwrap();
+
+            Ok(Builder::new().status(200).body(body).unwrap().into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reaction_users_iter() {
+        let transport = Arc::new(ReactionUsers::default());
+        let http = HttpBuilder::new("token").transport(Arc::clone(&transport)).build();
+
+        let reaction = ReactionType::Unicode("\u{1f44d}".to_string());
+        let users = ChannelId(1).reaction_users_iter(&http, 2, reaction).collect::<Vec<_>>().await;
+
+        assert_eq!(users.len(), 250);
+        assert!(users
+            .iter()
+            .enumerate()
+            .all(|(i, user)| user.as_ref().unwrap().id == i as u64 + 1));
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 3);
+    }
+
+    /// Serves 250 archived threads, the thread with Id `n` being archived at
+    /// `n` minutes past the epoch, in pages of at most the requested limit.
+    #[derive(Default)]
+    struct ArchivedThreads {
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for Arc<ArchivedThreads> {
+        async fn execute(&self, request: Request) -> crate::Result<Response> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+
+            assert!(request.url().path().ends_with("/threads/archived/public"));
+
+            let before = query(&request, "before")
+                .map_or(251, |before| Timestamp::parse(&before).unwrap().unix_timestamp() / 60);
+            let limit = query(&request, "limit").map_or(50, |limit| limit.parse().unwrap());
+
+            let ids = (1..before).rev().take(limit).collect::<Vec<i64>>();
+            let threads = ids.iter().map(|&id| {
+                json!({
+                    "id": id.to_string(),
+                    "type": 11,
+                    "guild_id": "2",
+                    "name": "thread",
+                    "position": 0,
+                    "thread_metadata": {
+                        "archived": true,
+                        "auto_archive_duration": 60,
+                        "archive_timestamp": Timestamp::from_unix_timestamp(id * 60).unwrap(),
+                        "locked": false,
+                    },
+                })
+            });
+            let body = json!({
+                "threads": threads.collect::<Vec<_>>(),
+                "members": [],
+                "has_more": ids.last().map_or(false, |&id| id > 1),
+            });
+
+            Ok(Builder::new().status(200).body(serde_json::to_vec(&body).unwrap()).unwrap().into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_archived_threads_iter() {
+        let transport = Arc::new(ArchivedThreads::default());
+        let http = HttpBuilder::new("token").transport(Arc::clone(&transport)).build();
+
+        let threads = ChannelId(1)
+            .archived_threads_iter(&http, ArchivedThreadsKind::Public)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(threads.len(), 250);
+        assert!(threads
+            .iter()
+            .enumerate()
+            .all(|(i, thread)| thread.as_ref().unwrap().id == 250 - i as u64));
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 3);
+    }
+
+    /// Serves a page of archived threads claiming there are more, the last of
+    /// which lacks its archive timestamp.
+    #[derive(Default)]
+    struct UntimestampedThreads {
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for Arc<UntimestampedThreads> {
+        async fn execute(&self, _: Request) -> crate::Result<Response> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+
+            let threads = [2, 1].iter().map(|&id| {
+                json!({
+                    "id": id.to_string(),
+                    "type": 11,
+                    "guild_id": "2",
+                    "name": "thread",
+                    "position": 0,
+                    "thread_metadata": {
+                        "archived": true,
+                        "auto_archive_duration": 60,
+                        "archive_timestamp": (id == 2)
+                            .then(|| Timestamp::from_unix_timestamp(id * 60).unwrap()),
+                        "locked": false,
+                    },
+                })
+            });
+            let body = json!({
+                "threads": threads.collect::<Vec<_>>(),
+                "members": [],
+                "has_more": true,
+            });
+
+            Ok(Builder::new().status(200).body(serde_json::to_vec(&body).unwrap()).unwrap().into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_archived_threads_iter_without_timestamp() {
+        let transport = Arc::new(UntimestampedThreads::default());
+        let http = HttpBuilder::new("token").transport(Arc::clone(&transport)).build();
+
+        // The stream ends instead of repeating the threads.
+        let threads = ChannelId(1)
+            .archived_threads_iter(&http, ArchivedThreadsKind::Public)
+            .take(10)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(threads.len(), 2);
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 1);
+    }
+}
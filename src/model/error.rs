@@ -170,6 +170,17 @@ pub enum Error {
     NoStickerFileSet,
     /// When attempting to send a message with over 3 stickers.
     StickerAmount,
+    /// Indicates that an attachment is larger than the maximum size allowed
+    /// for its download.
+    ///
+    /// The size of the attachment, or the number of bytes received before the
+    /// download was aborted, is provided.
+    AttachmentTooLarge(u64),
+    /// Indicates that the content type of an attachment is not one of those
+    /// allowed for its download.
+    ///
+    /// The content type, if known, is provided.
+    InvalidContentType(Option<String>),
 }
 
 impl Error {
@@ -216,6 +227,8 @@ impl fmt::Display for Error {
             Self::DeleteNitroSticker => f.write_str("Cannot delete an official sticker."),
             Self::NoStickerFileSet => f.write_str("Sticker file is not set."),
             Self::StickerAmount => f.write_str("Too many stickers in a message."),
+            Self::AttachmentTooLarge(_) => f.write_str("Attachment too large."),
+            Self::InvalidContentType(_) => f.write_str("Attachment has a disallowed content type."),
         }
     }
 }
@@ -189,6 +189,10 @@ pub struct GuildMembersChunkEvent {
     pub members: HashMap<UserId, Member>,
     pub chunk_index: u32,
     pub chunk_count: u32,
+    /// The IDs of the requested users which were not found in the guild.
+    pub not_found: Vec<UserId>,
+    /// The presences of the members in the chunk, if they were requested.
+    pub presences: HashMap<UserId, Presence>,
     pub nonce: Option<String>,
 }
 
@@ -201,6 +205,8 @@ impl<'de> Deserialize<'de> for GuildMembersChunkEvent {
             ChunkIndex,
             ChunkCount,
             Members,
+            NotFound,
+            Presences,
             Nonce,
             Unknown(String),
         }
@@ -219,6 +225,8 @@ impl<'de> Deserialize<'de> for GuildMembersChunkEvent {
                 let mut chunk_index = None;
                 let mut chunk_count = None;
                 let mut members = None;
+                let mut not_found = None;
+                let mut presences = None;
                 let mut nonce = None;
 
                 while let Some(key) = map.next_key()? {
@@ -247,6 +255,18 @@ impl<'de> Deserialize<'de> for GuildMembersChunkEvent {
                             }
                             members = Some(map.next_value::<Vec<InterimMember>>()?);
                         },
+                        Field::NotFound => {
+                            if not_found.is_some() {
+                                return Err(DeError::duplicate_field("not_found"));
+                            }
+                            not_found = Some(map.next_value()?);
+                        },
+                        Field::Presences => {
+                            if presences.is_some() {
+                                return Err(DeError::duplicate_field("presences"));
+                            }
+                            presences = Some(map.next_value::<Vec<Presence>>()?);
+                        },
                         Field::Nonce => {
                             if nonce.is_some() {
                                 return Err(DeError::duplicate_field("nonce"));
@@ -276,17 +296,30 @@ impl<'de> Deserialize<'de> for GuildMembersChunkEvent {
                     })
                     .collect();
 
+                let presences =
+                    presences.unwrap_or_default().into_iter().map(|p| (p.user.id, p)).collect();
+
                 Ok(GuildMembersChunkEvent {
                     guild_id,
                     members,
                     chunk_index,
                     chunk_count,
+                    not_found: not_found.unwrap_or_default(),
+                    presences,
                     nonce,
                 })
             }
         }
 
-        const FIELDS: &[&str] = &["guild_id", "chunk_index", "chunk_count", "members", "nonce"];
+        const FIELDS: &[&str] = &[
+            "guild_id",
+            "chunk_index",
+            "chunk_count",
+            "members",
+            "not_found",
+            "presences",
+            "nonce",
+        ];
         deserializer.deserialize_struct("GuildMembersChunkEvent", FIELDS, GuildMembersChunkVisitor)
     }
 }
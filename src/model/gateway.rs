@@ -529,6 +529,9 @@ pub struct Ready {
     #[serde(default, with = "private_channels")]
     pub private_channels: HashMap<ChannelId, Channel>,
     pub session_id: String,
+    /// The URL to connect to when resuming the session.
+    #[serde(default)]
+    pub resume_gateway_url: Option<String>,
     pub shard: Option<[u64; 2]>,
     #[serde(default, rename = "_trace")]
     pub trace: Vec<String>,
@@ -270,6 +270,9 @@ impl GuildId {
 
     /// Gets a list of the guild's bans.
     ///
+    /// At most 1000 bans are returned, use [`Self::bans_iter`] to get all of
+    /// them.
+    ///
     /// **Note**: Requires the [Ban Members] permission.
     ///
     /// # Errors
@@ -282,6 +285,39 @@ impl GuildId {
         http.as_ref().get_bans(self.0).await
     }
 
+    /// Streams over all the guild's bans, sorted by the banned user's Id.
+    ///
+    /// This is accomplished and equivalent to repeated calls to
+    /// [`Http::get_bans_paginated`]. A buffer of at most 1,000 bans is used to
+    /// reduce the number of calls necessary.
+    ///
+    /// **Note**: Requires the [Ban Members] permission.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use serenity::model::id::GuildId;
+    /// # use serenity::http::Http;
+    /// #
+    /// # async fn run() {
+    /// # let guild_id = GuildId::default();
+    /// # let ctx = Http::new("token");
+    /// use serenity::futures::StreamExt;
+    ///
+    /// let mut bans = guild_id.bans_iter(&ctx).boxed();
+    /// while let Some(ban_result) = bans.next().await {
+    ///     match ban_result {
+    ///         Ok(ban) => println!("{} is banned", ban.user.tag()),
+    ///         Err(error) => eprintln!("Uh oh!  Error: {}", error),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// [Ban Members]: Permissions::BAN_MEMBERS
+    pub fn bans_iter<H: AsRef<Http>>(self, http: H) -> impl Stream<Item = Result<Ban>> {
+        BansIter::<H>::stream(http, self)
+    }
+
     /// Gets a list of the guild's audit log entries
     ///
     /// **Note**: Requires the [View Audit Log] permission.
@@ -306,6 +342,46 @@ impl GuildId {
             .await
     }
 
+    /// Streams over all of the guild's audit log entries, newest first,
+    /// optionally filtered by the type of action or the user who performed
+    /// it.
+    ///
+    /// This is accomplished and equivalent to repeated calls to
+    /// [`Self::audit_logs`]. A buffer of at most 100 entries is used to reduce
+    /// the number of calls necessary.
+    ///
+    /// **Note**: Requires the [View Audit Log] permission.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use serenity::model::id::GuildId;
+    /// # use serenity::http::Http;
+    /// #
+    /// # async fn run() {
+    /// # let guild_id = GuildId::default();
+    /// # let ctx = Http::new("token");
+    /// use serenity::futures::StreamExt;
+    ///
+    /// let mut entries = guild_id.audit_logs_iter(&ctx, None, None).boxed();
+    /// while let Some(entry_result) = entries.next().await {
+    ///     match entry_result {
+    ///         Ok(entry) => println!("{} did {:?}", entry.user_id, entry.action),
+    ///         Err(error) => eprintln!("Uh oh!  Error: {}", error),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// [View Audit Log]: Permissions::VIEW_AUDIT_LOG
+    pub fn audit_logs_iter<H: AsRef<Http>>(
+        self,
+        http: H,
+        action_type: Option<u8>,
+        user_id: Option<UserId>,
+    ) -> impl Stream<Item = Result<AuditLogEntry>> {
+        AuditLogsIter::<H>::stream(http, self, action_type, user_id)
+    }
+
     /// Gets all of the guild's channels over the REST API.
     ///
     /// # Errors
@@ -1350,6 +1426,26 @@ impl GuildId {
             .await
     }
 
+    /// Streams over all users interested in the specified event, sorted by
+    /// their Id. See [`Http::get_scheduled_event_users`] for details on
+    /// `with_member`.
+    ///
+    /// This is accomplished and equivalent to repeated calls to
+    /// [`Self::scheduled_event_users_optioned`]. A buffer of at most 100 users
+    /// is used to reduce the number of calls necessary.
+    ///
+    /// **Note**: Requires the [Manage Events] permission.
+    ///
+    /// [Manage Events]: Permissions::MANAGE_EVENTS
+    pub fn scheduled_event_users_iter<H: AsRef<Http>>(
+        self,
+        http: H,
+        event_id: impl Into<ScheduledEventId>,
+        with_member: Option<bool>,
+    ) -> impl Stream<Item = Result<ScheduledEventUser>> {
+        ScheduledEventUsersIter::<H>::stream(http, self, event_id.into(), with_member)
+    }
+
     /// Returns the Id of the shard associated with the guild.
     ///
     /// When the cache is enabled this will automatically retrieve the total
@@ -1887,6 +1983,277 @@ impl<H: AsRef<Http>> MembersIter<H> {
     }
 }
 
+/// A helper class returned by [`GuildId::bans_iter`]
+#[derive(Clone, Debug)]
+#[cfg(feature = "model")]
+pub struct BansIter<H: AsRef<Http>> {
+    guild_id: GuildId,
+    http: H,
+    buffer: Vec<Ban>,
+    after: Option<UserId>,
+    tried_fetch: bool,
+}
+
+#[cfg(feature = "model")]
+impl<H: AsRef<Http>> BansIter<H> {
+    fn new(guild_id: GuildId, http: H) -> BansIter<H> {
+        BansIter {
+            guild_id,
+            http,
+            buffer: Vec::new(),
+            after: None,
+            tried_fetch: false,
+        }
+    }
+
+    /// Fills the `self.buffer` cache of bans.
+    ///
+    /// This drops any bans that were currently in the buffer, so it should
+    /// only be called when `self.buffer` is empty.  Additionally, this updates
+    /// `self.after` so that the next call does not return duplicate items.  If
+    /// there are no more bans to be fetched, then this marks `self.after` as
+    /// None, indicating that no more calls ought to be made.
+    async fn refresh(&mut self) -> Result<()> {
+        // Number of bans to fetch
+        let grab_size: u64 = 1000;
+
+        let target = self.after.map(UserPagination::After);
+        self.buffer =
+            self.http.as_ref().get_bans_paginated(self.guild_id.0, target, Some(grab_size)).await?;
+
+        // Get the last ban.  If shorter than 1000, there are no more results anyway
+        self.after = self.buffer.get(grab_size as usize - 1).map(|ban| ban.user.id);
+
+        // Reverse to optimize pop()
+        self.buffer.reverse();
+
+        self.tried_fetch = true;
+
+        Ok(())
+    }
+
+    /// Streams over all the bans in a guild.
+    ///
+    /// This is accomplished and equivalent to repeated calls to
+    /// [`Http::get_bans_paginated`]. A buffer of at most 1,000 bans is used to
+    /// reduce the number of calls necessary.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use serenity::model::id::GuildId;
+    /// # use serenity::http::Http;
+    /// #
+    /// # async fn run() {
+    /// # let guild_id = GuildId::default();
+    /// # let ctx = Http::new("token");
+    /// use serenity::futures::StreamExt;
+    /// use serenity::model::guild::BansIter;
+    ///
+    /// let mut bans = BansIter::<Http>::stream(&ctx, guild_id).boxed();
+    /// while let Some(ban_result) = bans.next().await {
+    ///     match ban_result {
+    ///         Ok(ban) => println!("{} is banned", ban.user.tag()),
+    ///         Err(error) => eprintln!("Uh oh!  Error: {}", error),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn stream(http: impl AsRef<Http>, guild_id: GuildId) -> impl Stream<Item = Result<Ban>> {
+        let init_state = BansIter::new(guild_id, http);
+
+        futures::stream::unfold(init_state, |mut state| async {
+            if state.buffer.is_empty() && state.after.is_some() || !state.tried_fetch {
+                if let Err(error) = state.refresh().await {
+                    return Some((Err(error), state));
+                }
+            }
+
+            state.buffer.pop().map(|entry| (Ok(entry), state))
+        })
+    }
+}
+
+/// A helper class returned by [`GuildId::audit_logs_iter`]
+#[derive(Debug)]
+#[cfg(feature = "model")]
+pub struct AuditLogsIter<H: AsRef<Http>> {
+    guild_id: GuildId,
+    http: H,
+    action_type: Option<u8>,
+    user_id: Option<UserId>,
+    buffer: Vec<AuditLogEntry>,
+    before: Option<AuditLogEntryId>,
+    tried_fetch: bool,
+}
+
+#[cfg(feature = "model")]
+impl<H: AsRef<Http>> AuditLogsIter<H> {
+    fn new(
+        guild_id: GuildId,
+        http: H,
+        action_type: Option<u8>,
+        user_id: Option<UserId>,
+    ) -> AuditLogsIter<H> {
+        AuditLogsIter {
+            guild_id,
+            http,
+            action_type,
+            user_id,
+            buffer: Vec::new(),
+            before: None,
+            tried_fetch: false,
+        }
+    }
+
+    /// Fills the `self.buffer` cache of audit log entries.
+    ///
+    /// This drops any entries that were currently in the buffer, so it should
+    /// only be called when `self.buffer` is empty.  Additionally, this updates
+    /// `self.before` so that the next call does not return duplicate items.
+    /// If there are no more entries to be fetched, then this marks
+    /// `self.before` as None, indicating that no more calls ought to be made.
+    async fn refresh(&mut self) -> Result<()> {
+        // Number of entries to fetch
+        let grab_size: u8 = 100;
+
+        self.buffer = self
+            .guild_id
+            .audit_logs(&self.http, self.action_type, self.user_id, self.before, Some(grab_size))
+            .await?
+            .entries;
+
+        // Get the oldest entry.  If shorter than 100, there are no more results anyway
+        self.before = self.buffer.get(grab_size as usize - 1).map(|entry| entry.id);
+
+        // Reverse to optimize pop()
+        self.buffer.reverse();
+
+        self.tried_fetch = true;
+
+        Ok(())
+    }
+
+    /// Streams over the audit log entries of a guild, newest first.
+    ///
+    /// This is accomplished and equivalent to repeated calls to
+    /// [`GuildId::audit_logs`]. A buffer of at most 100 entries is used to
+    /// reduce the number of calls necessary.
+    ///
+    /// The users and webhooks referenced by the entries are not included, use
+    /// [`GuildId::audit_logs`] if those are needed.
+    pub fn stream(
+        http: impl AsRef<Http>,
+        guild_id: GuildId,
+        action_type: Option<u8>,
+        user_id: Option<UserId>,
+    ) -> impl Stream<Item = Result<AuditLogEntry>> {
+        let init_state = AuditLogsIter::new(guild_id, http, action_type, user_id);
+
+        futures::stream::unfold(init_state, |mut state| async {
+            if state.buffer.is_empty() && state.before.is_some() || !state.tried_fetch {
+                if let Err(error) = state.refresh().await {
+                    return Some((Err(error), state));
+                }
+            }
+
+            state.buffer.pop().map(|entry| (Ok(entry), state))
+        })
+    }
+}
+
+/// A helper class returned by [`GuildId::scheduled_event_users_iter`]
+#[derive(Clone, Debug)]
+#[cfg(feature = "model")]
+pub struct ScheduledEventUsersIter<H: AsRef<Http>> {
+    guild_id: GuildId,
+    event_id: ScheduledEventId,
+    http: H,
+    with_member: Option<bool>,
+    buffer: Vec<ScheduledEventUser>,
+    after: Option<UserId>,
+    tried_fetch: bool,
+}
+
+#[cfg(feature = "model")]
+impl<H: AsRef<Http>> ScheduledEventUsersIter<H> {
+    fn new(
+        guild_id: GuildId,
+        event_id: ScheduledEventId,
+        http: H,
+        with_member: Option<bool>,
+    ) -> ScheduledEventUsersIter<H> {
+        ScheduledEventUsersIter {
+            guild_id,
+            event_id,
+            http,
+            with_member,
+            buffer: Vec::new(),
+            after: None,
+            tried_fetch: false,
+        }
+    }
+
+    /// Fills the `self.buffer` cache of interested users.
+    ///
+    /// This drops any users that were currently in the buffer, so it should
+    /// only be called when `self.buffer` is empty.  Additionally, this updates
+    /// `self.after` so that the next call does not return duplicate items.  If
+    /// there are no more users to be fetched, then this marks `self.after` as
+    /// None, indicating that no more calls ought to be made.
+    async fn refresh(&mut self) -> Result<()> {
+        // Number of users to fetch
+        let grab_size: u64 = 100;
+
+        self.buffer = self
+            .guild_id
+            .scheduled_event_users_optioned(
+                &self.http,
+                self.event_id,
+                Some(grab_size),
+                // Start from the lowest Id, so that every page continues
+                // where the previous one ended.
+                Some(UserPagination::After(self.after.unwrap_or_default())),
+                self.with_member,
+            )
+            .await?;
+
+        // Get the last user.  If shorter than 100, there are no more results anyway
+        self.after = self.buffer.get(grab_size as usize - 1).map(|user| user.user.id);
+
+        // Reverse to optimize pop()
+        self.buffer.reverse();
+
+        self.tried_fetch = true;
+
+        Ok(())
+    }
+
+    /// Streams over all the users interested in a scheduled event.
+    ///
+    /// This is accomplished and equivalent to repeated calls to
+    /// [`GuildId::scheduled_event_users_optioned`]. A buffer of at most 100
+    /// users is used to reduce the number of calls necessary.
+    pub fn stream(
+        http: impl AsRef<Http>,
+        guild_id: GuildId,
+        event_id: ScheduledEventId,
+        with_member: Option<bool>,
+    ) -> impl Stream<Item = Result<ScheduledEventUser>> {
+        let init_state = ScheduledEventUsersIter::new(guild_id, event_id, http, with_member);
+
+        futures::stream::unfold(init_state, |mut state| async {
+            if state.buffer.is_empty() && state.after.is_some() || !state.tried_fetch {
+                if let Err(error) = state.refresh().await {
+                    return Some((Err(error), state));
+                }
+            }
+
+            state.buffer.pop().map(|entry| (Ok(entry), state))
+        })
+    }
+}
+
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
 #[non_exhaustive]
 pub enum GuildWidgetStyle {
@@ -1908,3 +2275,169 @@ impl fmt::Display for GuildWidgetStyle {
         }
     }
 }
+
+#[cfg(all(test, feature = "model"))]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use futures::StreamExt;
+    use http_crate::response::Builder;
+    use reqwest::{Request, Response};
+
+    use crate::http::{HttpBuilder, Transport};
+    use crate::model::id::GuildId;
+
+    fn query(request: &Request, name: &str) -> Option<u64> {
+        request
+            .url()
+            .query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.parse().unwrap())
+    }
+
+    fn user(id: u64) -> serde_json::Value {
+        serde_json::json!({
+            "id": id.to_string(),
+            "username": "user",
+            "discriminator": "0001",
+            "avatar": null,
+        })
+    }
+
+    /// Serves 1500 bans in pages of at most the requested limit.
+    #[derive(Default)]
+    struct Bans {
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for Arc<Bans> {
+        async fn execute(&self, request: Request) -> crate::Result<Response> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+
+            let after = query(&request, "after").unwrap_or(0);
+            let limit = query(&request, "limit").unwrap_or(1000);
+
+            let bans = (after + 1..=1500).take(limit as usize).map(|id| {
+                serde_json::json!({
+                    "reason": null,
+                    "user": user(id),
+                })
+            });
+            let body = serde_json::to_vec(&bans.collect::<Vec<_>>()).unwrap();
+
+            Ok(Builder::new().status(200).body(body).unwrap().into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bans_iter() {
+        let transport = Arc::new(Bans::default());
+        let http = HttpBuilder::new("token").transport(Arc::clone(&transport)).build();
+
+        let bans = GuildId(1).bans_iter(&http).collect::<Vec<_>>().await;
+
+        assert_eq!(bans.len(), 1500);
+        assert!(bans
+            .iter()
+            .enumerate()
+            .all(|(i, ban)| ban.as_ref().unwrap().user.id == i as u64 + 1));
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 2);
+    }
+
+    /// Serves 250 audit log entries, newest first, in pages of at most the
+    /// requested limit.
+    #[derive(Default)]
+    struct AuditLogs {
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for Arc<AuditLogs> {
+        async fn execute(&self, request: Request) -> crate::Result<Response> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+
+            let before = query(&request, "before").unwrap_or(251);
+            let limit = query(&request, "limit").unwrap_or(50);
+
+            let entries = (1..before).rev().take(limit as usize).map(|id| {
+                serde_json::json!({
+                    "id": id.to_string(),
+                    "action_type": 1,
+                    "user_id": "1",
+                    "target_id": null,
+                    "reason": null,
+                    "changes": null,
+                    "options": null,
+                })
+            });
+            let body = serde_json::json!({
+                "audit_log_entries": entries.collect::<Vec<_>>(),
+                "users": [],
+                "webhooks": [],
+            });
+
+            Ok(Builder::new().status(200).body(serde_json::to_vec(&body).unwrap()).unwrap().into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_audit_logs_iter() {
+        let transport = Arc::new(AuditLogs::default());
+        let http = HttpBuilder::new("token").transport(Arc::clone(&transport)).build();
+
+        let entries = GuildId(1).audit_logs_iter(&http, None, None).collect::<Vec<_>>().await;
+
+        assert_eq!(entries.len(), 250);
+        assert!(entries
+            .iter()
+            .enumerate()
+            .all(|(i, entry)| entry.as_ref().unwrap().id == 250 - i as u64));
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 3);
+    }
+
+    /// Serves 250 users interested in a scheduled event, in pages of at most
+    /// the requested limit.
+    #[derive(Default)]
+    struct ScheduledEventUsers {
+        requests: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for Arc<ScheduledEventUsers> {
+        async fn execute(&self, request: Request) -> crate::Result<Response> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+
+            let after = query(&request, "after").unwrap_or(0);
+            let limit = query(&request, "limit").unwrap_or(100);
+
+            let users = (after + 1..=250).take(limit as usize).map(|id| {
+                serde_json::json!({
+                    "guild_scheduled_event_id": "2",
+                    "user": user(id),
+                    "member": null,
+                })
+            });
+            let body = serde_json::to_vec(&users.collect::<Vec<_>>()).unwrap();
+
+            Ok(Builder::new().status(200).body(body).unwrap().into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_event_users_iter() {
+        let transport = Arc::new(ScheduledEventUsers::default());
+        let http = HttpBuilder::new("token").transport(Arc::clone(&transport)).build();
+
+        let users = GuildId(1).scheduled_event_users_iter(&http, 2, None).collect::<Vec<_>>().await;
+
+        assert_eq!(users.len(), 250);
+        assert!(users
+            .iter()
+            .enumerate()
+            .all(|(i, user)| user.as_ref().unwrap().user.id == i as u64 + 1));
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 3);
+    }
+}